@@ -5,6 +5,7 @@ extern crate failure;
 extern crate bit_vec;
 extern crate hex;
 extern crate exonum_sodiumoxide as sodiumoxide;
+extern crate libsodium_sys;
 
 extern crate byteorder;
 extern crate vec_map;
@@ -18,6 +19,8 @@ extern crate uuid;
 extern crate chrono;
 
 extern crate rust_decimal;
+#[cfg(test)]
+extern crate rand;
 
 #[macro_use]
 pub mod encoding;
@@ -48,6 +51,7 @@ fn create_status(c: &mut Criterion) {
             Fun::new("new message with rust struct",
                          |b, _| {
                              let (p, s) = crypto::gen_keypair_from_seed(&Seed::new([210; 32]));
+                             let chain_id = crypto::chain_id(crypto::DEFAULT_NETWORK);
                              b.iter(|| {
                                  let message = Message::new(
                                      WithoutEncodingStatus {
@@ -55,17 +59,20 @@ fn create_status(c: &mut Criterion) {
                                          last_hash: Hash::zero()
                                      },
                                      p,
-                                     &s);
+                                     &chain_id,
+                                     &s).unwrap();
                              })
                          }),
         Fun::new("new message with encoding on types",
                          |b, _| {
                              let (p, s) = crypto::gen_keypair_from_seed(&Seed::new([210; 32]));
+                             let chain_id = crypto::chain_id(crypto::DEFAULT_NETWORK);
                              b.iter(|| {
                                  let message = Message::new(
                                      Status::new(types::Height(0), &Hash::zero()),
                                      p,
-                                     &s);
+                                     &chain_id,
+                                     &s).unwrap();
                              })
                          }))
     };
@@ -76,11 +83,13 @@ fn create_status(c: &mut Criterion) {
         Fun::new("old message",
                          |b, _| {
                              let (p, s) = crypto::gen_keypair_from_seed(&Seed::new([210; 32]));
+                             let chain_id = crypto::chain_id(crypto::DEFAULT_NETWORK);
                              b.iter(|| {
                                  Status::new(&p,
                                              types::Height(0),
                                              &Hash::zero(),
-                                             &s);
+                                             &chain_id,
+                                             &s).unwrap();
                              }
                              )
                          }
@@ -98,16 +107,20 @@ fn verify_status(c: &mut Criterion) {
         Fun::new("new status get",
                          |b, _| {
                              let (p, s) = crypto::gen_keypair_from_seed(&Seed::new([210; 32]));
+                             let chain_id = crypto::chain_id(crypto::DEFAULT_NETWORK);
                              let msigned: SignedMessage = Message::new(
                                  WithoutEncodingStatus {
                                      height: types::Height(0),
                                      last_hash: Hash::zero()
                                  }, p,
-                                 &s).into();
+                                 &chain_id,
+                                 &s).unwrap().into();
                              let message = msigned.to_vec();
 
                              b.iter(|| {
-                                 let signed = SignedMessage::verify_buffer(&message).unwrap().into_message();
+                                 let signed = SignedMessage::verify_buffer(&message, &chain_id)
+                                     .unwrap()
+                                     .into_message();
                                  let (proto, message) = signed.into_parts();
                                  match proto {
                                      Protocol::WithoutEncodingStatus(s) => {},
@@ -124,15 +137,17 @@ fn verify_status(c: &mut Criterion) {
         Fun::new("old status get",
                          |b, _| {
                              let (p, s) = crypto::gen_keypair_from_seed(&Seed::new([210; 32]));
+                             let chain_id = crypto::chain_id(crypto::DEFAULT_NETWORK);
                              let message = Status::new(&p,
                                          types::Height(0),
                                          &Hash::zero(),
-                                         &s).raw().clone();
+                                         &chain_id,
+                                         &s).unwrap().raw().clone();
                              b.iter(|| {
                                  let any = Any::from_raw(message.clone());
                                  match any {
                                      Ok(Any::Status(s)) => {
-                                         if !s.verify_signature(s.from()){
+                                         if !s.verify_signature(&chain_id, s.from()){
                                              panic!();
                                          }
                                      } ,
@@ -148,8 +163,78 @@ fn verify_status(c: &mut Criterion) {
     Criterion::default().bench_functions("verify_message", funs, ());
 }
 
+fn verify_batch(c: &mut Criterion) {
+    use criterion::Fun;
+    use crypto::{Hash, Seed};
+    use messages::{SignedMessage, WithoutEncodingStatus};
+
+    let chain_id = crypto::chain_id(crypto::DEFAULT_NETWORK);
+    let buffers = (0..256_u64).map(|i| {
+        let (p, s) = crypto::gen_keypair_from_seed(&Seed::new([i as u8; 32]));
+        SignedMessage::new(WithoutEncodingStatus {
+                               height: types::Height(i),
+                               last_hash: Hash::zero()
+                           },
+                           p,
+                           &chain_id,
+                           &s).unwrap().to_vec()
+    }).collect::<Vec<_>>();
+
+    let sequential = Fun::new("sequential verification",
+                              move |b, buffers: &Vec<Vec<u8>>| {
+                                  b.iter(|| {
+                                      for buffer in buffers {
+                                          SignedMessage::verify_buffer(buffer, &chain_id)
+                                              .unwrap();
+                                      }
+                                  })
+                              });
+    let batch = Fun::new("batch verification",
+                         move |b, buffers: &Vec<Vec<u8>>| {
+                             b.iter(|| {
+                                 SignedMessage::verify_buffers(buffers, &chain_id).unwrap();
+                             })
+                         });
+    let funs = vec![sequential, batch];
+    Criterion::default().bench_functions("verify_256_messages", funs, buffers);
+}
+
+fn hash_algorithms(c: &mut Criterion) {
+    use criterion::Fun;
+    use crypto::HashAlgorithm;
+
+    for &size in &[64_usize, 1024, 64 * 1024] {
+        let data = (0..size).map(|i| i as u8).collect::<Vec<_>>();
+        let funs = [HashAlgorithm::Sha256, HashAlgorithm::Blake2b].iter()
+            .map(|&algorithm| {
+                Fun::new(algorithm.name(), move |b, data: &Vec<u8>| {
+                    b.iter(|| algorithm.hash(data))
+                })
+            })
+            .collect();
+        Criterion::default().bench_functions(&format!("hash_{}_bytes", size), funs, data);
+    }
+
+    // Streaming of small chunks, as in hashing of the Merkle tree nodes.
+    let chunks = (0..1024_u32).map(|i| crypto::hash(&i.to_le_bytes())).collect::<Vec<_>>();
+    let funs = [HashAlgorithm::Sha256, HashAlgorithm::Blake2b].iter()
+        .map(|&algorithm| {
+            Fun::new(algorithm.name(), move |b, chunks: &Vec<crypto::Hash>| {
+                b.iter(|| {
+                    chunks.iter()
+                        .fold(algorithm.stream(), |stream, chunk| stream.update(chunk.as_ref()))
+                        .hash()
+                })
+            })
+        })
+        .collect();
+    Criterion::default().bench_functions("hash_stream_1024_hashes", funs, chunks);
+}
+
 criterion_group!(benches,
 create_status,
-verify_status
+verify_status,
+verify_batch,
+hash_algorithms
 );
 criterion_main!(benches);
\ No newline at end of file
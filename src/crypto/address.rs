@@ -0,0 +1,440 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Human-readable addresses of public keys and hashes in the
+//! [Bech32](https://github.com/bitcoin/bips/blob/master/bip-0173.mediawiki) format.
+//!
+//! The address consists of the network name, the `1` separator, the kind of the value
+//! (`q` for public keys and `p` for hashes), the value itself and a checksum, for example,
+//! `exonum1q...`. The checksum detects any error affecting up to four characters,
+//! so a mistyped address is rejected instead of being read as another key.
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use super::{Hash, PublicKey};
+
+/// Maximum length of the network name, so the addresses fit into 90 characters
+/// allowed by BIP-173.
+const MAX_NETWORK_LENGTH: usize = 30;
+/// Maximum length of a Bech32 string.
+const MAX_LENGTH: usize = 90;
+/// Length of the checksum in characters.
+const CHECKSUM_LENGTH: usize = 6;
+const SEPARATOR: char = '1';
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const GENERATORS: [u32; 5] = [0x3b6a_57b2, 0x2650_8e6d, 0x1ea1_19fa, 0x3d42_33dd, 0x2a14_62b3];
+
+/// Errors that may occur while parsing an address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddressError {
+    /// The network name is empty, too long or contains characters other than
+    /// lowercase ASCII letters and digits.
+    InvalidNetwork(String),
+    /// The address belongs to another network.
+    NetworkMismatch {
+        /// Network expected by the caller.
+        expected: String,
+        /// Network of the address.
+        actual: String,
+    },
+    /// The character is not allowed at the position.
+    InvalidCharacter {
+        /// Invalid character.
+        c: char,
+        /// Position of the character in the string.
+        index: usize,
+    },
+    /// The address contains both lowercase and uppercase letters.
+    MixedCase,
+    /// The address has no `1` separator.
+    MissingSeparator,
+    /// The address or its value has invalid length.
+    InvalidLength,
+    /// The checksum doesn't match, the address is mistyped.
+    InvalidChecksum,
+    /// The address is an address of another kind of value, for example, of a hash
+    /// instead of a public key.
+    UnexpectedKind(u8),
+}
+
+impl fmt::Display for AddressError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AddressError::InvalidNetwork(ref network) => {
+                write!(f, "Invalid network name `{}`", network)
+            }
+            AddressError::NetworkMismatch {
+                ref expected,
+                ref actual,
+            } => write!(
+                f,
+                "Address of the `{}` network, expected `{}`",
+                actual, expected
+            ),
+            AddressError::InvalidCharacter { c, index } => {
+                write!(f, "Invalid character {:?} at position {}", c, index)
+            }
+            AddressError::MixedCase => write!(f, "Address contains mixed case letters"),
+            AddressError::MissingSeparator => write!(f, "Address has no separator"),
+            AddressError::InvalidLength => write!(f, "Invalid address length"),
+            AddressError::InvalidChecksum => write!(f, "Invalid address checksum"),
+            AddressError::UnexpectedKind(kind) => write!(f, "Unexpected address kind {}", kind),
+        }
+    }
+}
+
+impl Error for AddressError {
+    fn description(&self) -> &str {
+        match *self {
+            AddressError::InvalidNetwork(_) => "Invalid network name",
+            AddressError::NetworkMismatch { .. } => "Address of another network",
+            AddressError::InvalidCharacter { .. } => "Invalid character",
+            AddressError::MixedCase => "Address contains mixed case letters",
+            AddressError::MissingSeparator => "Address has no separator",
+            AddressError::InvalidLength => "Invalid address length",
+            AddressError::InvalidChecksum => "Invalid address checksum",
+            AddressError::UnexpectedKind(_) => "Unexpected address kind",
+        }
+    }
+}
+
+/// Value, which may be represented as an address.
+pub trait AddressPayload: Copy + AsRef<[u8]> {
+    /// Kind of the value, which is the first 5-bit symbol of the address data.
+    const KIND: u8;
+
+    /// Creates the value from bytes, returns `None` if the length is invalid.
+    fn from_bytes(bytes: &[u8]) -> Option<Self>;
+}
+
+impl AddressPayload for PublicKey {
+    const KIND: u8 = 0;
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        PublicKey::from_slice(bytes)
+    }
+}
+
+impl AddressPayload for Hash {
+    const KIND: u8 = 1;
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        Hash::from_slice(bytes)
+    }
+}
+
+/// Address of a public key or a hash in the given network.
+///
+/// # Examples
+///
+/// ```
+/// use exonum::crypto::{self, PublicKeyAddress};
+///
+/// # crypto::init();
+/// let (public_key, _) = crypto::gen_keypair();
+/// let address = PublicKeyAddress::new("exonum", public_key).unwrap();
+/// let string = address.to_string();
+/// assert!(string.starts_with("exonum1q"));
+///
+/// let parsed = PublicKeyAddress::parse("exonum", &string).unwrap();
+/// assert_eq!(parsed.value(), public_key);
+/// assert!(PublicKeyAddress::parse("testnet", &string).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Address<T> {
+    network: String,
+    value: T,
+}
+
+/// Address of a public key.
+pub type PublicKeyAddress = Address<PublicKey>;
+/// Address of a hash.
+pub type HashAddress = Address<Hash>;
+
+impl<T: AddressPayload> Address<T> {
+    /// Creates the address of the value in the network. The network name should consist
+    /// of lowercase ASCII letters and digits and be no longer than 30 characters.
+    pub fn new<S: Into<String>>(network: S, value: T) -> Result<Self, AddressError> {
+        let network = network.into();
+        check_network(&network)?;
+        Ok(Address { network, value })
+    }
+
+    /// Parses the address and checks that it belongs to the network.
+    pub fn parse(network: &str, s: &str) -> Result<Self, AddressError> {
+        let address: Self = s.parse()?;
+        if address.network != network {
+            return Err(AddressError::NetworkMismatch {
+                expected: network.to_owned(),
+                actual: address.network,
+            });
+        }
+        Ok(address)
+    }
+
+    /// Returns the network name.
+    pub fn network(&self) -> &str {
+        &self.network
+    }
+
+    /// Returns the value.
+    pub fn value(&self) -> T {
+        self.value
+    }
+}
+
+impl<T: AddressPayload> fmt::Display for Address<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut data = vec![T::KIND];
+        data.extend(convert_bits(self.value.as_ref(), 8, 5, true).expect("Padding is allowed."));
+        f.write_str(&encode(&self.network, &data))
+    }
+}
+
+impl<T: AddressPayload> FromStr for Address<T> {
+    type Err = AddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (network, data) = decode(s)?;
+        check_network(&network)?;
+        match data.first() {
+            Some(&kind) if kind == T::KIND => {}
+            Some(&kind) => return Err(AddressError::UnexpectedKind(kind)),
+            None => return Err(AddressError::InvalidLength),
+        }
+        let value = convert_bits(&data[1..], 5, 8, false)
+            .as_ref()
+            .and_then(|bytes| T::from_bytes(bytes))
+            .ok_or(AddressError::InvalidLength)?;
+        Ok(Address { network, value })
+    }
+}
+
+fn check_network(network: &str) -> Result<(), AddressError> {
+    let is_valid = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit();
+    if network.is_empty() || network.len() > MAX_NETWORK_LENGTH || !network.chars().all(is_valid)
+    {
+        return Err(AddressError::InvalidNetwork(network.to_owned()));
+    }
+    Ok(())
+}
+
+fn polymod(values: &[u8]) -> u32 {
+    let mut checksum = 1_u32;
+    for &value in values {
+        let top = checksum >> 25;
+        checksum = (checksum & 0x1ff_ffff) << 5 ^ u32::from(value);
+        for (i, generator) in GENERATORS.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+// The human-readable part is mixed into the checksum, so an address of one network
+// doesn't pass as an address of another one.
+fn expand_hrp(hrp: &str) -> Vec<u8> {
+    let bytes = hrp.as_bytes();
+    let mut expanded: Vec<u8> = bytes.iter().map(|b| b >> 5).collect();
+    expanded.push(0);
+    expanded.extend(bytes.iter().map(|b| b & 0x1f));
+    expanded
+}
+
+fn checksum(hrp: &str, data: &[u8]) -> Vec<u8> {
+    let mut values = expand_hrp(hrp);
+    values.extend_from_slice(data);
+    values.extend_from_slice(&[0; CHECKSUM_LENGTH]);
+    let checksum = polymod(&values) ^ 1;
+    (0..CHECKSUM_LENGTH)
+        .map(|i| (checksum >> (5 * (5 - i)) & 0x1f) as u8)
+        .collect()
+}
+
+// Encodes 5-bit symbols with the human-readable part, which should be lowercase.
+fn encode(hrp: &str, data: &[u8]) -> String {
+    let mut out = String::with_capacity(hrp.len() + 1 + data.len() + CHECKSUM_LENGTH);
+    out.push_str(hrp);
+    out.push(SEPARATOR);
+    for &symbol in data.iter().chain(&checksum(hrp, data)) {
+        out.push(CHARSET[symbol as usize] as char);
+    }
+    out
+}
+
+// Returns the lowercase human-readable part and 5-bit symbols of the data
+// without the checksum.
+fn decode(s: &str) -> Result<(String, Vec<u8>), AddressError> {
+    if s.len() > MAX_LENGTH {
+        return Err(AddressError::InvalidLength);
+    }
+    if let Some((index, c)) = s.char_indices().find(|&(_, c)| c < '!' || c > '~') {
+        return Err(AddressError::InvalidCharacter { c, index });
+    }
+    if s.chars().any(|c| c.is_ascii_lowercase()) && s.chars().any(|c| c.is_ascii_uppercase()) {
+        return Err(AddressError::MixedCase);
+    }
+    let s = s.to_ascii_lowercase();
+    let separator = s.rfind(SEPARATOR).ok_or(AddressError::MissingSeparator)?;
+    if separator == 0 || separator + 1 + CHECKSUM_LENGTH > s.len() {
+        return Err(AddressError::InvalidLength);
+    }
+
+    let hrp = &s[..separator];
+    let mut data = Vec::with_capacity(s.len() - separator - 1);
+    for (index, c) in s.char_indices().skip(separator + 1) {
+        let symbol = CHARSET
+            .iter()
+            .position(|&b| b as char == c)
+            .ok_or(AddressError::InvalidCharacter { c, index })?;
+        data.push(symbol as u8);
+    }
+    let mut values = expand_hrp(hrp);
+    values.extend_from_slice(&data);
+    if polymod(&values) != 1 {
+        return Err(AddressError::InvalidChecksum);
+    }
+    data.truncate(data.len() - CHECKSUM_LENGTH);
+    Ok((hrp.to_owned(), data))
+}
+
+// Regroups bits of the values, returns `None` if the padding is not allowed and
+// the leftover bits don't form a zero padding.
+fn convert_bits(values: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>> {
+    let mut accumulator = 0_u32;
+    let mut bits = 0;
+    let max_value = (1 << to) - 1;
+    let mut out = Vec::with_capacity(values.len() * from as usize / to as usize + 1);
+    for &value in values {
+        accumulator = accumulator << from | u32::from(value);
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            out.push((accumulator >> bits & max_value) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            out.push((accumulator << (to - bits) & max_value) as u8);
+        }
+    } else if bits >= from || accumulator << (to - bits) & max_value != 0 {
+        return None;
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode, AddressError, HashAddress, PublicKeyAddress};
+    use crypto::{hash, PublicKey};
+
+    #[test]
+    fn test_bech32_vectors() {
+        // Valid and invalid checksums from BIP-173.
+        let zeros = format!("11{}c8247j", "q".repeat(82));
+        let valid = [
+            "A12UEL5L",
+            "a12uel5l",
+            "an83characterlonghumanreadablepartthatcontainsthenumber1andtheexcludedcharacter\
+             sbio1tt5tgs",
+            "abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw",
+            &zeros,
+            "split1checkupstagehandshakeupstreamerranterredcaperred2y9e3w",
+            "?1ezyfcl",
+        ];
+        for string in &valid {
+            let (hrp, data) = decode(string).unwrap();
+            assert_eq!(encode(&hrp, &data), string.to_ascii_lowercase());
+        }
+
+        assert_eq!(decode("\u{7f}1axkwrx").unwrap_err(), AddressError::InvalidCharacter {
+            c: '\u{7f}',
+            index: 0,
+        });
+        assert_eq!(decode("pzry9x0s0muk").unwrap_err(), AddressError::MissingSeparator);
+        assert_eq!(decode("1pzry9x0s0muk").unwrap_err(), AddressError::InvalidLength);
+        assert_eq!(decode("x1b4n0q5v").unwrap_err(), AddressError::InvalidCharacter {
+            c: 'b',
+            index: 2,
+        });
+        assert_eq!(decode("li1dgmt3").unwrap_err(), AddressError::InvalidLength);
+        assert_eq!(decode("A1G7SGD8").unwrap_err(), AddressError::InvalidChecksum);
+        assert_eq!(decode("10a06t8").unwrap_err(), AddressError::InvalidLength);
+        assert_eq!(decode("a12UEL5L").unwrap_err(), AddressError::MixedCase);
+    }
+
+    #[test]
+    fn test_address_roundtrip() {
+        let public_key = PublicKey::new([0xab; 32]);
+        let address = PublicKeyAddress::new("exonum", public_key).unwrap();
+        let string = address.to_string();
+        assert!(string.starts_with("exonum1q"));
+        assert_eq!(string.len(), "exonum".len() + 60);
+        assert_eq!(string.parse::<PublicKeyAddress>().unwrap(), address);
+        assert_eq!(string.to_uppercase().parse::<PublicKeyAddress>().unwrap(), address);
+
+        let hash_address = HashAddress::new("exonum", hash(b"data")).unwrap();
+        let hash_string = hash_address.to_string();
+        assert!(hash_string.starts_with("exonum1p"));
+        assert_eq!(HashAddress::parse("exonum", &hash_string).unwrap(), hash_address);
+        assert_eq!(
+            hash_string.parse::<PublicKeyAddress>().unwrap_err(),
+            AddressError::UnexpectedKind(1)
+        );
+        assert_eq!(
+            PublicKeyAddress::parse("testnet", &string).unwrap_err(),
+            AddressError::NetworkMismatch {
+                expected: "testnet".to_owned(),
+                actual: "exonum".to_owned(),
+            }
+        );
+
+        for network in &["", "Exonum", "exo-num", "a234567890123456789012345678901"] {
+            assert_eq!(
+                PublicKeyAddress::new(*network, public_key).unwrap_err(),
+                AddressError::InvalidNetwork(network.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_address_detects_typos() {
+        let address = PublicKeyAddress::new("exonum", PublicKey::new([7; 32])).unwrap();
+        let string = address.to_string();
+        let charset = "qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+        for index in "exonum1".len()..string.len() {
+            for replacement in charset.chars() {
+                if string[index..].starts_with(replacement) {
+                    continue;
+                }
+                let mut mistyped = string.clone();
+                mistyped.replace_range(index..index + 1, &replacement.to_string());
+                assert!(mistyped.parse::<PublicKeyAddress>().is_err(), "{}", mistyped);
+            }
+        }
+
+        let mut swapped: Vec<char> = string.chars().collect();
+        let index = swapped.iter().rposition(|&c| c != swapped[swapped.len() - 1]).unwrap();
+        swapped.swap(index, index + 1);
+        let swapped: String = swapped.into_iter().collect();
+        assert_eq!(
+            swapped.parse::<PublicKeyAddress>().unwrap_err(),
+            AddressError::InvalidChecksum
+        );
+    }
+}
@@ -0,0 +1,282 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Hash functions, which may be used to compute `Hash`es.
+
+#![allow(unsafe_code)]
+
+use libsodium_sys as ffi;
+use sodiumoxide::crypto::hash::sha256;
+
+use std::fmt;
+use std::ptr;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::{Hash, HashStream, HASH_SIZE};
+
+/// Hash function used by `hash` and `HashStream`, and thus by the `CryptoHash`
+/// implementations and the Merkle trees.
+///
+/// SHA-256 is used by default. BLAKE2b-256 is used by default if the crate is built
+/// with the `blake2b` feature. The algorithm of a chain may be selected at runtime with
+/// [`set_hash_algorithm`](fn.set_hash_algorithm.html) before anything is hashed.
+///
+/// # Examples
+///
+/// ```
+/// use exonum::crypto::{self, HashAlgorithm};
+///
+/// # crypto::init();
+/// let sha256 = HashAlgorithm::Sha256.hash(b"data");
+/// let blake2b = HashAlgorithm::Blake2b.hash(b"data");
+/// assert_ne!(sha256, blake2b);
+/// assert_eq!(crypto::hash(b"data"), HashAlgorithm::current().hash(b"data"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HashAlgorithm {
+    /// SHA-256.
+    Sha256,
+    /// BLAKE2b with 256-bit output.
+    Blake2b,
+}
+
+// Zero means that the algorithm isn't selected yet.
+static CURRENT_ALGORITHM: AtomicUsize = AtomicUsize::new(0);
+
+impl HashAlgorithm {
+    /// Returns the algorithm used by the process, which can't be changed after this call.
+    pub fn current() -> Self {
+        current_in(&CURRENT_ALGORITHM)
+    }
+
+    /// Returns the name of the algorithm.
+    pub fn name(self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Blake2b => "blake2b",
+        }
+    }
+
+    /// Returns a hash of the data computed with the algorithm.
+    pub fn hash(self, data: &[u8]) -> Hash {
+        match self {
+            HashAlgorithm::Sha256 => Hash(sha256::hash(data)),
+            HashAlgorithm::Blake2b => {
+                let mut digest = [0; HASH_SIZE];
+                let result = unsafe {
+                    ffi::crypto_generichash_blake2b(
+                        digest.as_mut_ptr(),
+                        HASH_SIZE,
+                        data.as_ptr(),
+                        data.len() as u64,
+                        ptr::null(),
+                        0,
+                    )
+                };
+                assert_eq!(result, 0, "BLAKE2b hashing failed");
+                Hash::new(digest)
+            }
+        }
+    }
+
+    /// Returns an empty hash stream of the algorithm.
+    pub fn stream(self) -> HashStream {
+        HashStream::with_algorithm(self)
+    }
+
+    fn id(self) -> usize {
+        match self {
+            HashAlgorithm::Sha256 => 1,
+            HashAlgorithm::Blake2b => 2,
+        }
+    }
+
+    fn from_id(id: usize) -> Self {
+        match id {
+            1 => HashAlgorithm::Sha256,
+            2 => HashAlgorithm::Blake2b,
+            _ => unreachable!("Unknown hash algorithm {}", id),
+        }
+    }
+}
+
+impl Default for HashAlgorithm {
+    #[cfg(not(feature = "blake2b"))]
+    fn default() -> Self {
+        HashAlgorithm::Sha256
+    }
+
+    #[cfg(feature = "blake2b")]
+    fn default() -> Self {
+        HashAlgorithm::Blake2b
+    }
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sha256" => Ok(HashAlgorithm::Sha256),
+            "blake2b" => Ok(HashAlgorithm::Blake2b),
+            _ => Err(format!("Unknown hash algorithm `{}`", s)),
+        }
+    }
+}
+
+/// Selects the hash algorithm of the process, for example, the one specified in
+/// the configuration of the chain. Should be called on startup, before anything
+/// is hashed; returns the current algorithm as an error if another algorithm
+/// has already been selected or used.
+pub fn set_hash_algorithm(algorithm: HashAlgorithm) -> Result<(), HashAlgorithm> {
+    set_in(&CURRENT_ALGORITHM, algorithm)
+}
+
+// The selected algorithm is only read after it's stored, so the fast path is a plain load.
+fn current_in(cell: &AtomicUsize) -> HashAlgorithm {
+    match cell.load(Ordering::Acquire) {
+        0 => {
+            let id = HashAlgorithm::default().id();
+            match cell.compare_exchange(0, id, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => HashAlgorithm::default(),
+                Err(current) => HashAlgorithm::from_id(current),
+            }
+        }
+        current => HashAlgorithm::from_id(current),
+    }
+}
+
+fn set_in(cell: &AtomicUsize, algorithm: HashAlgorithm) -> Result<(), HashAlgorithm> {
+    match cell.compare_exchange(0, algorithm.id(), Ordering::AcqRel, Ordering::Acquire) {
+        Ok(_) => Ok(()),
+        Err(current) if current == algorithm.id() => Ok(()),
+        Err(current) => Err(HashAlgorithm::from_id(current)),
+    }
+}
+
+/// Size of `crypto_generichash_blake2b_state`, which is opaque in the bindings.
+const BLAKE2B_STATE_SIZE: usize = 384;
+
+#[repr(C, align(64))]
+struct Blake2bRawState([u8; BLAKE2B_STATE_SIZE]);
+
+/// State of the streaming BLAKE2b-256 computation.
+pub(super) struct Blake2bState(Box<Blake2bRawState>);
+
+impl Blake2bState {
+    pub fn new() -> Self {
+        assert!(unsafe { ffi::crypto_generichash_statebytes() } <= BLAKE2B_STATE_SIZE);
+        let mut state = Blake2bState(Box::new(Blake2bRawState([0; BLAKE2B_STATE_SIZE])));
+        let result = unsafe {
+            ffi::crypto_generichash_blake2b_init(state.as_mut_ptr(), ptr::null(), 0, HASH_SIZE)
+        };
+        assert_eq!(result, 0, "BLAKE2b initialization failed");
+        state
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        let result = unsafe {
+            ffi::crypto_generichash_blake2b_update(
+                self.as_mut_ptr(),
+                data.as_ptr(),
+                data.len() as u64,
+            )
+        };
+        assert_eq!(result, 0, "BLAKE2b update failed");
+    }
+
+    pub fn finalize(mut self) -> Hash {
+        let mut digest = [0; HASH_SIZE];
+        let result = unsafe {
+            ffi::crypto_generichash_blake2b_final(
+                self.as_mut_ptr(),
+                digest.as_mut_ptr(),
+                HASH_SIZE,
+            )
+        };
+        assert_eq!(result, 0, "BLAKE2b finalization failed");
+        Hash::new(digest)
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut ffi::crypto_generichash_blake2b_state {
+        (self.0).0.as_mut_ptr() as *mut ffi::crypto_generichash_blake2b_state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::{current_in, set_in, HashAlgorithm};
+    use crypto::{hash, Hash, HashStream};
+    use encoding::serialize::FromHex;
+
+    #[test]
+    fn test_set_hash_algorithm() {
+        let cell = AtomicUsize::new(0);
+        assert_eq!(set_in(&cell, HashAlgorithm::Blake2b), Ok(()));
+        assert_eq!(set_in(&cell, HashAlgorithm::Blake2b), Ok(()));
+        assert_eq!(current_in(&cell), HashAlgorithm::Blake2b);
+        // Another algorithm conflicts with the selected one.
+        assert_eq!(set_in(&cell, HashAlgorithm::Sha256), Err(HashAlgorithm::Blake2b));
+        assert_eq!(current_in(&cell), HashAlgorithm::Blake2b);
+    }
+
+    #[test]
+    fn test_set_hash_algorithm_after_use() {
+        let other = match HashAlgorithm::default() {
+            HashAlgorithm::Sha256 => HashAlgorithm::Blake2b,
+            HashAlgorithm::Blake2b => HashAlgorithm::Sha256,
+        };
+        let cell = AtomicUsize::new(0);
+        assert_eq!(current_in(&cell), HashAlgorithm::default());
+        assert_eq!(set_in(&cell, other), Err(HashAlgorithm::default()));
+        assert_eq!(set_in(&cell, HashAlgorithm::default()), Ok(()));
+        assert_eq!(current_in(&cell), HashAlgorithm::default());
+    }
+
+    #[test]
+    fn test_hash_algorithms() {
+        let cases = [
+            (
+                HashAlgorithm::Sha256,
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+            (
+                HashAlgorithm::Blake2b,
+                "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8",
+                "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319",
+            ),
+        ];
+        for &(algorithm, empty, abc) in &cases {
+            assert_eq!(algorithm.hash(&[]), Hash::from_hex(empty).unwrap());
+            assert_eq!(algorithm.hash(b"abc"), Hash::from_hex(abc).unwrap());
+            let stream = algorithm.stream().update(b"a").update(b"").update(b"bc");
+            assert_eq!(stream.hash(), algorithm.hash(b"abc"));
+            assert_eq!(algorithm.name().parse(), Ok(algorithm));
+        }
+
+        assert_eq!(hash(b"abc"), HashAlgorithm::current().hash(b"abc"));
+        assert_eq!(HashStream::new().update(b"abc").hash(), hash(b"abc"));
+        assert!("md5".parse::<HashAlgorithm>().is_err());
+    }
+}
@@ -0,0 +1,376 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Hierarchical deterministic derivation of Ed25519 keys according to
+//! [SLIP-0010](https://github.com/satoshilabs/slips/blob/master/slip-0010.md).
+//!
+//! Ed25519 supports the hardened derivation only, so all indices of a derivation
+//! path must be hardened, that is, not less than `HARDENED_OFFSET`.
+
+use byteorder::{BigEndian, ByteOrder};
+use sodiumoxide::crypto::auth::hmacsha512;
+use sodiumoxide::utils::{memcmp, memzero};
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use super::{gen_keypair_from_seed, PublicKey, SecretKey, Seed, SEED_LENGTH};
+
+/// The first hardened index.
+pub const HARDENED_OFFSET: u32 = 0x8000_0000;
+
+/// Length of the chain code of an extended seed.
+pub const CHAIN_CODE_LENGTH: usize = 32;
+
+/// HMAC key of the master seed derivation for the Ed25519 curve.
+const MASTER_KEY: &[u8] = b"ed25519 seed";
+
+/// Errors that may occur while parsing a derivation path or deriving a seed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DerivationError {
+    /// The index is not hardened, which is not supported by Ed25519.
+    NonHardenedIndex(u32),
+    /// The derivation path can't be parsed.
+    InvalidPath(String),
+}
+
+impl fmt::Display for DerivationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DerivationError::NonHardenedIndex(index) => {
+                write!(f, "Index {} is not hardened", index)
+            }
+            DerivationError::InvalidPath(ref path) => {
+                write!(f, "Invalid derivation path `{}`", path)
+            }
+        }
+    }
+}
+
+impl Error for DerivationError {
+    fn description(&self) -> &str {
+        match *self {
+            DerivationError::NonHardenedIndex(_) => "Index is not hardened",
+            DerivationError::InvalidPath(_) => "Invalid derivation path",
+        }
+    }
+}
+
+/// Path of hardened indices leading from the master seed to a child seed,
+/// written as `m/44'/0'/1'`.
+///
+/// # Examples
+///
+/// ```
+/// use exonum::crypto::{DerivationPath, HARDENED_OFFSET};
+///
+/// let path: DerivationPath = "m/0'/1'".parse().unwrap();
+/// assert_eq!(path.indices(), &[HARDENED_OFFSET, HARDENED_OFFSET + 1]);
+/// assert_eq!(path.to_string(), "m/0'/1'");
+/// assert!("m/0/1".parse::<DerivationPath>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct DerivationPath(Vec<u32>);
+
+impl DerivationPath {
+    /// Creates a path from the hardened indices.
+    pub fn new(indices: Vec<u32>) -> Result<Self, DerivationError> {
+        if let Some(&index) = indices.iter().find(|&&index| index < HARDENED_OFFSET) {
+            return Err(DerivationError::NonHardenedIndex(index));
+        }
+        Ok(DerivationPath(indices))
+    }
+
+    /// Returns the path to the master seed.
+    pub fn master() -> Self {
+        DerivationPath(Vec::new())
+    }
+
+    /// Returns the path to the child with the given index, which is hardened
+    /// by adding `HARDENED_OFFSET`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is not less than `HARDENED_OFFSET`.
+    pub fn child(&self, index: u32) -> Self {
+        assert!(index < HARDENED_OFFSET, "Index {} is out of range", index);
+        let mut indices = self.0.clone();
+        indices.push(HARDENED_OFFSET + index);
+        DerivationPath(indices)
+    }
+
+    /// Returns the hardened indices of the path.
+    pub fn indices(&self) -> &[u32] {
+        &self.0
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = DerivationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || DerivationError::InvalidPath(s.to_owned());
+        let mut parts = s.split('/');
+        if parts.next() != Some("m") {
+            return Err(invalid());
+        }
+        let mut path = DerivationPath::master();
+        for part in parts {
+            let index = if part.ends_with('\'') || part.ends_with('h') || part.ends_with('H') {
+                &part[..part.len() - 1]
+            } else {
+                return Err(invalid());
+            };
+            let index = index.parse::<u32>().map_err(|_| invalid())?;
+            if index >= HARDENED_OFFSET {
+                return Err(invalid());
+            }
+            path = path.child(index);
+        }
+        Ok(path)
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "m")?;
+        for index in &self.0 {
+            write!(f, "/{}'", index - HARDENED_OFFSET)?;
+        }
+        Ok(())
+    }
+}
+
+/// Seed accompanied with the chain code, from which the child seeds are derived.
+///
+/// # Examples
+///
+/// ```
+/// use exonum::crypto::{self, ExtendedSeed, Seed};
+///
+/// # crypto::init();
+/// let master = ExtendedSeed::from_seed(&Seed::new([1; 32]));
+/// let consensus = master.derive_path(&"m/0'/0'".parse().unwrap());
+/// let service = master.derive_path(&"m/0'/1'".parse().unwrap());
+/// assert_ne!(consensus.keypair().0, service.keypair().0);
+/// ```
+#[derive(Clone)]
+pub struct ExtendedSeed {
+    seed: Seed,
+    chain_code: [u8; CHAIN_CODE_LENGTH],
+}
+
+impl ExtendedSeed {
+    /// Derives the master extended seed from the seed bytes of an arbitrary length.
+    pub fn master(seed: &[u8]) -> Self {
+        let mut state = hmacsha512::State::init(MASTER_KEY);
+        state.update(seed);
+        ExtendedSeed::from_hmac(state)
+    }
+
+    /// Derives the master extended seed from the `Seed`.
+    pub fn from_seed(seed: &Seed) -> Self {
+        ExtendedSeed::master(&seed[..])
+    }
+
+    /// Derives the child extended seed with the given hardened index.
+    pub fn derive_child(&self, index: u32) -> Result<Self, DerivationError> {
+        if index < HARDENED_OFFSET {
+            return Err(DerivationError::NonHardenedIndex(index));
+        }
+        let mut index_bytes = [0; 4];
+        BigEndian::write_u32(&mut index_bytes, index);
+
+        let mut state = hmacsha512::State::init(&self.chain_code);
+        state.update(&[0]);
+        state.update(&self.seed[..]);
+        state.update(&index_bytes);
+        Ok(ExtendedSeed::from_hmac(state))
+    }
+
+    /// Derives the descendant extended seed with the given path.
+    pub fn derive_path(&self, path: &DerivationPath) -> Self {
+        path.indices().iter().fold(self.clone(), |seed, &index| {
+            seed.derive_child(index).expect("Path contains hardened indices only.")
+        })
+    }
+
+    /// Returns the seed, from which the keypair is generated.
+    pub fn seed(&self) -> &Seed {
+        &self.seed
+    }
+
+    /// Returns the chain code.
+    pub fn chain_code(&self) -> &[u8; CHAIN_CODE_LENGTH] {
+        &self.chain_code
+    }
+
+    /// Returns the keypair generated from the seed.
+    pub fn keypair(&self) -> (PublicKey, SecretKey) {
+        gen_keypair_from_seed(&self.seed)
+    }
+
+    fn from_hmac(mut state: hmacsha512::State) -> Self {
+        let hmacsha512::Tag(mut bytes) = state.finalize();
+        let mut chain_code = [0; CHAIN_CODE_LENGTH];
+        chain_code.copy_from_slice(&bytes[SEED_LENGTH..]);
+        let seed = Seed::from_slice(&bytes[..SEED_LENGTH]).expect("Incorrect seed length.");
+        memzero(&mut bytes);
+        ExtendedSeed { seed, chain_code }
+    }
+}
+
+impl PartialEq for ExtendedSeed {
+    fn eq(&self, other: &Self) -> bool {
+        self.seed == other.seed && memcmp(&self.chain_code, &other.chain_code)
+    }
+}
+
+impl Drop for ExtendedSeed {
+    fn drop(&mut self) {
+        memzero(&mut self.chain_code);
+    }
+}
+
+impl fmt::Debug for ExtendedSeed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ExtendedSeed")
+            .field("seed", &self.seed)
+            .finish()
+    }
+}
+
+/// Derives the keypair with the given path from the master seed.
+///
+/// # Examples
+///
+/// ```
+/// use exonum::crypto::{self, Seed};
+///
+/// # crypto::init();
+/// let master = Seed::new([1; 32]);
+/// let path = "m/44'/0'".parse().unwrap();
+/// let (public_key, secret_key) = crypto::derive_keypair(&master, &path);
+/// assert_eq!((public_key, secret_key), crypto::derive_keypair(&master, &path));
+/// ```
+pub fn derive_keypair(master: &Seed, path: &DerivationPath) -> (PublicKey, SecretKey) {
+    ExtendedSeed::from_seed(master).derive_path(path).keypair()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DerivationError, DerivationPath, ExtendedSeed, HARDENED_OFFSET};
+    use encoding::serialize::encode_hex;
+
+    // Test vectors 1 and 2 for Ed25519 from SLIP-0010. The public keys are listed
+    // without the leading zero byte.
+    const TEST_VECTORS: &[(&str, &[(&str, &str, &str, &str)])] = &[
+        (
+            "000102030405060708090a0b0c0d0e0f",
+            &[
+                (
+                    "m",
+                    "90046a93de5380a72b5e45010748567d5ea02bbf6522f979e05c0d8d8ca9fffb",
+                    "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7",
+                    "a4b2856bfec510abab89753fac1ac0e1112364e7d250545963f135f2a33188ed",
+                ),
+                (
+                    "m/0'",
+                    "8b59aa11380b624e81507a27fedda59fea6d0b779a778918a2fd3590e16e9c69",
+                    "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3",
+                    "8c8a13df77a28f3445213a0f432fde644acaa215fc72dcdf300d5efaa85d350c",
+                ),
+                (
+                    "m/0'/1'",
+                    "a320425f77d1b5c2505a6b1b27382b37368ee640e3557c315416801243552f14",
+                    "b1d0bad404bf35da785a64ca1ac54b2617211d2777696fbffaf208f746ae84f2",
+                    "1932a5270f335bed617d5b935c80aedb1a35bd9fc1e31acafd5372c30f5c1187",
+                ),
+                (
+                    "m/0'/1'/2'",
+                    "2e69929e00b5ab250f49c3fb1c12f252de4fed2c1db88387094a0f8c4c9ccd6c",
+                    "92a5b23c0b8a99e37d07df3fb9966917f5d06e02ddbd909c7e184371463e9fc9",
+                    "ae98736566d30ed0e9d2f4486a64bc95740d89c7db33f52121f8ea8f76ff0fc1",
+                ),
+                (
+                    "m/0'/1'/2'/2'",
+                    "8f6d87f93d750e0efccda017d662a1b31a266e4a6f5993b15f5c1f07f74dd5cc",
+                    "30d1dc7e5fc04c31219ab25a27ae00b50f6fd66622f6e9c913253d6511d1e662",
+                    "8abae2d66361c879b900d204ad2cc4984fa2aa344dd7ddc46007329ac76c429c",
+                ),
+                (
+                    "m/0'/1'/2'/2'/1000000000'",
+                    "68789923a0cac2cd5a29172a475fe9e0fb14cd6adb5ad98a3fa70333e7afa230",
+                    "8f94d394a8e8fd6b1bc2f3f49f5c47e385281d5c17e65324b0f62483e37e8793",
+                    "3c24da049451555d51a7014a37337aa4e12d41e485abccfa46b47dfb2af54b7a",
+                ),
+            ],
+        ),
+        (
+            "fffcf9f6f3f0edeae7e4e1dedbd8d5d2cfccc9c6c3c0bdbab7b4b1aeaba8a5a2\
+             9f9c999693908d8a8784817e7b7875726f6c696663605d5a5754514e4b484542",
+            &[
+                (
+                    "m",
+                    "ef70a74db9c3a5af931b5fe73ed8e1a53464133654fd55e7a66f8570b8e33c3b",
+                    "171cb88b1b3c1db25add599712e36245d75bc65a1a5c9e18d76f9f2b1eab4012",
+                    "8fe9693f8fa62a4305a140b9764c5ee01e455963744fe18204b4fb948249308a",
+                ),
+                (
+                    "m/0'",
+                    "0b78a3226f915c082bf118f83618a618ab6dec793752624cbeb622acb562862d",
+                    "1559eb2bbec5790b0c65d8693e4d0875b1747f4970ae8b650486ed7470845635",
+                    "86fab68dcb57aa196c77c5f264f215a112c22a912c10d123b0d03c3c28ef1037",
+                ),
+                (
+                    "m/0'/2147483647'",
+                    "138f0b2551bcafeca6ff2aa88ba8ed0ed8de070841f0c4ef0165df8181eaad7f",
+                    "ea4f5bfe8694d8bb74b7b59404632fd5968b774ed545e810de9c32a4fb4192f4",
+                    "5ba3b9ac6e90e83effcd25ac4e58a1365a9e35a3d3ae5eb07b9e4d90bcf7506d",
+                ),
+            ],
+        ),
+    ];
+
+    #[test]
+    fn test_slip10_vectors() {
+        for &(seed, chains) in TEST_VECTORS {
+            let master = ExtendedSeed::master(&::hex::decode(seed).unwrap());
+            for &(path, chain_code, secret, public) in chains {
+                let derived = master.derive_path(&path.parse().unwrap());
+                assert_eq!(encode_hex(&derived.chain_code()[..]), chain_code, "{}", path);
+                assert_eq!(derived.seed().to_hex(), secret, "{}", path);
+                assert_eq!(derived.keypair().0.to_hex(), public, "{}", path);
+            }
+        }
+    }
+
+    #[test]
+    fn test_derivation_path() {
+        let path: DerivationPath = "m/44'/1h/2H".parse().unwrap();
+        assert_eq!(path, DerivationPath::master().child(44).child(1).child(2));
+        assert_eq!(path.to_string(), "m/44'/1'/2'");
+        assert_eq!("m".parse(), Ok(DerivationPath::master()));
+        for invalid in &["", "n/0'", "m/0", "m/'", "m/2147483648'", "m/0'/"] {
+            assert!(invalid.parse::<DerivationPath>().is_err(), "{}", invalid);
+        }
+        assert_eq!(
+            DerivationPath::new(vec![HARDENED_OFFSET, 1]),
+            Err(DerivationError::NonHardenedIndex(1))
+        );
+        let master = ExtendedSeed::master(&[1, 2, 3]);
+        assert_eq!(master.derive_child(5), Err(DerivationError::NonHardenedIndex(5)));
+    }
+}
@@ -0,0 +1,163 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Domain separation of the hashed and signed data.
+
+use failure::Error;
+
+use super::{verify, Hash, HashStream, PublicKey, Signature, Signer, HASH_SIZE};
+
+/// Name of the default network.
+pub const DEFAULT_NETWORK: &str = "exonum";
+
+/// Domain of the hashed or signed data.
+///
+/// The tag of the domain is prepended to the data, so the hash or the signature
+/// of a value in one domain can't be presented as the one in another domain,
+/// even if the binary representations of the values are the same. The signed
+/// data is also prefixed with the given [chain id](fn.chain_id.html) of the network,
+/// so the signatures of one network are not valid in another.
+///
+/// The tags of the Merkle tree domains coincide with the corresponding
+/// [`HashTag`](../storage/enum.HashTag.html)s, which are used to hash the trees.
+///
+/// # Examples
+///
+/// ```
+/// use exonum::crypto::{self, Domain, Signer};
+///
+/// # crypto::init();
+/// let (public_key, secret_key) = crypto::gen_keypair();
+/// let chain_id = crypto::chain_id("testnet");
+/// let signature = Domain::Message.sign(&chain_id, b"data", &secret_key).unwrap();
+/// assert!(Domain::Message.verify(&chain_id, &signature, b"data", &public_key));
+/// assert!(!Domain::Transaction.verify(&chain_id, &signature, b"data", &public_key));
+/// let other_chain_id = crypto::chain_id("mainnet");
+/// assert!(!Domain::Message.verify(&other_chain_id, &signature, b"data", &public_key));
+/// assert_ne!(Domain::Message.hash(b"data"), Domain::Transaction.hash(b"data"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum Domain {
+    /// Leaf of a Merkle tree.
+    MerkleLeaf = 0,
+    /// Inner node of a Merkle tree.
+    MerkleNode = 1,
+    /// Message exchanged between the nodes.
+    Message = 0x10,
+    /// Transaction, that is a message changing the blockchain state.
+    Transaction = 0x11,
+    /// Block header.
+    Block = 0x12,
+    /// Identifier of the network, which is derived from its name.
+    ChainId = 0x13,
+    /// Structure declared with `encoding_struct!` outside of the domains above.
+    Struct = 0x14,
+}
+
+impl Domain {
+    /// Returns the tag prepended to the data of the domain.
+    pub fn tag(self) -> u8 {
+        self as u8
+    }
+
+    /// Returns a hash stream with the tag of the domain written to it.
+    pub fn hash_stream(self) -> HashStream {
+        HashStream::new().update(&[self.tag()])
+    }
+
+    /// Returns a hash of the data in the domain.
+    pub fn hash(self, data: &[u8]) -> Hash {
+        self.hash_stream().update(data).hash()
+    }
+
+    /// Returns the data prefixed with the tag of the domain and the chain id,
+    /// which is the content of the signatures in the domain.
+    pub fn prefixed(self, chain_id: &Hash, data: &[u8]) -> Vec<u8> {
+        let mut prefixed = Vec::with_capacity(data.len() + 1 + HASH_SIZE);
+        prefixed.push(self.tag());
+        prefixed.extend_from_slice(chain_id.as_ref());
+        prefixed.extend_from_slice(data);
+        prefixed
+    }
+
+    /// Signs the data in the domain of the network with the given chain id.
+    pub fn sign<S: Signer + ?Sized>(
+        self,
+        chain_id: &Hash,
+        data: &[u8],
+        signer: &S,
+    ) -> Result<Signature, Error> {
+        signer.sign(&self.prefixed(chain_id, data))
+    }
+
+    /// Verifies the signature of the data in the domain of the network with the given chain id.
+    pub fn verify(
+        self,
+        chain_id: &Hash,
+        signature: &Signature,
+        data: &[u8],
+        public_key: &PublicKey,
+    ) -> bool {
+        verify(signature, &self.prefixed(chain_id, data), public_key)
+    }
+}
+
+/// Returns the identifier of the network with the given name.
+pub fn chain_id(network_name: &str) -> Hash {
+    Domain::ChainId.hash(network_name.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{chain_id, Domain, DEFAULT_NETWORK};
+    use crypto::{gen_keypair, hash, sign, verify};
+
+    #[test]
+    fn test_domain_separation() {
+        let (public_key, secret_key) = gen_keypair();
+        let chain_id = chain_id(DEFAULT_NETWORK);
+        let data = [1, 2, 3];
+
+        let signature = Domain::Message.sign(&chain_id, &data, &secret_key).unwrap();
+        assert!(Domain::Message.verify(&chain_id, &signature, &data, &public_key));
+        assert!(!Domain::Block.verify(&chain_id, &signature, &data, &public_key));
+        assert!(!verify(&signature, &data, &public_key));
+        let plain = sign(&data, &secret_key);
+        assert!(!Domain::Message.verify(&chain_id, &plain, &data, &public_key));
+
+        assert_eq!(Domain::Message.hash(&data), hash(&[0x10, 1, 2, 3]));
+        assert_ne!(Domain::Message.hash(&data), Domain::Transaction.hash(&data));
+        assert_ne!(Domain::Message.hash(&data), hash(&data));
+    }
+
+    #[test]
+    fn test_chain_id_in_signatures() {
+        let (public_key, secret_key) = gen_keypair();
+        let data = [1, 2, 3];
+        let signature = Domain::Message.sign(&chain_id(DEFAULT_NETWORK), &data, &secret_key)
+            .unwrap();
+
+        let prefixed = Domain::Message.prefixed(&chain_id(DEFAULT_NETWORK), &data);
+        assert_eq!(prefixed[0], Domain::Message.tag());
+        assert_eq!(&prefixed[1..33], chain_id(DEFAULT_NETWORK).as_ref());
+        assert_eq!(&prefixed[33..], &data);
+        assert!(verify(&signature, &prefixed, &public_key));
+
+        let other_network = Domain::Message.prefixed(&chain_id("testnet"), &data);
+        assert!(!verify(&signature, &other_network, &public_key));
+        assert!(!Domain::Message.verify(&chain_id("testnet"), &signature, &data, &public_key));
+        assert_ne!(chain_id("testnet"), chain_id(DEFAULT_NETWORK));
+    }
+}
@@ -0,0 +1,401 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Key files storing the validator secrets encrypted under a passphrase.
+//!
+//! The seed of the keypair is encrypted with `xsalsa20-poly1305` authenticated
+//! encryption, the key of which is derived from the passphrase with
+//! `scrypt-salsa208-sha256`. The file itself is a JSON document, which also
+//! contains the public key, so the key could be identified without the passphrase.
+
+use serde_json;
+use sodiumoxide::crypto::pwhash::scryptsalsa208sha256 as scrypt;
+use sodiumoxide::crypto::secretbox::xsalsa20poly1305 as secretbox;
+use sodiumoxide::utils::memzero;
+
+use std::error::Error;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use super::{gen_keypair_from_seed, PublicKey, SecretKey, Seed, SEED_LENGTH};
+use encoding::serialize::{encode_hex, FromHex};
+
+/// Version of the key file format written by this module.
+pub const KEY_FILE_VERSION: u32 = 1;
+
+const KDF_NAME: &str = "scrypt-salsa208-sha256";
+const CIPHER_NAME: &str = "xsalsa20-poly1305";
+
+/// Errors that may occur while reading or writing a key file.
+#[derive(Debug)]
+pub enum KeyFileError {
+    /// The file could not be read or written.
+    Io(io::Error),
+    /// The file already exists and is not overwritten.
+    AlreadyExists(PathBuf),
+    /// The file is not a valid key file document.
+    Format(serde_json::Error),
+    /// The file was written in the unknown version of the format.
+    UnsupportedVersion(u32),
+    /// The file uses the unknown key derivation function or cipher.
+    UnsupportedAlgorithm(String),
+    /// The key derivation parameters are malformed or out of the allowed bounds.
+    InvalidParams,
+    /// The passphrase is wrong or the ciphertext was modified.
+    Decryption,
+    /// The decrypted seed doesn't correspond to the public key of the file.
+    PublicKeyMismatch,
+}
+
+impl fmt::Display for KeyFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            KeyFileError::Io(ref e) => write!(f, "Could not access the key file: {}", e),
+            KeyFileError::AlreadyExists(ref path) => {
+                write!(f, "Key file {} already exists", path.display())
+            }
+            KeyFileError::Format(ref e) => write!(f, "Malformed key file: {}", e),
+            KeyFileError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported key file version {}", version)
+            }
+            KeyFileError::UnsupportedAlgorithm(ref name) => {
+                write!(f, "Unsupported key file algorithm `{}`", name)
+            }
+            KeyFileError::InvalidParams => write!(f, "Invalid key derivation parameters"),
+            KeyFileError::Decryption => write!(f, "Wrong passphrase or corrupted key file"),
+            KeyFileError::PublicKeyMismatch => {
+                write!(f, "Decrypted key doesn't match the public key")
+            }
+        }
+    }
+}
+
+impl Error for KeyFileError {
+    fn description(&self) -> &str {
+        match *self {
+            KeyFileError::Io(_) => "Could not access the key file",
+            KeyFileError::AlreadyExists(_) => "Key file already exists",
+            KeyFileError::Format(_) => "Malformed key file",
+            KeyFileError::UnsupportedVersion(_) => "Unsupported key file version",
+            KeyFileError::UnsupportedAlgorithm(_) => "Unsupported key file algorithm",
+            KeyFileError::InvalidParams => "Invalid key derivation parameters",
+            KeyFileError::Decryption => "Wrong passphrase or corrupted key file",
+            KeyFileError::PublicKeyMismatch => "Decrypted key doesn't match the public key",
+        }
+    }
+}
+
+impl From<io::Error> for KeyFileError {
+    fn from(e: io::Error) -> Self {
+        KeyFileError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for KeyFileError {
+    fn from(e: serde_json::Error) -> Self {
+        KeyFileError::Format(e)
+    }
+}
+
+/// Cost parameters of the passphrase based key derivation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    /// Maximum number of computations to perform.
+    pub opslimit: u64,
+    /// Maximum amount of memory in bytes to use.
+    pub memlimit: u64,
+}
+
+impl KdfParams {
+    /// Parameters suitable for the interactive use, which take a fraction of a second.
+    pub fn interactive() -> Self {
+        KdfParams {
+            opslimit: scrypt::OPSLIMIT_INTERACTIVE.0 as u64,
+            memlimit: scrypt::MEMLIMIT_INTERACTIVE.0 as u64,
+        }
+    }
+
+    /// Parameters suitable for the keys, which are rarely decrypted,
+    /// for example, the keys of validators. Derivation takes several seconds
+    /// and requires 1 GB of memory.
+    pub fn sensitive() -> Self {
+        KdfParams {
+            opslimit: scrypt::OPSLIMIT_SENSITIVE.0 as u64,
+            memlimit: scrypt::MEMLIMIT_SENSITIVE.0 as u64,
+        }
+    }
+
+    fn derive_key(
+        &self,
+        passphrase: &[u8],
+        salt: &scrypt::Salt,
+    ) -> Result<secretbox::Key, KeyFileError> {
+        // The limits are taken from the file, so they are bounded
+        // by the sensitive ones to prevent resource exhaustion.
+        if self.opslimit > scrypt::OPSLIMIT_SENSITIVE.0 as u64
+            || self.memlimit > scrypt::MEMLIMIT_SENSITIVE.0 as u64
+        {
+            return Err(KeyFileError::InvalidParams);
+        }
+        let mut key = secretbox::Key([0; secretbox::KEYBYTES]);
+        scrypt::derive_key(
+            &mut key.0,
+            passphrase,
+            salt,
+            scrypt::OpsLimit(self.opslimit as usize),
+            scrypt::MemLimit(self.memlimit as usize),
+        ).map_err(|_| KeyFileError::InvalidParams)?;
+        Ok(key)
+    }
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        KdfParams::interactive()
+    }
+}
+
+/// Keypair seed encrypted under a passphrase, as it is stored in a key file.
+///
+/// # Examples
+///
+/// ```
+/// use exonum::crypto::{self, EncryptedKey, Seed};
+///
+/// # crypto::init();
+/// let seed = Seed::new([1; 32]);
+/// let encrypted = EncryptedKey::encrypt(&seed, b"passphrase").unwrap();
+/// let (public_key, _) = encrypted.decrypt(b"passphrase").unwrap();
+/// assert_eq!(public_key, crypto::gen_keypair_from_seed(&seed).0);
+/// assert!(encrypted.decrypt(b"wrong passphrase").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EncryptedKey {
+    version: u32,
+    public_key: String,
+    kdf: String,
+    kdf_params: KdfParams,
+    salt: String,
+    cipher: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+impl EncryptedKey {
+    /// Encrypts the seed under the passphrase with the default key derivation parameters.
+    pub fn encrypt(seed: &Seed, passphrase: &[u8]) -> Result<Self, KeyFileError> {
+        EncryptedKey::encrypt_with_params(seed, passphrase, KdfParams::default())
+    }
+
+    /// Encrypts the seed under the passphrase with the given key derivation parameters.
+    pub fn encrypt_with_params(
+        seed: &Seed,
+        passphrase: &[u8],
+        params: KdfParams,
+    ) -> Result<Self, KeyFileError> {
+        let salt = scrypt::gen_salt();
+        let nonce = secretbox::gen_nonce();
+        let key = params.derive_key(passphrase, &salt)?;
+        let ciphertext = secretbox::seal(&seed[..], &nonce, &key);
+        Ok(EncryptedKey {
+            version: KEY_FILE_VERSION,
+            public_key: gen_keypair_from_seed(seed).0.to_hex(),
+            kdf: KDF_NAME.to_owned(),
+            kdf_params: params,
+            salt: encode_hex(&salt.0[..]),
+            cipher: CIPHER_NAME.to_owned(),
+            nonce: encode_hex(&nonce.0[..]),
+            ciphertext: encode_hex(&ciphertext),
+        })
+    }
+
+    /// Returns the version of the format.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Returns the public key, which corresponds to the encrypted seed,
+    /// or `None` if it is malformed.
+    pub fn public_key(&self) -> Option<PublicKey> {
+        PublicKey::from_hex(&self.public_key).ok()
+    }
+
+    /// Returns the key derivation parameters.
+    pub fn kdf_params(&self) -> KdfParams {
+        self.kdf_params
+    }
+
+    /// Decrypts the seed and returns the keypair generated from it.
+    pub fn decrypt(&self, passphrase: &[u8]) -> Result<(PublicKey, SecretKey), KeyFileError> {
+        let seed = self.decrypt_seed(passphrase)?;
+        let (public_key, secret_key) = gen_keypair_from_seed(&seed);
+        if Some(public_key) != self.public_key() {
+            return Err(KeyFileError::PublicKeyMismatch);
+        }
+        Ok((public_key, secret_key))
+    }
+
+    /// Decrypts the seed of the keypair.
+    pub fn decrypt_seed(&self, passphrase: &[u8]) -> Result<Seed, KeyFileError> {
+        if self.version != KEY_FILE_VERSION {
+            return Err(KeyFileError::UnsupportedVersion(self.version));
+        }
+        if self.kdf != KDF_NAME {
+            return Err(KeyFileError::UnsupportedAlgorithm(self.kdf.clone()));
+        }
+        if self.cipher != CIPHER_NAME {
+            return Err(KeyFileError::UnsupportedAlgorithm(self.cipher.clone()));
+        }
+
+        let salt = Vec::<u8>::from_hex(&self.salt)
+            .ok()
+            .and_then(|bytes| scrypt::Salt::from_slice(&bytes))
+            .ok_or(KeyFileError::InvalidParams)?;
+        let nonce = Vec::<u8>::from_hex(&self.nonce)
+            .ok()
+            .and_then(|bytes| secretbox::Nonce::from_slice(&bytes))
+            .ok_or(KeyFileError::Decryption)?;
+        let ciphertext =
+            Vec::<u8>::from_hex(&self.ciphertext).map_err(|_| KeyFileError::Decryption)?;
+
+        let key = self.kdf_params.derive_key(passphrase, &salt)?;
+        let mut plaintext =
+            secretbox::open(&ciphertext, &nonce, &key).map_err(|_| KeyFileError::Decryption)?;
+        let seed = if plaintext.len() == SEED_LENGTH {
+            Seed::from_slice(&plaintext)
+        } else {
+            None
+        };
+        memzero(&mut plaintext);
+        seed.ok_or(KeyFileError::Decryption)
+    }
+
+    /// Reads the encrypted key from the file.
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, KeyFileError> {
+        let mut contents = String::new();
+        File::open(path)?.read_to_string(&mut contents)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// Writes the encrypted key to a new file, an existing file is never overwritten.
+    /// On Unix, the created file is readable by its owner only.
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), KeyFileError> {
+        let path = path.as_ref();
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(path).map_err(|e| match e.kind() {
+            io::ErrorKind::AlreadyExists => KeyFileError::AlreadyExists(path.to_owned()),
+            _ => KeyFileError::Io(e),
+        })?;
+        file.write_all(serde_json::to_string_pretty(self)?.as_bytes())?;
+        file.sync_all()?;
+        Ok(())
+    }
+}
+
+/// Encrypts the seed under the passphrase and saves it to the key file.
+/// Returns the public key, which corresponds to the seed.
+pub fn save_key_file<P: AsRef<Path>>(
+    path: P,
+    seed: &Seed,
+    passphrase: &[u8],
+) -> Result<PublicKey, KeyFileError> {
+    let encrypted = EncryptedKey::encrypt(seed, passphrase)?;
+    encrypted.write(path)?;
+    Ok(gen_keypair_from_seed(seed).0)
+}
+
+/// Loads the key file and decrypts the keypair stored in it.
+pub fn load_key_file<P: AsRef<Path>>(
+    path: P,
+    passphrase: &[u8],
+) -> Result<(PublicKey, SecretKey), KeyFileError> {
+    EncryptedKey::read(path)?.decrypt(passphrase)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{self, Value};
+
+    use std::env;
+    use std::fs;
+
+    use super::{load_key_file, save_key_file, EncryptedKey, KdfParams, KeyFileError};
+    use crypto::{gen_keypair_from_seed, Seed};
+
+    #[test]
+    fn test_save_load_key_file() {
+        let seed = Seed::new([42; 32]);
+        let file_name = format!("exonum-key-file-{}.json", ::std::process::id());
+        let path = env::temp_dir().join(file_name);
+        let public_key = save_key_file(&path, &seed, b"correct horse").unwrap();
+
+        let (loaded_public_key, loaded_secret_key) =
+            load_key_file(&path, b"correct horse").unwrap();
+        let (expected_public_key, expected_secret_key) = gen_keypair_from_seed(&seed);
+        assert_eq!(public_key, expected_public_key);
+        assert_eq!(loaded_public_key, expected_public_key);
+        assert_eq!(loaded_secret_key, expected_secret_key);
+
+        match load_key_file(&path, b"battery staple") {
+            Err(KeyFileError::Decryption) => {}
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        match save_key_file(&path, &Seed::new([43; 32]), b"correct horse") {
+            Err(KeyFileError::AlreadyExists(ref existing)) if *existing == path => {}
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        assert_eq!(load_key_file(&path, b"correct horse").unwrap().0, expected_public_key);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_key_file_is_authenticated() {
+        let seed = Seed::new([7; 32]);
+        let encrypted = EncryptedKey::encrypt(&seed, b"passphrase").unwrap();
+        assert_eq!(encrypted.decrypt_seed(b"passphrase").unwrap(), seed);
+
+        let mut json = serde_json::to_value(&encrypted).unwrap();
+        json["version"] = Value::from(2);
+        let future: EncryptedKey = serde_json::from_value(json.clone()).unwrap();
+        match future.decrypt(b"passphrase") {
+            Err(KeyFileError::UnsupportedVersion(2)) => {}
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        json["version"] = Value::from(1);
+        let ciphertext = json["ciphertext"].as_str().unwrap().to_owned();
+        let flipped = if ciphertext.starts_with('0') { "1" } else { "0" };
+        json["ciphertext"] = Value::from(format!("{}{}", flipped, &ciphertext[1..]));
+        let tampered: EncryptedKey = serde_json::from_value(json.clone()).unwrap();
+        match tampered.decrypt(b"passphrase") {
+            Err(KeyFileError::Decryption) => {}
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        json["kdf_params"]["memlimit"] = Value::from(KdfParams::sensitive().memlimit + 1);
+        let exhausting: EncryptedKey = serde_json::from_value(json).unwrap();
+        match exhausting.decrypt(b"passphrase") {
+            Err(KeyFileError::InvalidParams) => {}
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+}
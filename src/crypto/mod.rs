@@ -20,11 +20,12 @@
 //! and runs each block through a cycle of 64 iterations. The result of the
 //! function is a cryptographic hash 256 bits or 32 bytes in length. This
 //! hash can later be used to verify the integrity of data without accessing the
-//! data itself.
+//! data itself. BLAKE2b-256 may be selected instead of SHA-256, see `HashAlgorithm`.
 //!
 //! Exonum also makes use of Ed25519 keys. Ed25519 is a signature system that ensures
 //! fast signing and key generation, as well as security and collision
-//! resilience.
+//! resilience. Public keys and hashes may be written as addresses with a checksum,
+//! see `Address`.
 //!
 //! [Sodium library](https://github.com/jedisct1/libsodium)
 //! is used under the hood through [sodiumoxide rust bindings](https://github.com/dnaq/sodiumoxide).
@@ -41,15 +42,27 @@ pub use sodiumoxide::crypto::sign::ed25519::{PUBLICKEYBYTES as PUBLIC_KEY_LENGTH
                                              SIGNATUREBYTES as SIGNATURE_LENGTH};
 pub use sodiumoxide::crypto::hash::sha256::DIGESTBYTES as HASH_SIZE;
 // spell-checker:enable
+pub use self::address::{Address, AddressError, AddressPayload, HashAddress, PublicKeyAddress};
+pub use self::algorithm::{set_hash_algorithm, HashAlgorithm};
+pub use self::derivation::{derive_keypair, DerivationError, DerivationPath, ExtendedSeed,
+                           CHAIN_CODE_LENGTH, HARDENED_OFFSET};
+pub use self::domain::{chain_id, Domain, DEFAULT_NETWORK};
+pub use self::key_file::{load_key_file, save_key_file, EncryptedKey, KdfParams, KeyFileError,
+                         KEY_FILE_VERSION};
+pub use self::signer::{InMemorySigner, Signer};
+#[cfg(unix)]
+pub use self::signer::{SignerServer, UnixSocketSigner};
 
 use sodiumoxide::crypto::sign::ed25519::{gen_keypair as gen_keypair_sodium, keypair_from_seed,
                                          sign_detached, verify_detached,
                                          PublicKey as PublicKeySodium,
                                          SecretKey as SecretKeySodium, Seed as SeedSodium,
                                          Signature as SignatureSodium, State as SignState};
-use sodiumoxide::crypto::hash::sha256::{hash as hash_sodium, Digest as DigestSodium,
-                                        State as HashState};
+use sodiumoxide::crypto::hash::sha256::{Digest as DigestSodium, State as Sha256State};
 use sodiumoxide;
+use self::algorithm::Blake2bState;
+use sodiumoxide::randombytes::randombytes_into;
+use sodiumoxide::utils::{memcmp, memzero};
 use serde::{Serialize, Serializer};
 use serde::de::{self, Deserialize, Deserializer, Visitor};
 use byteorder::{ByteOrder, LittleEndian};
@@ -64,11 +77,15 @@ use std::str::FromStr;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use encoding::{Field, Offset};
+#[cfg(feature = "float_serialize")]
+use encoding::{F32, F64};
 use encoding::serialize::{encode_hex, FromHex, FromHexError, ToHex};
 use types::Round;
 
 /// The size to crop the string in debug messages.
 const BYTES_IN_DEBUG: usize = 4;
+/// Minimal number of signatures verified by a thread in `verify_batch`.
+const BATCH_CHUNK_SIZE: usize = 32;
 
 /// Signs a slice of bytes using the signer's secret key and returns the
 /// resulting `Signature`.
@@ -130,6 +147,27 @@ pub fn gen_keypair() -> (PublicKey, SecretKey) {
     (PublicKey(pubkey), SecretKey(secret_key))
 }
 
+/// Generates a random seed using a cryptographically secure pseudo-random number generator.
+///
+/// # Examples
+///
+/// The example below generates a seed and the keypair which corresponds to it.
+///
+/// ```
+/// use exonum::crypto;
+///
+/// # crypto::init();
+/// let seed = crypto::gen_seed();
+/// let (public_key, secret_key) = crypto::gen_keypair_from_seed(&seed);
+/// ```
+pub fn gen_seed() -> Seed {
+    let mut bytes = [0; SEED_LENGTH];
+    randombytes_into(&mut bytes);
+    let seed = Seed::new(bytes);
+    memzero(&mut bytes);
+    seed
+}
+
 /// Verifies that `data` is signed with a secret key corresponding to the
 /// given public key.
 ///
@@ -152,6 +190,64 @@ pub fn verify(sig: &Signature, data: &[u8], pubkey: &PublicKey) -> bool {
     verify_detached(&sig.0, data, &pubkey.0)
 }
 
+/// Verifies a batch of `(data, signature, public key)` triples and returns the indices
+/// of the triples with invalid signatures in ascending order.
+///
+/// Sodium has no batch verification of Ed25519 signatures, so large batches are split
+/// between threads and verified in parallel.
+///
+/// # Examples
+///
+/// ```
+/// use exonum::crypto;
+///
+/// # crypto::init();
+/// let (public_key, secret_key) = crypto::gen_keypair();
+/// let signature = crypto::sign(&[1], &secret_key);
+/// let batch: Vec<(&[u8], _, _)> = vec![
+///     (&[1], &signature, &public_key),
+///     (&[2], &signature, &public_key),
+/// ];
+/// assert_eq!(crypto::verify_batch(&batch), Err(vec![1]));
+/// ```
+pub fn verify_batch(items: &[(&[u8], &Signature, &PublicKey)]) -> Result<(), Vec<usize>> {
+    let verify_chunk = |offset: usize, chunk: &[(&[u8], &Signature, &PublicKey)]| {
+        chunk
+            .iter()
+            .enumerate()
+            .filter(|&(_, &(data, signature, public_key))| !verify(signature, data, public_key))
+            .map(|(i, _)| offset + i)
+            .collect::<Vec<_>>()
+    };
+
+    let threads = ::std::thread::available_parallelism()
+        .map(|threads| threads.get())
+        .unwrap_or(1)
+        .min(items.len() / BATCH_CHUNK_SIZE);
+    let failed = if threads <= 1 {
+        verify_chunk(0, items)
+    } else {
+        let chunk_size = (items.len() + threads - 1) / threads;
+        ::std::thread::scope(|scope| {
+            let handles = items
+                .chunks(chunk_size)
+                .enumerate()
+                .map(|(i, chunk)| scope.spawn(move || verify_chunk(i * chunk_size, chunk)))
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().expect("Verification thread panicked"))
+                .collect::<Vec<_>>()
+        })
+    };
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(failed)
+    }
+}
+
 /// Calculates an SHA-256 hash of a bytes slice.
 ///
 /// # Examples
@@ -166,8 +262,7 @@ pub fn verify(sig: &Signature, data: &[u8], pubkey: &PublicKey) -> bool {
 /// let hash = crypto::hash(&data);
 /// ```
 pub fn hash(data: &[u8]) -> Hash {
-    let dig = hash_sodium(data);
-    Hash(dig)
+    HashAlgorithm::current().hash(data)
 }
 
 /// A common trait for the ability to compute a cryptographic hash.
@@ -200,8 +295,8 @@ pub fn init() {
     }
 }
 
-/// This structure provides a possibility to calculate an SHA-256 hash digest
-/// for a stream of data. Unlike the
+/// This structure provides a possibility to calculate a hash digest
+/// for a stream of data with the current [`HashAlgorithm`](enum.HashAlgorithm.html). Unlike the
 /// [`Hash` structure](https://docs.rs/exonum/0.7.0/exonum/crypto/struct.Hash.html),
 /// the given structure lets the code process several data chunks without
 /// the need to copy them into a single buffer.
@@ -222,26 +317,63 @@ pub fn init() {
 /// }
 /// let _ = hash_stream.hash();
 /// ```
-#[derive(Debug, Default)]
 pub struct HashStream(HashState);
 
+enum HashState {
+    Sha256(Sha256State),
+    Blake2b(Blake2bState),
+}
+
 impl HashStream {
     /// Creates a new instance of `HashStream`.
     pub fn new() -> Self {
-        HashStream(HashState::init())
+        HashStream::with_algorithm(HashAlgorithm::current())
+    }
+
+    /// Creates a new instance of `HashStream`, which uses the given algorithm.
+    pub fn with_algorithm(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Sha256 => HashStream(HashState::Sha256(Sha256State::init())),
+            HashAlgorithm::Blake2b => HashStream(HashState::Blake2b(Blake2bState::new())),
+        }
+    }
+
+    /// Returns the algorithm of the stream.
+    pub fn algorithm(&self) -> HashAlgorithm {
+        match self.0 {
+            HashState::Sha256(_) => HashAlgorithm::Sha256,
+            HashState::Blake2b(_) => HashAlgorithm::Blake2b,
+        }
     }
 
     /// Processes a chunk of stream and returns a `HashStream` with the updated internal state.
     pub fn update(mut self, chunk: &[u8]) -> Self {
-        self.0.update(chunk);
+        match self.0 {
+            HashState::Sha256(ref mut state) => state.update(chunk),
+            HashState::Blake2b(ref mut state) => state.update(chunk),
+        }
         self
     }
 
     /// Returns the resulting hash of the system calculated upon the commit
     /// of currently supplied data.
     pub fn hash(self) -> Hash {
-        let dig = self.0.finalize();
-        Hash(dig)
+        match self.0 {
+            HashState::Sha256(state) => Hash(state.finalize()),
+            HashState::Blake2b(state) => state.finalize(),
+        }
+    }
+}
+
+impl Default for HashStream {
+    fn default() -> Self {
+        HashStream::new()
+    }
+}
+
+impl fmt::Debug for HashStream {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "HashStream({})", self.algorithm())
     }
 }
 
@@ -363,12 +495,23 @@ macro_rules! implement_public_sodium_wrapper {
     )
 }
 
+// Secret wrappers don't implement serde traits, see `ExposeSecret`. The wrapped sodium
+// types zero their memory on drop.
 macro_rules! implement_private_sodium_wrapper {
     ($(#[$attr:meta])* struct $name:ident, $name_from:ident, $size:expr) => (
-    #[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+    #[derive(Clone)]
     $(#[$attr])*
     pub struct $name($name_from);
 
+    /// Comparison takes constant time to not leak the secret bytes through timing.
+    impl PartialEq for $name {
+        fn eq(&self, other: &Self) -> bool {
+            memcmp(&(self.0).0, &(other.0).0)
+        }
+    }
+
+    impl Eq for $name {}
+
     impl $name {
         /// Creates a new instance filled with zeros.
         pub fn zero() -> Self {
@@ -389,19 +532,17 @@ macro_rules! implement_private_sodium_wrapper {
 
         /// Returns a hex representation of binary data.
         /// Lower case letters are used (e.g. f9b4ca).
+        ///
+        /// The returned string contains the secret and isn't zeroed on drop.
         pub fn to_hex(&self) -> String {
             encode_hex(&self[..])
         }
     }
 
+    /// The secret bytes are never printed.
     impl fmt::Debug for $name {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            write!(f, stringify!($name))?;
-            write!(f, "(")?;
-            for i in &self[0..BYTES_IN_DEBUG] {
-                write!(f, "{:02X}", i)?
-            }
-            write!(f, "...)")
+            write!(f, concat!(stringify!($name), "(****)"))
         }
     }
 
@@ -439,6 +580,7 @@ implement_public_sodium_wrapper! {
 /// # crypto::init();
 /// let (public_key, _) = crypto::gen_keypair();
 /// ```
+#[serde(rename = "$exonum::PublicKey")]
     struct PublicKey, PublicKeySodium, PUBLIC_KEY_LENGTH
 }
 
@@ -485,6 +627,7 @@ implement_public_sodium_wrapper! {
 /// let hash_from_data = crypto::hash(&data);
 /// let default_hash = Hash::default();
 /// ```
+#[serde(rename = "$exonum::Hash")]
     struct Hash, DigestSodium, HASH_SIZE
 }
 
@@ -511,6 +654,7 @@ implement_public_sodium_wrapper! {
 /// let signature = crypto::sign(&data, &secret_key);
 /// assert!(crypto::verify(&signature, &data, &public_key));
 /// ```
+#[serde(rename = "$exonum::Signature")]
     struct Signature, SignatureSodium, SIGNATURE_LENGTH
 }
 
@@ -545,12 +689,11 @@ macro_rules! implement_serde {
         type Error = FromHexError;
 
         fn from_hex<T: AsRef<[u8]>>(v: T) -> Result<Self, Self::Error> {
-            let bytes = Vec::<u8>::from_hex(v)?;
-            if let Some(self_value) = Self::from_slice(bytes.as_ref()) {
-                Ok(self_value)
-            } else {
-                Err(FromHexError::InvalidStringLength)
-            }
+            let mut bytes = Vec::<u8>::from_hex(v)?;
+            let value = Self::from_slice(bytes.as_ref());
+            // The decoded bytes may be secret.
+            memzero(&mut bytes);
+            value.ok_or(FromHexError::InvalidStringLength)
         }
     }
     )
@@ -562,6 +705,54 @@ implement_serde! {SecretKey}
 implement_serde! {Seed}
 implement_serde! {Signature}
 
+/// Wrapper, which explicitly allows to serialize and deserialize a secret,
+/// for example, to store it in a configuration file.
+///
+/// The secret is serialized in the same way as the public values of the same length.
+///
+/// # Examples
+///
+/// ```
+/// extern crate exonum;
+/// extern crate serde_json;
+///
+/// use exonum::crypto::{ExposeSecret, Seed};
+///
+/// # fn main() {
+/// let seed = Seed::new([1; 32]);
+/// let json = serde_json::to_string(&ExposeSecret(&seed)).unwrap();
+/// let ExposeSecret(restored): ExposeSecret<Seed> = serde_json::from_str(&json).unwrap();
+/// assert_eq!(restored, seed);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExposeSecret<T>(pub T);
+
+macro_rules! implement_expose_secret {
+    ($name:ident, $name_from:ident) => (
+    impl Serialize for ExposeSecret<$name> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            ExposeSecret(&self.0).serialize(serializer)
+        }
+    }
+
+    impl<'a> Serialize for ExposeSecret<&'a $name> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_newtype_struct(stringify!($name), &(self.0).0)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for ExposeSecret<$name> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            $name_from::deserialize(deserializer).map(|secret| ExposeSecret($name(secret)))
+        }
+    }
+    )
+}
+
+implement_expose_secret! {SecretKey, SecretKeySodium}
+implement_expose_secret! {Seed, SeedSodium}
+
 macro_rules! implement_index_traits {
     ($new_type:ident) => (
         impl Index<Range<usize>> for $new_type {
@@ -672,6 +863,7 @@ impl CryptoHash for i64 {
     }
 }
 
+#[cfg(test)]
 const EMPTY_SLICE_HASH: Hash = Hash(DigestSodium([
     227, 176, 196, 66, 152, 252, 28, 20, 154, 251, 244, 200, 153, 111, 185, 36, 39, 174, 65, 228,
     100, 155, 147, 76, 164, 149, 153, 27, 120, 82, 184, 85,
@@ -679,7 +871,7 @@ const EMPTY_SLICE_HASH: Hash = Hash(DigestSodium([
 
 impl CryptoHash for () {
     fn hash(&self) -> Hash {
-        EMPTY_SLICE_HASH
+        hash(&[])
     }
 }
 
@@ -743,6 +935,24 @@ impl CryptoHash for Round {
     }
 }
 
+#[cfg(feature = "float_serialize")]
+impl CryptoHash for F32 {
+    fn hash(&self) -> Hash {
+        let mut v = [0; 4];
+        LittleEndian::write_f32(&mut v, self.get());
+        hash(&v)
+    }
+}
+
+#[cfg(feature = "float_serialize")]
+impl CryptoHash for F64 {
+    fn hash(&self) -> Hash {
+        let mut v = [0; 8];
+        LittleEndian::write_f64(&mut v, self.get());
+        hash(&v)
+    }
+}
+
 impl CryptoHash for Uuid {
     fn hash(&self) -> Hash {
         hash(self.as_bytes())
@@ -755,12 +965,19 @@ impl CryptoHash for Decimal {
     }
 }
 
+mod address;
+mod algorithm;
+mod derivation;
+mod domain;
+mod key_file;
+mod signer;
+
 #[cfg(test)]
 mod tests {
     use serde_json;
     use encoding::serialize::FromHex;
-    use super::{gen_keypair, hash, Hash, HashStream, PublicKey, SecretKey, Seed, SignStream,
-                Signature, EMPTY_SLICE_HASH};
+    use super::{gen_keypair, hash, sign, verify_batch, ExposeSecret, Hash, HashAlgorithm,
+                HashStream, PublicKey, SecretKey, Seed, SignStream, Signature, EMPTY_SLICE_HASH};
 
     #[test]
     fn test_hash() {
@@ -797,14 +1014,15 @@ mod tests {
         let h1 = serde_json::from_str(&json_h).unwrap();
         assert_eq!(h, h1);
 
+        // Secrets are serialized only through an explicit wrapper.
         let h = Seed::new([210; 32]);
-        let json_h = serde_json::to_string(&h).unwrap();
-        let h1 = serde_json::from_str(&json_h).unwrap();
+        let json_h = serde_json::to_string(&ExposeSecret(&h)).unwrap();
+        let ExposeSecret(h1): ExposeSecret<Seed> = serde_json::from_str(&json_h).unwrap();
         assert_eq!(h, h1);
 
         let h = SecretKey::new([211; 64]);
-        let json_h = serde_json::to_string(&h).unwrap();
-        let h1 = serde_json::from_str(&json_h).unwrap();
+        let json_h = serde_json::to_string(&ExposeSecret(h.clone())).unwrap();
+        let ExposeSecret(h1): ExposeSecret<SecretKey> = serde_json::from_str(&json_h).unwrap();
         assert_eq!(h, h1);
     }
 
@@ -817,22 +1035,23 @@ mod tests {
         let pk = PublicKey::new([15; 32]);
         assert_eq!(format!("{:?}", &pk), "PublicKey(0F0F0F0F)");
         let sk = SecretKey::new([8; 64]);
-        assert_eq!(format!("{:?}", &sk), "SecretKey(08080808...)");
+        assert_eq!(format!("{:?}", &sk), "SecretKey(****)");
         let signature = Signature::new([10; 64]);
         assert_eq!(format!("{:?}", &signature), "Signature(0A0A0A0A)");
         let seed = Seed::new([4; 32]);
-        assert_eq!(format!("{:?}", &seed), "Seed(04040404...)");
+        assert_eq!(format!("{:?}", &seed), "Seed(****)");
 
         // Check no padding
         let hash = Hash::new([128; 32]);
         assert_eq!(format!("{:?}", &hash), "Hash(80808080)");
         let sk = SecretKey::new([255; 64]);
-        assert_eq!(format!("{:?}", &sk), "SecretKey(FFFFFFFF...)");
+        assert_eq!(format!("{:?}", &sk), "SecretKey(****)");
+        assert_eq!(format!("{:?}", ExposeSecret(&sk)), "ExposeSecret(SecretKey(****))");
     }
 
     #[test]
     fn test_range_sodium() {
-        let h = hash(&[]);
+        let h = HashAlgorithm::Sha256.hash(&[]);
         let sub_range = &h[10..20];
         assert_eq!(
             &[244u8, 200, 153, 111, 185, 36, 39, 174, 65, 228],
@@ -876,8 +1095,39 @@ mod tests {
         assert!(verified_stream.verify(&sig, &pk));
     }
 
+    #[test]
+    fn test_verify_batch() {
+        let keys = (0..4).map(|_| gen_keypair()).collect::<Vec<_>>();
+        let data = (0..200_u32)
+            .map(|i| i.to_le_bytes().to_vec())
+            .collect::<Vec<_>>();
+        let mut signatures = data.iter()
+            .enumerate()
+            .map(|(i, data)| sign(data, &keys[i % 4].1))
+            .collect::<Vec<_>>();
+        signatures[7] = signatures[8];
+        signatures[150] = Signature::zero();
+
+        let batch = |len: usize| {
+            data.iter()
+                .zip(&signatures)
+                .enumerate()
+                .take(len)
+                .map(|(i, (data, signature))| (data.as_ref(), signature, &keys[i % 4].0))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(verify_batch(&[]), Ok(()));
+        assert_eq!(verify_batch(&batch(7)), Ok(()));
+        assert_eq!(verify_batch(&batch(10)), Err(vec![7]));
+        assert_eq!(verify_batch(&batch(200)), Err(vec![7, 150]));
+
+        let mut other_key = batch(200);
+        other_key[199].2 = &keys[0].0;
+        assert_eq!(verify_batch(&other_key), Err(vec![7, 150, 199]));
+    }
+
     #[test]
     fn test_empty_slice_hash() {
-        assert_eq!(EMPTY_SLICE_HASH, hash(&[]));
+        assert_eq!(EMPTY_SLICE_HASH, HashAlgorithm::Sha256.hash(&[]));
     }
 }
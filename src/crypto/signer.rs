@@ -0,0 +1,488 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Signers, which create signatures on behalf of a public key without exposing
+//! the secret key to the caller.
+
+use failure::Error;
+
+use super::{gen_keypair, gen_keypair_from_seed, sign, PublicKey, SecretKey, Seed, Signature,
+            PUBLIC_KEY_LENGTH, SEED_LENGTH};
+
+/// Entity, which signs the data with the secret key corresponding to its public key.
+///
+/// The secret key may be kept in memory of the current process, as in the case
+/// of `SecretKey` and `InMemorySigner`, or in a separate process, as in the case
+/// of `UnixSocketSigner`.
+///
+/// # Examples
+///
+/// ```
+/// use exonum::crypto::{self, InMemorySigner, Signer};
+///
+/// # crypto::init();
+/// let signer = InMemorySigner::random();
+/// let signature = signer.sign(b"data").unwrap();
+/// assert!(crypto::verify(&signature, b"data", &signer.public_key()));
+/// ```
+pub trait Signer {
+    /// Returns the public key, which verifies the signatures of the signer.
+    fn public_key(&self) -> PublicKey;
+
+    /// Signs the data.
+    fn sign(&self, data: &[u8]) -> Result<Signature, Error>;
+}
+
+impl<'a, S: Signer + ?Sized> Signer for &'a S {
+    fn public_key(&self) -> PublicKey {
+        (**self).public_key()
+    }
+
+    fn sign(&self, data: &[u8]) -> Result<Signature, Error> {
+        (**self).sign(data)
+    }
+}
+
+impl<S: Signer + ?Sized> Signer for Box<S> {
+    fn public_key(&self) -> PublicKey {
+        (**self).public_key()
+    }
+
+    fn sign(&self, data: &[u8]) -> Result<Signature, Error> {
+        (**self).sign(data)
+    }
+}
+
+/// Ed25519 secret key contains the seed followed by the public key.
+impl Signer for SecretKey {
+    fn public_key(&self) -> PublicKey {
+        PublicKey::from_slice(&self[SEED_LENGTH..SEED_LENGTH + PUBLIC_KEY_LENGTH])
+            .expect("Secret key contains the public key.")
+    }
+
+    fn sign(&self, data: &[u8]) -> Result<Signature, Error> {
+        Ok(sign(data, self))
+    }
+}
+
+/// Signer, which keeps the keypair in memory of the current process.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InMemorySigner {
+    public_key: PublicKey,
+    secret_key: SecretKey,
+}
+
+impl InMemorySigner {
+    /// Creates a signer with the given keypair.
+    pub fn new(public_key: PublicKey, secret_key: SecretKey) -> Self {
+        InMemorySigner {
+            public_key,
+            secret_key,
+        }
+    }
+
+    /// Creates a signer with the keypair generated from the seed.
+    pub fn from_seed(seed: &Seed) -> Self {
+        let (public_key, secret_key) = gen_keypair_from_seed(seed);
+        InMemorySigner::new(public_key, secret_key)
+    }
+
+    /// Creates a signer with a random keypair.
+    pub fn random() -> Self {
+        let (public_key, secret_key) = gen_keypair();
+        InMemorySigner::new(public_key, secret_key)
+    }
+
+    /// Returns the secret key of the signer.
+    pub fn secret_key(&self) -> &SecretKey {
+        &self.secret_key
+    }
+}
+
+impl Signer for InMemorySigner {
+    fn public_key(&self) -> PublicKey {
+        self.public_key
+    }
+
+    fn sign(&self, data: &[u8]) -> Result<Signature, Error> {
+        Ok(sign(data, &self.secret_key))
+    }
+}
+
+#[cfg(unix)]
+pub use self::unix::{SignerServer, UnixSocketSigner};
+
+#[cfg(unix)]
+mod unix {
+    use byteorder::{ByteOrder, LittleEndian};
+    use failure::Error;
+
+    use std::fs::{self, Permissions};
+    use std::io::{self, Read, Write};
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    use super::Signer;
+    use crypto::{verify, PublicKey, Signature, PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH};
+
+    const PUBLIC_KEY_REQUEST: u8 = 0;
+    const SIGN_REQUEST: u8 = 1;
+    const STATUS_OK: u8 = 0;
+    const STATUS_ERROR: u8 = 1;
+    /// Maximum length of the data accepted by the server.
+    const MAX_DATA_LENGTH: usize = 16 * 1024 * 1024;
+    /// Timeout of reading or writing a request or a response.
+    const IO_TIMEOUT: Duration = Duration::from_secs(10);
+    /// Number of connections served by `SignerServer::run` at the same time.
+    const MAX_CONNECTIONS: usize = 8;
+
+    /// Signer, which forwards the data to a separate process listening on a Unix socket.
+    ///
+    /// Each request is sent over a new connection and consists of a tag byte, which is
+    /// `0` for the public key request and `1` for the signature request. The latter is
+    /// followed by the length of the data as `u32` in little endian and the data itself.
+    /// The response consists of a status byte, which is `0` on success, and the public
+    /// key or the signature respectively. Reads and writes time out after 10 seconds.
+    /// Signatures, which don't match the public key of the signer, are rejected.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct UnixSocketSigner {
+        path: PathBuf,
+        public_key: PublicKey,
+    }
+
+    impl UnixSocketSigner {
+        /// Connects to the signer process listening on the given path
+        /// and requests its public key.
+        pub fn connect<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+            let path = path.as_ref().to_owned();
+            let mut stream = connect(&path)?;
+            stream.write_all(&[PUBLIC_KEY_REQUEST])?;
+            let response = read_response(&mut stream, PUBLIC_KEY_LENGTH)?;
+            let public_key = PublicKey::from_slice(&response).expect("Incorrect key length.");
+            Ok(UnixSocketSigner { path, public_key })
+        }
+
+        /// Returns the path of the signer socket.
+        pub fn path(&self) -> &Path {
+            &self.path
+        }
+    }
+
+    impl Signer for UnixSocketSigner {
+        fn public_key(&self) -> PublicKey {
+            self.public_key
+        }
+
+        fn sign(&self, data: &[u8]) -> Result<Signature, Error> {
+            if data.len() > MAX_DATA_LENGTH {
+                bail!("Data of {} bytes is too long to be signed", data.len());
+            }
+            let mut request = vec![SIGN_REQUEST, 0, 0, 0, 0];
+            LittleEndian::write_u32(&mut request[1..], data.len() as u32);
+            request.extend_from_slice(data);
+
+            let mut stream = connect(&self.path)?;
+            stream.write_all(&request)?;
+            let response = read_response(&mut stream, SIGNATURE_LENGTH)?;
+            let signature = Signature::from_slice(&response).expect("Incorrect signature length.");
+            if !verify(&signature, data, &self.public_key) {
+                bail!("Signer process has returned an invalid signature");
+            }
+            Ok(signature)
+        }
+    }
+
+    fn connect(path: &Path) -> io::Result<UnixStream> {
+        let stream = UnixStream::connect(path)?;
+        set_timeouts(&stream)?;
+        Ok(stream)
+    }
+
+    fn set_timeouts(stream: &UnixStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(IO_TIMEOUT))?;
+        stream.set_write_timeout(Some(IO_TIMEOUT))
+    }
+
+    fn read_response(stream: &mut UnixStream, length: usize) -> Result<Vec<u8>, Error> {
+        let mut status = [0];
+        stream.read_exact(&mut status)?;
+        if status[0] != STATUS_OK {
+            bail!("Signer process has rejected the request");
+        }
+        let mut response = vec![0; length];
+        stream.read_exact(&mut response)?;
+        Ok(response)
+    }
+
+    /// Signer process stand-in, which serves the requests of `UnixSocketSigner`
+    /// with the wrapped signer.
+    ///
+    /// The socket is accessible by the owner of the process only. Since the socket
+    /// is connectable as soon as it is bound, it must be created in a directory
+    /// inaccessible by the other users, e.g. with `0700` mode; otherwise `bind` fails.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use exonum::crypto::{InMemorySigner, SignerServer};
+    ///
+    /// // `/run/exonum` is only accessible by the owner.
+    /// let server = SignerServer::bind("/run/exonum/signer.sock", InMemorySigner::random())
+    ///     .unwrap();
+    /// server.run().unwrap();
+    /// ```
+    #[derive(Debug)]
+    pub struct SignerServer<S: Signer> {
+        listener: UnixListener,
+        signer: Arc<S>,
+    }
+
+    impl<S: Signer> SignerServer<S> {
+        /// Creates a server listening on the given path, which should not exist.
+        /// The parent directory must not be accessible by the group or the others.
+        pub fn bind<P: AsRef<Path>>(path: P, signer: S) -> io::Result<Self> {
+            check_private_dir(path.as_ref())?;
+            let listener = UnixListener::bind(&path)?;
+            fs::set_permissions(&path, Permissions::from_mode(0o600))?;
+            Ok(SignerServer {
+                listener,
+                signer: Arc::new(signer),
+            })
+        }
+
+        /// Accepts the next connection and serves its request on the current thread.
+        /// Errors of the connection are reported to the client and are not returned.
+        pub fn handle_next(&self) -> Result<(), Error> {
+            let (stream, _) = self.listener.accept()?;
+            serve(&*self.signer, stream);
+            Ok(())
+        }
+    }
+
+    impl<S: Signer + Send + Sync + 'static> SignerServer<S> {
+        /// Serves the requests until the socket fails. Connections are served by
+        /// a fixed number of threads, so a stalled client doesn't delay the others
+        /// while the number of threads stays bounded.
+        pub fn run(&self) -> Result<(), Error> {
+            let workers = (0..MAX_CONNECTIONS)
+                .map(|_| {
+                    let listener = self.listener.try_clone()?;
+                    let signer = Arc::clone(&self.signer);
+                    Ok(thread::spawn(move || -> io::Result<()> {
+                        loop {
+                            let (stream, _) = listener.accept()?;
+                            serve(&*signer, stream);
+                        }
+                    }))
+                })
+                .collect::<io::Result<Vec<_>>>()?;
+            for worker in workers {
+                worker
+                    .join()
+                    .map_err(|_| format_err!("Signer server thread has panicked"))??;
+            }
+            Ok(())
+        }
+    }
+
+    /// Checks that the directory of the socket prevents the other users from connecting
+    /// before the permissions of the socket itself are restricted.
+    fn check_private_dir(path: &Path) -> io::Result<()> {
+        let dir = match path.parent() {
+            Some(dir) if dir != Path::new("") => dir,
+            _ => Path::new("."),
+        };
+        let mode = fs::metadata(dir)?.permissions().mode();
+        if mode & 0o077 != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!(
+                    "Directory {} of the signer socket is accessible by other users \
+                     (mode {:o})",
+                    dir.display(),
+                    mode & 0o777
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    fn serve<S: Signer + ?Sized>(signer: &S, mut stream: UnixStream) {
+        let response = set_timeouts(&stream)
+            .map_err(Error::from)
+            .and_then(|()| respond(signer, &mut stream));
+        match response {
+            Ok(response) => {
+                let _ = stream.write_all(&[STATUS_OK]);
+                let _ = stream.write_all(&response);
+            }
+            Err(_) => {
+                let _ = stream.write_all(&[STATUS_ERROR]);
+            }
+        }
+    }
+
+    fn respond<S: Signer + ?Sized>(signer: &S, stream: &mut UnixStream) -> Result<Vec<u8>, Error> {
+        let mut tag = [0];
+        stream.read_exact(&mut tag)?;
+        match tag[0] {
+            PUBLIC_KEY_REQUEST => Ok(signer.public_key().as_ref().to_vec()),
+            SIGN_REQUEST => {
+                let mut length = [0; 4];
+                stream.read_exact(&mut length)?;
+                let length = LittleEndian::read_u32(&length) as usize;
+                if length > MAX_DATA_LENGTH {
+                    bail!("Data of {} bytes is too long to be signed", length);
+                }
+                let mut data = vec![0; length];
+                stream.read_exact(&mut data)?;
+                Ok(signer.sign(&data)?.as_ref().to_vec())
+            }
+            tag => bail!("Unknown request {}", tag),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::thread;
+
+    use failure::Error;
+
+    use super::{InMemorySigner, Signer};
+    use crypto::{gen_keypair_from_seed, sign, verify, PublicKey, Seed, Signature};
+
+    #[test]
+    fn test_in_memory_signer() {
+        let (public_key, secret_key) = gen_keypair_from_seed(&Seed::new([3; 32]));
+        let signer = InMemorySigner::from_seed(&Seed::new([3; 32]));
+        assert_eq!(signer.public_key(), public_key);
+        assert_eq!(Signer::public_key(&secret_key), public_key);
+        assert_eq!(signer.sign(b"data").unwrap(), sign(b"data", &secret_key));
+        assert_eq!(Signer::sign(&secret_key, b"data").unwrap(), sign(b"data", &secret_key));
+
+        let boxed: Box<Signer> = Box::new(signer.clone());
+        assert_eq!(boxed.sign(b"data").unwrap(), sign(b"data", &secret_key));
+    }
+
+    /// Creates a directory for the signer socket accessible by the owner only.
+    #[cfg(unix)]
+    fn private_dir(name: &str) -> PathBuf {
+        use std::os::unix::fs::DirBuilderExt;
+
+        let dir = env::temp_dir().join(format!("{}-{}", name, ::std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::DirBuilder::new().mode(0o700).create(&dir).unwrap();
+        dir
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unix_socket_signer() {
+        use super::{SignerServer, UnixSocketSigner};
+
+        let dir = private_dir("exonum-signer");
+        let path = dir.join("signer.sock");
+        let signer = InMemorySigner::from_seed(&Seed::new([4; 32]));
+        let server = SignerServer::bind(&path, signer.clone()).unwrap();
+        let handle = thread::spawn(move || {
+            for _ in 0..3 {
+                server.handle_next().unwrap();
+            }
+        });
+
+        let remote = UnixSocketSigner::connect(&path).unwrap();
+        assert_eq!(remote.public_key(), signer.public_key());
+        let signature = remote.sign(b"remote data").unwrap();
+        assert!(verify(&signature, b"remote data", &signer.public_key()));
+        assert_eq!(remote.sign(&[]).unwrap(), signer.sign(&[]).unwrap());
+
+        handle.join().unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Signer, which claims a public key other than the one it signs with.
+    struct ImpostorSigner(InMemorySigner);
+
+    impl Signer for ImpostorSigner {
+        fn public_key(&self) -> PublicKey {
+            gen_keypair_from_seed(&Seed::new([7; 32])).0
+        }
+
+        fn sign(&self, data: &[u8]) -> Result<Signature, Error> {
+            self.0.sign(data)
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unix_socket_signer_rejects_invalid_signature() {
+        use super::{SignerServer, UnixSocketSigner};
+
+        let dir = private_dir("exonum-signer-impostor");
+        let path = dir.join("signer.sock");
+        let signer = ImpostorSigner(InMemorySigner::from_seed(&Seed::new([6; 32])));
+        let server = SignerServer::bind(&path, signer).unwrap();
+        let handle = thread::spawn(move || {
+            for _ in 0..2 {
+                server.handle_next().unwrap();
+            }
+        });
+
+        let remote = UnixSocketSigner::connect(&path).unwrap();
+        assert!(remote.sign(b"data").is_err());
+
+        handle.join().unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_signer_server_is_private_and_concurrent() {
+        use std::os::unix::fs::PermissionsExt;
+        use std::os::unix::net::UnixStream;
+
+        use super::{SignerServer, UnixSocketSigner};
+
+        let dir = private_dir("exonum-signer-run");
+        let path = dir.join("signer.sock");
+        let signer = InMemorySigner::from_seed(&Seed::new([5; 32]));
+
+        // The socket isn't bound in a directory accessible by other users.
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(SignerServer::bind(&path, signer.clone()).is_err());
+        assert!(!path.exists());
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o700)).unwrap();
+
+        let server = SignerServer::bind(&path, signer.clone()).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        thread::spawn(move || server.run());
+
+        // A client, which connects and sends nothing, doesn't block the others.
+        let _stalled = UnixStream::connect(&path).unwrap();
+        let remote = UnixSocketSigner::connect(&path).unwrap();
+        let signature = remote.sign(b"data").unwrap();
+        assert!(verify(&signature, b"data", &signer.public_key()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,667 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Seeded generator of arbitrary values for the property tests of the encoding.
+//!
+//! Every value is generated from a seed and the number of the test case, so a failed case
+//! could be reproduced without any external state.
+
+use bit_vec::BitVec;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use rand::{Rand, Rng, SeedableRng, XorShiftRng};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+
+use crypto::{chain_id, gen_keypair_from_seed, Hash, PublicKey, Seed, Signature, DEFAULT_NETWORK};
+#[cfg(feature = "float_serialize")]
+use encoding::{F32, F64};
+use messages::{Block, BlockRequest, BlockResponse, Connect, PeersRequest, Precommit, Prevote,
+               PrevotesRequest, Propose, ProposeRequest, SignedMessage, Status,
+               TransactionsRequest, TransactionsResponse, WithoutEncodingStatus};
+use types::{Height, Round, ValidatorId};
+
+// Limits of the generated time, years from 1 to 9999.
+const MIN_TIMESTAMP: i64 = -62_135_596_800;
+const MAX_TIMESTAMP: i64 = 253_402_300_799;
+const NANOS_PER_SECOND: i64 = 1_000_000_000;
+
+// Characters of the generated strings, including multibyte and escaped in json ones.
+const CHARS: &[char] = &['a', 'z', 'A', '0', ' ', '"', '\\', '\n', '\u{0}', 'é', 'ß', '€', '😀'];
+
+/// Source of arbitrary values.
+pub struct Gen {
+    rng: XorShiftRng,
+    size: usize,
+}
+
+impl Gen {
+    /// Creates a generator for the given test case.
+    pub fn new(seed: u32, case: u32) -> Self {
+        Gen {
+            // Xorshift seed must not be all zeros.
+            rng: XorShiftRng::from_seed([seed, case, 0x9e37_79b9, 0x7f4a_7c15]),
+            size: 8,
+        }
+    }
+
+    /// Returns maximal length of the generated collections.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns length of a collection.
+    pub fn len(&mut self) -> usize {
+        self.rng.gen_range(0, self.size + 1)
+    }
+
+    /// Returns uniformly distributed value.
+    pub fn gen<T: Rand>(&mut self) -> T {
+        self.rng.gen()
+    }
+
+    /// Returns value in the `[low, high)` range.
+    pub fn gen_range(&mut self, low: i64, high: i64) -> i64 {
+        self.rng.gen_range(low, high)
+    }
+
+    /// Returns `true` with the probability of `1 / n`.
+    pub fn one_in(&mut self, n: u32) -> bool {
+        self.rng.gen_weighted_bool(n)
+    }
+
+    /// Returns one of the given items.
+    pub fn choose<T: Clone>(&mut self, items: &[T]) -> T {
+        self.rng
+            .choose(items)
+            .expect("Items should not be empty")
+            .clone()
+    }
+
+    /// Returns random bytes.
+    pub fn bytes(&mut self, len: usize) -> Vec<u8> {
+        let mut bytes = vec![0; len];
+        self.rng.fill_bytes(&mut bytes);
+        bytes
+    }
+}
+
+/// Type with arbitrary values.
+pub trait Arbitrary: Sized {
+    /// Generates a value.
+    fn arbitrary(gen: &mut Gen) -> Self;
+}
+
+/// Integers are biased to the boundary values.
+macro_rules! impl_arbitrary_int {
+    ($($typename:ty),*) => {$(
+        impl Arbitrary for $typename {
+            fn arbitrary(gen: &mut Gen) -> Self {
+                if gen.one_in(4) {
+                    gen.choose(&[0, 1, <$typename>::min_value(), <$typename>::max_value()])
+                } else {
+                    gen.gen()
+                }
+            }
+        }
+    )*};
+}
+
+impl_arbitrary_int!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+impl Arbitrary for bool {
+    fn arbitrary(gen: &mut Gen) -> Self {
+        gen.gen()
+    }
+}
+
+macro_rules! impl_arbitrary_newtype {
+    ($($typename:ident($inner:ty)),*) => {$(
+        impl Arbitrary for $typename {
+            fn arbitrary(gen: &mut Gen) -> Self {
+                $typename(<$inner>::arbitrary(gen))
+            }
+        }
+    )*};
+}
+
+impl_arbitrary_newtype!(Height(u64), Round(u32), ValidatorId(u16));
+
+macro_rules! impl_arbitrary_crypto {
+    ($($typename:ident: $size:expr),*) => {$(
+        impl Arbitrary for $typename {
+            fn arbitrary(gen: &mut Gen) -> Self {
+                $typename::from_slice(&gen.bytes($size)).expect("Size should be correct")
+            }
+        }
+    )*};
+}
+
+impl_arbitrary_crypto!(Hash: 32, PublicKey: 32, Signature: 64);
+
+impl Arbitrary for DateTime<Utc> {
+    fn arbitrary(gen: &mut Gen) -> Self {
+        let secs = gen.gen_range(MIN_TIMESTAMP, MAX_TIMESTAMP + 1);
+        let nanos = gen.gen_range(0, NANOS_PER_SECOND) as u32;
+        Utc.timestamp(secs, nanos)
+    }
+}
+
+/// Seconds and nanoseconds have the same sign, as required by the binary representation.
+impl Arbitrary for Duration {
+    fn arbitrary(gen: &mut Gen) -> Self {
+        let secs = gen.gen_range(MIN_TIMESTAMP, MAX_TIMESTAMP + 1);
+        let nanos = gen.gen_range(0, NANOS_PER_SECOND);
+        let nanos = if secs < 0 || (secs == 0 && gen.gen()) {
+            -nanos
+        } else {
+            nanos
+        };
+        Duration::seconds(secs) + Duration::nanoseconds(nanos)
+    }
+}
+
+impl Arbitrary for SocketAddr {
+    fn arbitrary(gen: &mut Gen) -> Self {
+        if gen.gen() {
+            let octets = gen.gen::<[u8; 4]>();
+            SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::from(octets), gen.gen()))
+        } else {
+            let segments = gen.gen::<[u16; 8]>();
+            SocketAddr::V6(SocketAddrV6::new(
+                Ipv6Addr::new(
+                    segments[0],
+                    segments[1],
+                    segments[2],
+                    segments[3],
+                    segments[4],
+                    segments[5],
+                    segments[6],
+                    segments[7],
+                ),
+                gen.gen(),
+                0,
+                0,
+            ))
+        }
+    }
+}
+
+impl Arbitrary for Uuid {
+    fn arbitrary(gen: &mut Gen) -> Self {
+        Uuid::from_bytes(&gen.bytes(16)).expect("Size should be correct")
+    }
+}
+
+impl Arbitrary for Decimal {
+    fn arbitrary(gen: &mut Gen) -> Self {
+        let scale = gen.gen_range(0, 29) as u32;
+        // `Decimal::new` negates the number, so `i64::MIN` overflows.
+        let num = i64::arbitrary(gen).max(-i64::max_value());
+        Decimal::new(num, scale)
+    }
+}
+
+impl Arbitrary for BitVec {
+    fn arbitrary(gen: &mut Gen) -> Self {
+        let len = gen.len() * 3;
+        (0..len).map(|_| gen.gen()).collect()
+    }
+}
+
+impl Arbitrary for String {
+    fn arbitrary(gen: &mut Gen) -> Self {
+        let len = gen.len();
+        (0..len).map(|_| gen.choose(CHARS)).collect()
+    }
+}
+
+#[cfg(feature = "float_serialize")]
+impl Arbitrary for F32 {
+    fn arbitrary(gen: &mut Gen) -> Self {
+        loop {
+            if let Some(value) = F32::try_from(f32::from_bits(gen.gen())) {
+                return value;
+            }
+        }
+    }
+}
+
+#[cfg(feature = "float_serialize")]
+impl Arbitrary for F64 {
+    fn arbitrary(gen: &mut Gen) -> Self {
+        loop {
+            if let Some(value) = F64::try_from(f64::from_bits(gen.gen())) {
+                return value;
+            }
+        }
+    }
+}
+
+impl<T: Arbitrary> Arbitrary for Vec<T> {
+    fn arbitrary(gen: &mut Gen) -> Self {
+        let len = gen.len();
+        (0..len).map(|_| T::arbitrary(gen)).collect()
+    }
+}
+
+impl Arbitrary for WithoutEncodingStatus {
+    fn arbitrary(gen: &mut Gen) -> Self {
+        WithoutEncodingStatus {
+            height: Height::arbitrary(gen),
+            last_hash: Hash::arbitrary(gen),
+        }
+    }
+}
+
+/// Messages are signed in the default network by a key derived from the generator,
+/// their payload should be readable by the binary form of signed messages.
+impl Arbitrary for SignedMessage {
+    fn arbitrary(gen: &mut Gen) -> Self {
+        let mut seed = [0; 32];
+        seed.copy_from_slice(&gen.bytes(32));
+        let (public_key, secret_key) = gen_keypair_from_seed(&Seed::new(seed));
+        let payload = WithoutEncodingStatus::arbitrary(gen);
+        SignedMessage::new(payload, public_key, &chain_id(DEFAULT_NETWORK), &secret_key)
+            .expect("Message should be signed")
+    }
+}
+
+// Rounds of the consensus messages start from `Round::first()`.
+fn consensus_round(gen: &mut Gen) -> Round {
+    Round(gen.gen_range(i64::from(Round::first().0), i64::from(u32::max_value()) + 1) as u32)
+}
+
+impl Arbitrary for Block {
+    fn arbitrary(gen: &mut Gen) -> Self {
+        Block::new(
+            Arbitrary::arbitrary(gen),
+            Arbitrary::arbitrary(gen),
+            Arbitrary::arbitrary(gen),
+            Arbitrary::arbitrary(gen),
+            &Arbitrary::arbitrary(gen),
+            &Arbitrary::arbitrary(gen),
+            &Arbitrary::arbitrary(gen),
+        )
+    }
+}
+
+impl Arbitrary for Connect {
+    fn arbitrary(gen: &mut Gen) -> Self {
+        let mut user_agent = String::arbitrary(gen);
+        user_agent.insert(0, gen.choose(CHARS));
+        Connect::new(
+            Arbitrary::arbitrary(gen),
+            Arbitrary::arbitrary(gen),
+            &user_agent,
+        )
+    }
+}
+
+impl Arbitrary for Status {
+    fn arbitrary(gen: &mut Gen) -> Self {
+        Status::new(Arbitrary::arbitrary(gen), &Arbitrary::arbitrary(gen))
+    }
+}
+
+impl Arbitrary for Propose {
+    fn arbitrary(gen: &mut Gen) -> Self {
+        Propose::new(
+            Arbitrary::arbitrary(gen),
+            Arbitrary::arbitrary(gen),
+            consensus_round(gen),
+            &Arbitrary::arbitrary(gen),
+            &Vec::<Hash>::arbitrary(gen),
+        )
+    }
+}
+
+impl Arbitrary for Prevote {
+    fn arbitrary(gen: &mut Gen) -> Self {
+        let round = consensus_round(gen);
+        let locked_round = Round(gen.gen_range(0, i64::from(round.0)) as u32);
+        Prevote::new(
+            Arbitrary::arbitrary(gen),
+            Arbitrary::arbitrary(gen),
+            round,
+            &Arbitrary::arbitrary(gen),
+            locked_round,
+        )
+    }
+}
+
+impl Arbitrary for Precommit {
+    fn arbitrary(gen: &mut Gen) -> Self {
+        Precommit::new(
+            Arbitrary::arbitrary(gen),
+            Arbitrary::arbitrary(gen),
+            consensus_round(gen),
+            &Arbitrary::arbitrary(gen),
+            &Arbitrary::arbitrary(gen),
+            Arbitrary::arbitrary(gen),
+        )
+    }
+}
+
+impl Arbitrary for BlockResponse {
+    fn arbitrary(gen: &mut Gen) -> Self {
+        BlockResponse::new(
+            &Arbitrary::arbitrary(gen),
+            Arbitrary::arbitrary(gen),
+            Arbitrary::arbitrary(gen),
+            &Vec::<Hash>::arbitrary(gen),
+        )
+    }
+}
+
+impl Arbitrary for TransactionsResponse {
+    fn arbitrary(gen: &mut Gen) -> Self {
+        TransactionsResponse::new(&Arbitrary::arbitrary(gen), Arbitrary::arbitrary(gen))
+    }
+}
+
+impl Arbitrary for ProposeRequest {
+    fn arbitrary(gen: &mut Gen) -> Self {
+        ProposeRequest::new(
+            &Arbitrary::arbitrary(gen),
+            Arbitrary::arbitrary(gen),
+            &Arbitrary::arbitrary(gen),
+        )
+    }
+}
+
+impl Arbitrary for TransactionsRequest {
+    fn arbitrary(gen: &mut Gen) -> Self {
+        TransactionsRequest::new(&Arbitrary::arbitrary(gen), &Vec::<Hash>::arbitrary(gen))
+    }
+}
+
+impl Arbitrary for PrevotesRequest {
+    fn arbitrary(gen: &mut Gen) -> Self {
+        PrevotesRequest::new(
+            &Arbitrary::arbitrary(gen),
+            Arbitrary::arbitrary(gen),
+            consensus_round(gen),
+            &Arbitrary::arbitrary(gen),
+            Arbitrary::arbitrary(gen),
+        )
+    }
+}
+
+impl Arbitrary for PeersRequest {
+    fn arbitrary(gen: &mut Gen) -> Self {
+        PeersRequest::new(&Arbitrary::arbitrary(gen))
+    }
+}
+
+impl Arbitrary for BlockRequest {
+    fn arbitrary(gen: &mut Gen) -> Self {
+        BlockRequest::new(&Arbitrary::arbitrary(gen), Arbitrary::arbitrary(gen))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bit_vec::BitVec;
+    use chrono::{DateTime, Duration, Utc};
+    use rust_decimal::Decimal;
+    use uuid::Uuid;
+
+    use std::any::Any;
+    use std::fmt::Debug;
+    use std::net::SocketAddr;
+    use std::panic::{self, AssertUnwindSafe};
+
+    use crypto::{CryptoHash, Hash, HashAlgorithm, HashStream, PublicKey};
+    use encoding::serialize::json::ExonumJson;
+    use encoding::serialize::FromHex;
+    use encoding::Field;
+    #[cfg(feature = "float_serialize")]
+    use encoding::{F32, F64};
+    use messages::{Block, BlockRequest, BlockResponse, Connect, PeersRequest, Precommit,
+                   Prevote, PrevotesRequest, Propose, ProposeRequest, SignedMessage, Status,
+                   TransactionsRequest, TransactionsResponse};
+    use storage::{StorageValue, UniqueHash};
+    use types::{Height, Round, ValidatorId};
+
+    use super::{Arbitrary, Gen};
+
+    const CASES: u32 = 64;
+    const MUTATIONS: usize = 16;
+
+    fn panic_message(payload: &Box<Any + Send>) -> String {
+        payload
+            .downcast_ref::<String>()
+            .cloned()
+            .or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+            .unwrap_or_default()
+    }
+
+    // Checks the property on the generated values, the failed case is reported with its seed.
+    fn for_all<T, F>(seed: u32, property: F)
+    where
+        T: Arbitrary + Debug,
+        F: Fn(&T, &mut Gen),
+    {
+        for case in 0..CASES {
+            let mut gen = Gen::new(seed, case);
+            let value = T::arbitrary(&mut gen);
+            let result = panic::catch_unwind(AssertUnwindSafe(|| property(&value, &mut gen)));
+            if let Err(payload) = result {
+                panic!(
+                    "Property failed for {:?} (seed {}, case {}): {}",
+                    value,
+                    seed,
+                    case,
+                    panic_message(&payload)
+                );
+            }
+        }
+    }
+
+    fn write<T: for<'r> Field<'r>>(value: &T) -> Vec<u8> {
+        let mut buffer = vec![0; T::field_size() as usize];
+        value.write(&mut buffer, 0, T::field_size());
+        buffer
+    }
+
+    fn check<T: for<'r> Field<'r>>(buffer: &[u8]) -> bool {
+        let size = T::field_size();
+        T::check(buffer, 0.into(), size.into(), size.into()).is_ok()
+    }
+
+    fn read<T: for<'r> Field<'r>>(buffer: &[u8]) -> T {
+        assert!(check::<T>(buffer), "Buffer should be correct");
+        unsafe { T::read(buffer, 0, T::field_size()) }
+    }
+
+    fn write_check_read<T>(value: &T, _: &mut Gen)
+    where
+        T: for<'r> Field<'r> + PartialEq + Debug,
+    {
+        let buffer = write(value);
+        assert_eq!(&read::<T>(&buffer), value);
+        // Written representation doesn't depend on the way the value was obtained.
+        assert_eq!(write(&read::<T>(&buffer)), buffer);
+    }
+
+    fn json_round_trip<T>(value: &T, _: &mut Gen)
+    where
+        T: for<'r> Field<'r> + ExonumJson + PartialEq + Debug,
+    {
+        let json = value.serialize_field().unwrap();
+        let size = T::field_size();
+        let mut buffer = vec![0; size as usize];
+        T::deserialize_field(&json, &mut buffer, 0, size).unwrap();
+        assert_eq!(&read::<T>(&buffer), value);
+    }
+
+    fn storage_round_trip<T>(value: &T, _: &mut Gen)
+    where
+        T: StorageValue + Clone + PartialEq + Debug,
+    {
+        let bytes = value.clone().into_bytes();
+        let restored = T::from_bytes(bytes.clone().into());
+        assert_eq!(&restored, value);
+        assert_eq!(restored.into_bytes(), bytes);
+    }
+
+    fn hash_is_stable<T>(value: &T, _: &mut Gen)
+    where
+        T: StorageValue + Clone,
+    {
+        let restored = T::from_bytes(value.clone().into_bytes().into());
+        assert_eq!(UniqueHash::hash(value), UniqueHash::hash(&value.clone()));
+        assert_eq!(UniqueHash::hash(value), UniqueHash::hash(&restored));
+    }
+
+    // Changes, inserts or removes bytes of the buffer, the header is never shortened,
+    // as `check` requires the header to be present.
+    fn mutate(buffer: &mut Vec<u8>, header_size: usize, gen: &mut Gen) {
+        for _ in 0..gen.len() / 2 + 1 {
+            match gen.gen_range(0, 4) {
+                0 if !buffer.is_empty() => {
+                    let pos = gen.gen_range(0, buffer.len() as i64) as usize;
+                    buffer[pos] ^= 1 << gen.gen_range(0, 8);
+                }
+                1 if !buffer.is_empty() => {
+                    let pos = gen.gen_range(0, buffer.len() as i64) as usize;
+                    let random = gen.gen();
+                    buffer[pos] = gen.choose(&[0, 1, 0x7f, 0x80, 0xff, random]);
+                }
+                2 => {
+                    let len = gen.len();
+                    buffer.extend(gen.bytes(len));
+                }
+                _ if buffer.len() > header_size => {
+                    let len = gen.gen_range(header_size as i64, buffer.len() as i64);
+                    buffer.truncate(len as usize);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn mutations_never_panic<T>(value: &T, gen: &mut Gen)
+    where
+        T: for<'r> Field<'r>,
+    {
+        let buffer = write(value);
+        for _ in 0..MUTATIONS {
+            let mut mutated = buffer.clone();
+            mutate(&mut mutated, T::field_size() as usize, gen);
+            let result = panic::catch_unwind(|| check::<T>(&mutated));
+            if let Err(payload) = result {
+                panic!(
+                    "`check` panicked on {:?}: {}",
+                    mutated,
+                    panic_message(&payload)
+                );
+            }
+        }
+    }
+
+    macro_rules! check_property {
+        ($property:ident, $seed:expr; $($typename:ty),* $(,)*) => {$(
+            for_all::<$typename, _>($seed, $property);
+        )*};
+    }
+
+    // Crypto types are fields by reference only, they are covered by the messages.
+    macro_rules! check_field_property {
+        ($property:ident, $seed:expr) => {
+            check_property!($property, $seed;
+                bool, u8, u16, u32, u64, i8, i16, i32, i64,
+                Height, Round, ValidatorId, DateTime<Utc>, Duration, SocketAddr, Uuid, Decimal,
+                BitVec, Vec<u8>, Vec<i64>, Vec<Vec<u16>>, Vec<Uuid>, Vec<SignedMessage>,
+                Block, Connect, Status, Propose, Prevote, Precommit,
+                BlockResponse, TransactionsResponse, ProposeRequest, TransactionsRequest,
+                PrevotesRequest, PeersRequest, BlockRequest,
+            );
+            #[cfg(feature = "float_serialize")]
+            {
+                check_property!($property, $seed; F32, F64, Vec<F32>, Vec<F64>);
+            }
+        };
+    }
+
+    macro_rules! check_storage_property {
+        ($property:ident, $seed:expr) => {
+            check_property!($property, $seed;
+                bool, u8, u16, u32, u64, i8, i16, i32, i64,
+                Hash, PublicKey, Vec<u8>, String, DateTime<Utc>, Duration, Round, Uuid, Decimal,
+                SignedMessage, Block, Connect, Status, Propose, Prevote, Precommit,
+                BlockResponse, TransactionsResponse, ProposeRequest, TransactionsRequest,
+                PrevotesRequest, PeersRequest, BlockRequest,
+            );
+            #[cfg(feature = "float_serialize")]
+            {
+                check_property!($property, $seed; F32, F64);
+            }
+        };
+    }
+
+    #[test]
+    fn test_generator_is_deterministic() {
+        let first = Vec::<Precommit>::arbitrary(&mut Gen::new(1, 2));
+        let second = Vec::<Precommit>::arbitrary(&mut Gen::new(1, 2));
+        assert_eq!(first, second);
+        assert_ne!(first, Vec::<Precommit>::arbitrary(&mut Gen::new(1, 3)));
+        assert!(Gen::new(0, 0).len() <= Gen::new(0, 0).size());
+    }
+
+    #[test]
+    fn test_write_check_read() {
+        check_field_property!(write_check_read, 1);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        check_field_property!(json_round_trip, 2);
+    }
+
+    #[test]
+    fn test_storage_value_round_trip() {
+        check_storage_property!(storage_round_trip, 3);
+    }
+
+    #[test]
+    fn test_hash_stability() {
+        check_storage_property!(hash_is_stable, 4);
+
+        // Hashes of the generated messages don't change between versions.
+        let mut stream = HashStream::new();
+        for case in 0..16 {
+            let mut gen = Gen::new(5, case);
+            stream = stream
+                .update(CryptoHash::hash(&Block::arbitrary(&mut gen)).as_ref())
+                .update(CryptoHash::hash(&Precommit::arbitrary(&mut gen)).as_ref());
+        }
+        let expected = match HashAlgorithm::current() {
+            HashAlgorithm::Sha256 => {
+                "255ac3ad2c75cdec0eb4338cf240684a8121c36d91f488602a6f96394d93a59c"
+            }
+            HashAlgorithm::Blake2b => {
+                "beecb251367ab17625e9ad545d65b856356dde1639ae12ba8d01222a575bcf16"
+            }
+        };
+        assert_eq!(stream.hash(), Hash::from_hex(expected).unwrap());
+    }
+
+    #[test]
+    fn test_mutations_never_panic_in_check() {
+        check_field_property!(mutations_never_panic, 6);
+    }
+}
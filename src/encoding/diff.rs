@@ -0,0 +1,546 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Structural comparison of encoded values.
+//!
+//! Two values of the same type are compared field by field, descending into nested
+//! structures and segments. Each difference is reported together with the path to the
+//! field, e.g. `.block.prev_hash` or `.transactions[2]`.
+//!
+//! `Diff` is implemented for all types declared with [`encoding_struct!`] and
+//! [`encoding_layout!`], so the field list of the structure is used for the comparison.
+//! Signed messages are compared by their envelope and payload; the payload path is
+//! `.protocol` whatever the message type, e.g. `.precommits[0].protocol.block_hash`.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[macro_use] extern crate exonum;
+//! # extern crate serde;
+//! # extern crate serde_json;
+//! use exonum::encoding::diff::diff;
+//!
+//! encoding_struct! {
+//!     struct Point {
+//!         x: u32,
+//!         y: u32,
+//!     }
+//! }
+//!
+//! # fn main() {
+//! let differences = diff(&Point::new(1, 2), &Point::new(1, 3));
+//! assert_eq!(differences.len(), 1);
+//! assert_eq!(differences.to_string(), ".y: 2 -> 3\n");
+//! # }
+//! ```
+//!
+//! [`encoding_struct!`]: ../../macro.encoding_struct.html
+//! [`encoding_layout!`]: ../../macro.encoding_layout.html
+
+use serde_json::{self, Value};
+use bit_vec::BitVec;
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+use rust_decimal::Decimal;
+
+use std::fmt;
+use std::net::SocketAddr;
+use std::slice;
+
+use crypto::{Hash, PublicKey, Signature};
+use types::{Height, Round, ValidatorId};
+use messages::{ConsensusMessage, Message, Protocol, ProtocolMessage, RawTransaction,
+               RequestMessage, SignedMessage, WithoutEncodingStatus};
+use super::serialize::json::{ExonumJson, ProtocolJson};
+use super::serialize::text::JsonConfig;
+
+/// Computes the differences between two values.
+pub fn diff<T: Diff + ?Sized>(left: &T, right: &T) -> Differences {
+    let mut differences = Differences::new();
+    left.diff_at(right, "", &mut differences);
+    differences
+}
+
+/// Trait for values that can be compared field by field.
+pub trait Diff {
+    /// Appends the differences between `self` and `other` to `differences`.
+    /// `path` is the path of `self` inside the value being compared.
+    fn diff_at(&self, other: &Self, path: &str, differences: &mut Differences);
+
+    /// Returns the representation of the value used in reports.
+    fn diff_value(&self) -> Value;
+}
+
+/// A single difference between two values.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Difference {
+    /// Path to the differing field, empty for the value itself.
+    pub path: String,
+    /// Value of the field in the left operand, `null` if it is absent.
+    pub left: Value,
+    /// Value of the field in the right operand, `null` if it is absent.
+    pub right: Value,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let path = if self.path.is_empty() { "." } else { &self.path };
+        write!(f, "{}: {} -> {}", path, self.left, self.right)
+    }
+}
+
+/// List of differences between two values.
+///
+/// The `Display` implementation renders one difference per line.
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct Differences(Vec<Difference>);
+
+impl Differences {
+    /// Creates an empty list of differences.
+    pub fn new() -> Self {
+        Differences(Vec::new())
+    }
+
+    /// Adds a difference at the given path.
+    pub fn push(&mut self, path: &str, left: Value, right: Value) {
+        self.0.push(Difference {
+            path: path.to_string(),
+            left,
+            right,
+        });
+    }
+
+    /// Returns `true` if the compared values are equal.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the number of differences.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns an iterator over differences.
+    pub fn iter(&self) -> slice::Iter<Difference> {
+        self.0.iter()
+    }
+
+    /// Renders differences as a JSON array of `{ "path", "left", "right" }` objects.
+    pub fn to_json(&self) -> Value {
+        serde_json::to_value(&self.0).expect("Differences are always serializable")
+    }
+}
+
+impl fmt::Display for Differences {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for difference in &self.0 {
+            writeln!(f, "{}", difference)?;
+        }
+        Ok(())
+    }
+}
+
+impl IntoIterator for Differences {
+    type Item = Difference;
+    type IntoIter = ::std::vec::IntoIter<Difference>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// Returns path of the named field inside the value at `path`.
+#[doc(hidden)]
+pub fn field_path(path: &str, name: &str) -> String {
+    format!("{}.{}", path, name)
+}
+
+/// Returns path of the item inside the sequence at `path`.
+fn item_path(path: &str, index: usize) -> String {
+    format!("{}[{}]", path, index)
+}
+
+/// Returns the value representation produced by `ExonumJson`.
+#[doc(hidden)]
+pub fn json_value<T: ExonumJson + ?Sized>(value: &T) -> Value {
+    value.serialize_field().unwrap_or(Value::Null)
+}
+
+/// Compares sequences item by item; missing items are reported as `null`.
+fn diff_items<T: Diff>(left: &[T], right: &[T], path: &str, differences: &mut Differences) {
+    for (index, (l, r)) in left.iter().zip(right).enumerate() {
+        l.diff_at(r, &item_path(path, index), differences);
+    }
+    for (index, l) in left.iter().enumerate().skip(right.len()) {
+        differences.push(&item_path(path, index), l.diff_value(), Value::Null);
+    }
+    for (index, r) in right.iter().enumerate().skip(left.len()) {
+        differences.push(&item_path(path, index), Value::Null, r.diff_value());
+    }
+}
+
+/// Implements `Diff` for types compared as a whole.
+macro_rules! impl_diff_leaf {
+    (@impl $typename:ty) => {
+        impl<'a> Diff for $typename {
+            fn diff_at(&self, other: &Self, path: &str, differences: &mut Differences) {
+                if self != other {
+                    differences.push(path, self.diff_value(), other.diff_value());
+                }
+            }
+
+            fn diff_value(&self) -> Value {
+                json_value(self)
+            }
+        }
+    };
+    ($($name:ty);*) =>
+        ($(impl_diff_leaf!{@impl $name})*);
+}
+
+impl_diff_leaf!{u8; u16; u32; u64; i8; i16; i32; i64; bool;
+                Height; Round; ValidatorId; DateTime<Utc>; Duration;
+                SocketAddr; Uuid; Decimal; BitVec;
+                &'a str; &'a [u8]; &'a Hash; &'a PublicKey; &'a Signature}
+
+#[cfg(feature = "float_serialize")]
+impl_diff_leaf!{super::F32; super::F64}
+
+impl<'a> Diff for &'a [Hash] {
+    fn diff_at(&self, other: &Self, path: &str, differences: &mut Differences) {
+        diff_items(self, other, path, differences)
+    }
+
+    fn diff_value(&self) -> Value {
+        Value::Array(self.iter().map(Diff::diff_value).collect())
+    }
+}
+
+impl Diff for Hash {
+    fn diff_at(&self, other: &Self, path: &str, differences: &mut Differences) {
+        <&Hash as Diff>::diff_at(&self, &other, path, differences)
+    }
+
+    fn diff_value(&self) -> Value {
+        json_value(&self)
+    }
+}
+
+impl<T: Diff> Diff for Vec<T> {
+    fn diff_at(&self, other: &Self, path: &str, differences: &mut Differences) {
+        diff_items(self, other, path, differences)
+    }
+
+    fn diff_value(&self) -> Value {
+        Value::Array(self.iter().map(Diff::diff_value).collect())
+    }
+}
+
+/// Returns the value representation produced by `ProtocolJson`.
+fn protocol_value<T: ProtocolJson>(value: &T) -> Value {
+    value.protocol_to_json(&JsonConfig::default()).unwrap_or(Value::Null)
+}
+
+/// Implements `Diff` for an enum of messages. Payloads of the same variant are compared
+/// field by field at the path of the enum, different variants are reported as a whole.
+macro_rules! impl_diff_enum {
+    ($name:ident { $($variant:ident),* }) => {
+        impl Diff for $name {
+            fn diff_at(&self, other: &Self, path: &str, differences: &mut Differences) {
+                match (self, other) {
+                    $(
+                        (&$name::$variant(ref left), &$name::$variant(ref right)) => {
+                            left.diff_at(right, path, differences)
+                        }
+                    )*
+                    _ => differences.push(path, self.diff_value(), other.diff_value()),
+                }
+            }
+
+            fn diff_value(&self) -> Value {
+                protocol_value(self)
+            }
+        }
+    };
+}
+
+impl_diff_enum!(Protocol {
+    Transaction,
+    Connect,
+    Status,
+    WithoutEncodingStatus,
+    Block,
+    Consensus,
+    Request,
+    TransactionsBatch
+});
+impl_diff_enum!(ConsensusMessage { Propose, Prevote, Precommit });
+impl_diff_enum!(RequestMessage { Propose, Transactions, Prevotes, Peers, Block });
+
+impl Diff for RawTransaction {
+    fn diff_at(&self, other: &Self, path: &str, differences: &mut Differences) {
+        self.service_id()
+            .diff_at(&other.service_id(), &field_path(path, "service_id"), differences);
+        self.payload()
+            .diff_at(&other.payload(), &field_path(path, "payload"), differences);
+    }
+
+    fn diff_value(&self) -> Value {
+        protocol_value(self)
+    }
+}
+
+impl Diff for WithoutEncodingStatus {
+    fn diff_at(&self, other: &Self, path: &str, differences: &mut Differences) {
+        self.height
+            .diff_at(&other.height, &field_path(path, "height"), differences);
+        self.last_hash
+            .diff_at(&other.last_hash, &field_path(path, "last_hash"), differences);
+    }
+
+    fn diff_value(&self) -> Value {
+        protocol_value(self)
+    }
+}
+
+// Compares the envelopes of the signed messages, the payloads are compared
+// by `diff_payload` at the `protocol` path.
+fn diff_signed<F>(
+    left: &SignedMessage,
+    right: &SignedMessage,
+    path: &str,
+    differences: &mut Differences,
+    diff_payload: F,
+) where
+    F: FnOnce(&str, &mut Differences),
+{
+    let (left_message, right_message) = (&left.authorised_message, &right.authorised_message);
+    left_message
+        .version
+        .diff_at(&right_message.version, &field_path(path, "version"), differences);
+    <&PublicKey as Diff>::diff_at(
+        &&left_message.author,
+        &&right_message.author,
+        &field_path(path, "author"),
+        differences,
+    );
+    diff_payload(&field_path(path, "protocol"), differences);
+    <&Signature as Diff>::diff_at(
+        &&left.signature,
+        &&right.signature,
+        &field_path(path, "signature"),
+        differences,
+    );
+}
+
+impl Diff for SignedMessage {
+    fn diff_at(&self, other: &Self, path: &str, differences: &mut Differences) {
+        diff_signed(self, other, path, differences, |path, differences| {
+            let (left, right) = (&self.authorised_message, &other.authorised_message);
+            left.protocol.diff_at(&right.protocol, path, differences)
+        });
+    }
+
+    fn diff_value(&self) -> Value {
+        Value::String(self.to_hex_string())
+    }
+}
+
+impl<T: ProtocolMessage + Diff> Diff for Message<T> {
+    fn diff_at(&self, other: &Self, path: &str, differences: &mut Differences) {
+        diff_signed(self.as_ref(), other.as_ref(), path, differences, |path, differences| {
+            (**self).diff_at(&**other, path, differences)
+        });
+    }
+
+    fn diff_value(&self) -> Value {
+        Value::String(self.to_hex_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crypto::{chain_id, gen_keypair, hash, DEFAULT_NETWORK};
+    use messages::{Block, BlockResponse, Precommit, Prevote, Propose};
+    use types::{Height, Round, ValidatorId};
+    use chrono::Utc;
+
+    use super::*;
+
+    encoding_struct! {
+        struct Inner {
+            id: u32,
+            name: &str,
+        }
+    }
+
+    encoding_struct! {
+        struct Outer {
+            owner: &PublicKey,
+            inner: Inner,
+            items: Vec<Inner>,
+        }
+    }
+
+    #[test]
+    fn test_diff_equal_values() {
+        let (pub_key, _) = gen_keypair();
+        let value = Outer::new(&pub_key, Inner::new(1, "a"), vec![Inner::new(2, "b")]);
+        let differences = diff(&value, &value.clone());
+        assert!(differences.is_empty());
+        assert_eq!(differences.to_string(), "");
+        assert_eq!(differences.to_json(), Value::Array(vec![]));
+    }
+
+    #[test]
+    fn test_diff_nested_fields() {
+        let (pub_key, _) = gen_keypair();
+        let left = Outer::new(
+            &pub_key,
+            Inner::new(1, "a"),
+            vec![Inner::new(2, "b"), Inner::new(3, "c")],
+        );
+        let right = Outer::new(
+            &pub_key,
+            Inner::new(1, "x"),
+            vec![Inner::new(2, "b"), Inner::new(4, "c"), Inner::new(5, "d")],
+        );
+
+        let differences = diff(&left, &right);
+        let paths = differences.iter().map(|d| d.path.as_str()).collect::<Vec<_>>();
+        assert_eq!(paths, vec![".inner.name", ".items[1].id", ".items[2]"]);
+        assert_eq!(
+            differences.to_string(),
+            ".inner.name: \"a\" -> \"x\"\n\
+             .items[1].id: 3 -> 4\n\
+             .items[2]: null -> {\"id\":5,\"name\":\"d\"}\n"
+        );
+
+        let json: Value = serde_json::from_str(
+            r#"[
+                { "path": ".inner.name", "left": "a", "right": "x" },
+                { "path": ".items[1].id", "left": 3, "right": 4 },
+                { "path": ".items[2]", "left": null, "right": { "id": 5, "name": "d" } }
+            ]"#,
+        ).unwrap();
+        assert_eq!(differences.to_json(), json);
+    }
+
+    #[test]
+    fn test_diff_hash_segment() {
+        let txs = [hash(&[1]), hash(&[2])];
+        let other_txs = [hash(&[1]), hash(&[3])];
+        let left = Propose::new(
+            ValidatorId(0),
+            Height(1),
+            Round(1),
+            &hash(&[0]),
+            &txs,
+        );
+        let right = Propose::new(
+            ValidatorId(0),
+            Height(2),
+            Round(1),
+            &hash(&[0]),
+            &other_txs,
+        );
+
+        let differences = diff(&left, &right);
+        let paths = differences.iter().map(|d| d.path.as_str()).collect::<Vec<_>>();
+        assert_eq!(paths, vec![".height", ".transactions[1]"]);
+        assert_eq!(
+            differences.iter().nth(1).unwrap().right,
+            Value::String(other_txs[1].to_hex())
+        );
+    }
+
+    #[test]
+    fn test_diff_leaf_values() {
+        assert_eq!(diff(&1_u64, &2_u64).to_string(), ".: \"1\" -> \"2\"\n");
+        assert!(diff(&Round(1), &Round(1)).is_empty());
+
+        let time = Utc::now();
+        let left = Precommit::new(
+            ValidatorId(1),
+            Height(1),
+            Round(1),
+            &hash(&[1]),
+            &hash(&[2]),
+            time,
+        );
+        let right = Precommit::new(
+            ValidatorId(2),
+            Height(1),
+            Round(1),
+            &hash(&[1]),
+            &hash(&[2]),
+            time,
+        );
+        let differences = diff(&left, &right);
+        assert_eq!(differences.to_string(), ".validator: 1 -> 2\n");
+    }
+
+    fn precommit(block_hash: &Hash, time: DateTime<Utc>) -> Precommit {
+        Precommit::new(ValidatorId(1), Height(1), Round(1), &hash(&[1]), block_hash, time)
+    }
+
+    #[test]
+    fn test_diff_signed_message_payloads() {
+        let (pub_key, secret_key) = gen_keypair();
+        let chain_id = chain_id(DEFAULT_NETWORK);
+        let time = Utc::now();
+        let block = Block::new(0, ValidatorId(0), Height(1), 0, &hash(&[]), &hash(&[]), &hash(&[]));
+        let response = |block_hash: &Hash| {
+            let precommit = precommit(block_hash, time);
+            let precommit =
+                SignedMessage::new(precommit, pub_key, &chain_id, &secret_key).unwrap();
+            BlockResponse::new(&pub_key, block.clone(), vec![precommit], &[])
+        };
+        let (left, right) = (response(&hash(&[2])), response(&hash(&[3])));
+
+        let differences = diff(&left, &right);
+        let paths = differences.iter().map(|d| d.path.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![
+                ".precommits[0].protocol.block_hash",
+                ".precommits[0].signature",
+            ]
+        );
+        assert_eq!(
+            differences.iter().next().unwrap().right,
+            Value::String(hash(&[3]).to_hex())
+        );
+
+        // Typed messages are compared the same way.
+        let left = Message::new(precommit(&hash(&[2]), time), pub_key, &chain_id, &secret_key);
+        let right = Message::new(precommit(&hash(&[3]), time), pub_key, &chain_id, &secret_key);
+        let differences = diff(&left.unwrap(), &right.unwrap());
+        assert_eq!(
+            differences.iter().next().unwrap().path,
+            ".protocol.block_hash"
+        );
+
+        // Payloads of different types are reported as a whole.
+        let prevote = Prevote::new(ValidatorId(1), Height(1), Round(1), &hash(&[1]), Round(0));
+        let left = SignedMessage::new(prevote, pub_key, &chain_id, &secret_key).unwrap();
+        let right = SignedMessage::new(precommit(&hash(&[2]), time), pub_key, &chain_id,
+                                       &secret_key).unwrap();
+        let differences = diff(&left, &right);
+        let protocol = differences.iter().next().unwrap();
+        assert_eq!(protocol.path, ".protocol");
+        assert_eq!(protocol.left["Prevote"]["validator"], Value::from(1));
+        assert!(protocol.right["Precommit"].is_object());
+    }
+}
@@ -58,6 +58,20 @@ pub enum Error {
         /// Padding value.
         value: [u8; 12],
     },
+    /// Reserved bytes of a custom layout must be zeroed.
+    IncorrectReservedBytes {
+        /// Position in buffer where error appears.
+        position: Offset,
+        /// Value of the first nonzero reserved byte.
+        value: u8,
+    },
+    /// Unused bits in the last byte of `BitVec` must be zero.
+    IncorrectBitVecPadding {
+        /// Position in buffer where error appears.
+        position: Offset,
+        /// Value of the last byte.
+        value: u8,
+    },
     /// Segment reference is incorrect.
     IncorrectSegmentReference {
         /// position in buffer where error appears.
@@ -135,6 +149,13 @@ pub enum Error {
         /// Nanoseconds in gotten duration.
         nanos: i32,
     },
+    /// Value doesn't satisfy a validation rule declared in `encoding_struct!`.
+    Validation {
+        /// Name of the field, or name of the structure for structure-wide rules.
+        field: &'static str,
+        /// Source of the failed rule.
+        rule: &'static str,
+    },
     /// Basic error support, for custom fields.
     Basic(Cow<'static, str>),
     /// Other error for custom fields.
@@ -155,6 +176,8 @@ impl StdError for Error {
             Error::UnsupportedFloat { .. } => "Unsupported float value",
             Error::IncorrectSocketAddrHeader { .. } => "Incorrect SocketAddr header value",
             Error::IncorrectSocketAddrPadding { .. } => "Incorrect SocketAddr padding",
+            Error::IncorrectReservedBytes { .. } => "Nonzero reserved bytes",
+            Error::IncorrectBitVecPadding { .. } => "Incorrect BitVec padding",
             Error::IncorrectSegmentReference { .. } => "Incorrect segment reference",
             Error::IncorrectSegmentSize { .. } => "Incorrect segment size",
             Error::UnexpectedlyShortRawMessage { .. } => "Unexpectedly short RawMessage",
@@ -168,6 +191,7 @@ impl StdError for Error {
             Error::OffsetOverflow => "Offset pointers overflow",
             Error::DurationOverflow => "Overflow in Duration object",
             Error::IncorrectDuration { .. } => "Incorrect Duration object representation",
+            Error::Validation { .. } => "Validation rule is not satisfied",
             Error::Basic(ref x) => x.as_ref(),
             Error::Other(_) => "Other error",
         }
@@ -44,8 +44,14 @@ pub trait Field<'a> {
     // TODO: use Read and Cursor (ECR-156)
     // TODO: debug_assert_eq!(to-from == size of Self) (ECR-156)
 
+    /// Field's header size, known at compile time, so layouts could be checked
+    /// by macros.
+    const FIELD_SIZE: Offset;
+
     /// Field's header size.
-    fn field_size() -> Offset;
+    fn field_size() -> Offset {
+        Self::FIELD_SIZE
+    }
 
     /// Read Field from buffer, with given position,
     /// beware of memory unsafety,
@@ -69,6 +75,26 @@ pub trait Field<'a> {
         debug_assert_eq!((to - from)?.unchecked_offset(), Self::field_size());
         Ok(latest_segment)
     }
+
+    /// Checks `count` consecutive fields starting from `from`, e.g. the items of a `Vec`.
+    /// Returns an index of latest data seen.
+    /// Default implementation checks the fields one by one, types could override it
+    /// to check all fields at once.
+    fn check_items(
+        buffer: &'a [u8],
+        from: CheckedOffset,
+        count: CheckedOffset,
+        latest_segment: CheckedOffset,
+    ) -> Result {
+        let mut start = from;
+        let mut latest_segment = latest_segment;
+        for _ in 0..count.unchecked_offset() {
+            let end = (start + Self::field_size())?;
+            latest_segment = Self::check(buffer, start, end, latest_segment)?;
+            start = end;
+        }
+        Ok(latest_segment)
+    }
 }
 
 /// implement field for all types that has writer and reader functions
@@ -79,9 +105,8 @@ pub trait Field<'a> {
 macro_rules! implement_std_field {
     ($name:ident $fn_read:expr; $fn_write:expr) => (
         impl<'a> Field<'a> for $name {
-            fn field_size() -> $crate::encoding::Offset {
-                mem::size_of::<$name>() as $crate::encoding::Offset
-            }
+            const FIELD_SIZE: $crate::encoding::Offset =
+                mem::size_of::<$name>() as $crate::encoding::Offset;
 
             unsafe fn read(buffer: &'a [u8],
                            from: $crate::encoding::Offset,
@@ -103,9 +128,8 @@ macro_rules! implement_std_field {
 macro_rules! implement_std_typedef_field {
     ($name:ident ($t:ty) $fn_read:expr; $fn_write:expr) => (
         impl<'a> Field<'a> for $name {
-            fn field_size() -> $crate::encoding::Offset {
-                mem::size_of::<$t>() as $crate::encoding::Offset
-            }
+            const FIELD_SIZE: $crate::encoding::Offset =
+                mem::size_of::<$t>() as $crate::encoding::Offset;
 
             unsafe fn read(buffer: &'a [u8],
                            from: $crate::encoding::Offset,
@@ -131,9 +155,8 @@ macro_rules! implement_std_typedef_field {
 macro_rules! implement_pod_as_ref_field {
     ($name:ident) => (
         impl<'a> Field<'a> for &'a $name {
-            fn field_size() ->  $crate::encoding::Offset {
-                ::std::mem::size_of::<$name>() as $crate::encoding::Offset
-            }
+            const FIELD_SIZE: $crate::encoding::Offset =
+                ::std::mem::size_of::<$name>() as $crate::encoding::Offset;
 
             unsafe fn read(buffer: &'a [u8],
                             from: $crate::encoding::Offset,
@@ -160,9 +183,7 @@ macro_rules! implement_pod_as_ref_field {
 }
 
 impl<'a> Field<'a> for bool {
-    fn field_size() -> Offset {
-        1
-    }
+    const FIELD_SIZE: Offset = 1;
 
     unsafe fn read(buffer: &'a [u8], from: Offset, _: Offset) -> Self {
         buffer[from as usize] == 1
@@ -193,9 +214,7 @@ impl<'a> Field<'a> for bool {
 }
 
 impl<'a> Field<'a> for u8 {
-    fn field_size() -> Offset {
-        mem::size_of::<Self>() as Offset
-    }
+    const FIELD_SIZE: Offset = mem::size_of::<Self>() as Offset;
 
     unsafe fn read(buffer: &'a [u8], from: Offset, _: Offset) -> Self {
         buffer[from as usize]
@@ -208,9 +227,7 @@ impl<'a> Field<'a> for u8 {
 
 // TODO expect some codding of signed integers (ECR-156) ?
 impl<'a> Field<'a> for i8 {
-    fn field_size() -> Offset {
-        mem::size_of::<Self>() as Offset
-    }
+    const FIELD_SIZE: Offset = mem::size_of::<Self>() as Offset;
 
     unsafe fn read(buffer: &'a [u8], from: Offset, _: Offset) -> Self {
         buffer[from as usize] as i8
@@ -237,9 +254,7 @@ implement_pod_as_ref_field! {PublicKey}
 implement_pod_as_ref_field! {Hash}
 
 impl<'a> Field<'a> for DateTime<Utc> {
-    fn field_size() -> Offset {
-        (mem::size_of::<i64>() + mem::size_of::<u32>()) as Offset
-    }
+    const FIELD_SIZE: Offset = (mem::size_of::<i64>() + mem::size_of::<u32>()) as Offset;
 
     unsafe fn read(buffer: &'a [u8], from: Offset, to: Offset) -> Self {
         let secs =
@@ -280,9 +295,7 @@ fn is_duration_representation_valid(secs: i64, nanos: i32) -> bool {
 }
 
 impl<'a> Field<'a> for Duration {
-    fn field_size() -> Offset {
-        (mem::size_of::<i64>() + mem::size_of::<i32>()) as Offset
-    }
+    const FIELD_SIZE: Offset = (mem::size_of::<i64>() + mem::size_of::<i32>()) as Offset;
 
     unsafe fn read(buffer: &'a [u8], from: Offset, to: Offset) -> Self {
         let secs =
@@ -356,10 +369,8 @@ impl<'a> Field<'a> for Duration {
 // TODO add socketaddr check, for now with only ipv4
 // all possible (>6 bytes long) sequences is a valid addr (ECR-156).
 impl<'a> Field<'a> for SocketAddr {
-    fn field_size() -> Offset {
-        // FIXME: reserve space for future compatibility (ECR-156)
-        (SOCKET_ADDR_HEADER_SIZE + IPV6_SIZE + PORT_SIZE) as Offset
-    }
+    // FIXME: reserve space for future compatibility (ECR-156)
+    const FIELD_SIZE: Offset = (SOCKET_ADDR_HEADER_SIZE + IPV6_SIZE + PORT_SIZE) as Offset;
 
     unsafe fn read(buffer: &'a [u8], from: Offset, to: Offset) -> Self {
         let addr_start = from as usize + SOCKET_ADDR_HEADER_SIZE;
@@ -437,9 +448,7 @@ impl<'a> Field<'a> for SocketAddr {
 }
 
 impl<'a> Field<'a> for Uuid {
-    fn field_size() -> Offset {
-        16
-    }
+    const FIELD_SIZE: Offset = 16;
 
     unsafe fn read(buffer: &'a [u8], from: Offset, to: Offset) -> Self {
         try_read_uuid(buffer, from, to).unwrap()
@@ -468,9 +477,7 @@ fn try_read_uuid(buffer: &[u8], from: Offset, to: Offset) -> StdResult<Uuid, uui
 }
 
 impl<'a> Field<'a> for Decimal {
-    fn field_size() -> Offset {
-        DECIMAL_SIZE as Offset
-    }
+    const FIELD_SIZE: Offset = DECIMAL_SIZE as Offset;
 
     unsafe fn read(buffer: &'a [u8], from: Offset, to: Offset) -> Self {
         let mut bytes: [u8; DECIMAL_SIZE] = mem::uninitialized();
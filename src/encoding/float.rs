@@ -15,18 +15,22 @@
 use byteorder::{ByteOrder, LittleEndian};
 use serde_json::value::{Number, Value};
 
-use std::mem;
+use std::cmp::Ordering;
 use std::error::Error;
+use std::hash::{Hash, Hasher};
+use std::mem;
 
 use encoding::{CheckedOffset, Field, Offset};
 use encoding::serialize::WriteBufferWrapper;
-use encoding::serialize::json::{ExonumJson, ExonumJsonDeserialize};
+use encoding::serialize::json::{ExonumJson, ExonumJsonDeserialize, JsonError};
 use super::Result as EncodingResult;
 use super::Error as EncodingError;
 
 /// Wrapper for the `f32` type that restricts non-finite
 /// (NaN, Infinity, negative zero and subnormal) values.
-#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+///
+/// Restricted values are totally ordered, so the wrapper implements `Eq`, `Ord` and `Hash`.
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct F32 {
     value: f32,
 }
@@ -90,7 +94,9 @@ impl F32 {
 
 /// Wrapper for the `f64` type that restricts non-finite
 /// (NaN, Infinity, negative zero and subnormal) values.
-#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+///
+/// Restricted values are totally ordered, so the wrapper implements `Eq`, `Ord` and `Hash`.
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct F64 {
     value: f64,
 }
@@ -152,10 +158,37 @@ impl F64 {
     }
 }
 
+macro_rules! impl_float_ord {
+    ($($name:ident),*) => {$(
+        impl Eq for $name {}
+
+        impl PartialOrd for $name {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $name {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.value
+                    .partial_cmp(&other.value)
+                    .expect("Restricted float values are comparable")
+            }
+        }
+
+        // Negative zero is restricted, so equal values have equal bits.
+        impl Hash for $name {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.value.to_bits().hash(state)
+            }
+        }
+    )*};
+}
+
+impl_float_ord!(F32, F64);
+
 impl<'a> Field<'a> for F32 {
-    fn field_size() -> Offset {
-        mem::size_of::<Self>() as Offset
-    }
+    const FIELD_SIZE: Offset = mem::size_of::<Self>() as Offset;
 
     unsafe fn read(buffer: &'a [u8], from: Offset, to: Offset) -> Self {
         Self::new(LittleEndian::read_f32(&buffer[from as usize..to as usize]))
@@ -188,9 +221,7 @@ impl<'a> Field<'a> for F32 {
 }
 
 impl<'a> Field<'a> for F64 {
-    fn field_size() -> Offset {
-        mem::size_of::<Self>() as Offset
-    }
+    const FIELD_SIZE: Offset = mem::size_of::<Self>() as Offset;
 
     unsafe fn read(buffer: &'a [u8], from: Offset, to: Offset) -> Self {
         Self::new(LittleEndian::read_f64(&buffer[from as usize..to as usize]))
@@ -246,9 +277,11 @@ impl ExonumJson for F32 {
 }
 
 impl ExonumJsonDeserialize for F32 {
-    fn deserialize(value: &Value) -> Result<Self, Box<Error>> {
-        let number = value.as_f64().ok_or("Can't cast json as float")?;
-        Ok(Self::try_from(number as f32).ok_or("Invalid float value in json")?)
+    fn deserialize(value: &Value) -> Result<Self, JsonError> {
+        let number = value
+            .as_f64()
+            .ok_or_else(|| JsonError::wrong_type("number"))?;
+        Self::try_from(number as f32).ok_or_else(|| JsonError::invalid_value("Invalid float value in json"))
     }
 }
 
@@ -274,21 +307,27 @@ impl ExonumJson for F64 {
 }
 
 impl ExonumJsonDeserialize for F64 {
-    fn deserialize(value: &Value) -> Result<Self, Box<Error>> {
-        let number = value.as_f64().ok_or("Can't cast json as float")?;
-        Ok(Self::try_from(number).ok_or("Invalid float value in json")?)
+    fn deserialize(value: &Value) -> Result<Self, JsonError> {
+        let number = value
+            .as_f64()
+            .ok_or_else(|| JsonError::wrong_type("number"))?;
+        Self::try_from(number).ok_or_else(|| JsonError::invalid_value("Invalid float value in json"))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{F32, F64};
+    use std::collections::{BTreeSet, HashSet};
     use std::num::FpCategory;
     use std::{f32, f64};
     use std::panic;
+    use crypto::{hash, CryptoHash};
     use encoding::fields::Field;
     use byteorder::{ByteOrder, LittleEndian};
-    use encoding::Offset;
+    use encoding::{Error as EncodingError, Offset};
+    use encoding::serialize::cbor;
+    use storage::StorageValue;
 
     fn validate_constructor<T, V, C: Fn(V) -> T>(
         constructor: C,
@@ -350,6 +389,96 @@ mod tests {
         }
     }
 
+    fn check_error<T: for<'r> Field<'r>>(buffer: &[u8], header_size: Offset) -> Option<Offset> {
+        match <T as Field>::check(buffer, 0.into(), header_size.into(), header_size.into()) {
+            Err(EncodingError::UnsupportedFloat { position, .. }) => Some(position),
+            Err(e) => panic!("Unexpected error {:?}", e),
+            Ok(_) => None,
+        }
+    }
+
+    #[test]
+    fn test_check_rejects_subnormal_and_negative_zero() {
+        let mut buf = vec![0; 4];
+        for &value in &[-0.0f32, f32::MIN_POSITIVE / 2.0, -f32::MIN_POSITIVE / 2.0] {
+            LittleEndian::write_f32(&mut buf, value);
+            assert_eq!(check_error::<F32>(&buf, 4), Some(0));
+        }
+        LittleEndian::write_f32(&mut buf, f32::MIN_POSITIVE);
+        assert_eq!(check_error::<F32>(&buf, 4), None);
+
+        let mut buf = vec![0; 8];
+        for &value in &[-0.0f64, f64::MIN_POSITIVE / 2.0, -f64::MIN_POSITIVE / 2.0] {
+            LittleEndian::write_f64(&mut buf, value);
+            assert_eq!(check_error::<F64>(&buf, 8), Some(0));
+        }
+        LittleEndian::write_f64(&mut buf, f64::MIN_POSITIVE);
+        assert_eq!(check_error::<F64>(&buf, 8), None);
+    }
+
+    #[test]
+    fn test_float_segments() {
+        let values = vec![F32::new(1.5), F32::new(0.0), F32::new(-2.0)];
+        let mut buf = vec![0; 8];
+        values.write(&mut buf, 0, 8);
+        assert_eq!(check_error::<Vec<F32>>(&buf, 8), None);
+        assert_eq!(unsafe { <Vec<F32> as Field>::read(&buf, 0, 8) }, values);
+
+        LittleEndian::write_f32(&mut buf[12..16], -0.0);
+        assert_eq!(check_error::<Vec<F32>>(&buf, 8), Some(12));
+        LittleEndian::write_f32(&mut buf[12..16], f32::MIN_POSITIVE / 4.0);
+        assert_eq!(check_error::<Vec<F32>>(&buf, 8), Some(12));
+
+        let values = vec![F64::new(f64::MAX), F64::new(f64::MIN_POSITIVE)];
+        let mut buf = vec![0; 8];
+        values.write(&mut buf, 0, 8);
+        assert_eq!(check_error::<Vec<F64>>(&buf, 8), None);
+        LittleEndian::write_f64(&mut buf[16..24], -0.0);
+        assert_eq!(check_error::<Vec<F64>>(&buf, 8), Some(16));
+    }
+
+    #[test]
+    fn test_float_total_order() {
+        let mut values = vec![F64::new(3.0), F64::new(-1.0), F64::new(0.0), F64::new(f64::MIN)];
+        values.sort();
+        assert_eq!(
+            values.iter().map(F64::get).collect::<Vec<_>>(),
+            vec![f64::MIN, -1.0, 0.0, 3.0]
+        );
+        assert_eq!(F32::new(1.0).max(F32::new(2.0)), F32::new(2.0));
+
+        let set = [F32::new(1.0), F32::new(0.0), F32::new(1.0)]
+            .iter()
+            .cloned()
+            .collect::<HashSet<_>>();
+        assert_eq!(set.len(), 2);
+        let set = [F64::new(2.5), F64::new(2.5)].iter().cloned().collect::<BTreeSet<_>>();
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn test_float_storage_value() {
+        let value = F64::new(-12.25);
+        let bytes = value.into_bytes();
+        assert_eq!(bytes, (-12.25f64).to_bits().to_le_bytes().to_vec());
+        assert_eq!(F64::from_bytes(bytes.clone().into()), value);
+        assert_eq!(CryptoHash::hash(&value), hash(&bytes));
+        assert_eq!(cbor::from_slice(&cbor::to_vec(&value)), Ok(value));
+
+        let value = F32::new(0.5);
+        let bytes = value.into_bytes();
+        assert_eq!(F32::from_bytes(bytes.clone().into()), value);
+        assert_eq!(CryptoHash::hash(&value), hash(&bytes));
+        assert_eq!(cbor::from_slice(&cbor::to_vec(&value)), Ok(value));
+        assert!(cbor::from_slice::<F32>(&cbor::to_vec(&F64::new(0.1))).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "Unexpected non-finite value")]
+    fn test_float_storage_value_rejects_negative_zero() {
+        F32::from_bytes((-0.0f32).to_bits().to_le_bytes().to_vec().into());
+    }
+
     #[test]
     #[allow(dead_code)]
     fn test_f32_struct() {
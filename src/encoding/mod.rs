@@ -26,10 +26,17 @@
 //! - **Body:** dynamically sized part, known only after parsing the header
 //!
 //! To create a structure type, you can use [`transactions!`] and [`encoding_struct!`] macros.
+//! Structures with an explicit header layout, including reserved ranges,
+//! are declared with the [`encoding_layout!`] macro.
 //!
 //! [doc:serialization]: https://exonum.com/doc/architecture/serialization/
 //! [`transactions!`]: ../macro.transactions.html
 //! [`encoding_struct!`]: ../macro.encoding_struct.html
+//! [`encoding_layout!`]: ../macro.encoding_layout.html
+//!
+//! Two values of such structures can be compared field by field with [`diff`].
+//!
+//! [`diff`]: ./diff/fn.diff.html
 //!
 //! # Examples
 //!
@@ -123,9 +130,12 @@ mod fields;
 mod segments;
 #[macro_use]
 mod spec;
+pub mod diff;
 #[cfg(feature = "float_serialize")]
 mod float;
 
+#[cfg(test)]
+mod arbitrary;
 #[cfg(test)]
 mod tests;
 
@@ -156,6 +166,23 @@ impl CheckedOffset {
     }
 }
 
+/// Checks that the reserved part `from..to` of the buffer is filled with zeros.
+#[doc(hidden)]
+pub fn check_reserved(
+    buffer: &[u8],
+    from: Offset,
+    to: Offset,
+) -> ::std::result::Result<(), Error> {
+    let reserved = &buffer[from as usize..to as usize];
+    match reserved.iter().position(|&byte| byte != 0) {
+        Some(index) => Err(Error::IncorrectReservedBytes {
+            position: from + index as Offset,
+            value: reserved[index],
+        }),
+        None => Ok(()),
+    }
+}
+
 macro_rules! implement_default_ops_checked {
     ($trait_name: ident $function:ident $checked_function:ident) => (
         impl $trait_name<CheckedOffset> for CheckedOffset {
@@ -17,12 +17,16 @@
 use byteorder::{ByteOrder, LittleEndian};
 use bit_vec::BitVec;
 
+use std::result::Result as StdResult;
+
 use messages::SignedMessage;
-use old_messages::RawMessage;
+use old_messages::{RawMessage, HEADER_LENGTH};
 
 use crypto::Hash;
 use super::{CheckedOffset, Error, Field, Offset, Result};
 
+const BIT_VEC_LENGTH_SIZE: usize = 4;
+
 /// Trait for fields, that has unknown `compile-time` size.
 /// Usually important for arrays,
 /// or other types that in rust is always at `HEAP`
@@ -44,15 +48,79 @@ pub trait SegmentField<'a>: Sized {
         count: CheckedOffset,
         latest_segment: CheckedOffset,
     ) -> Result;
+
+    /// Checks `count` consecutive pointers to the collections of this type
+    /// starting from `from`, and the collections data.
+    /// Default implementation checks the collections one by one.
+    fn check_segments(
+        buffer: &'a [u8],
+        from: CheckedOffset,
+        count: CheckedOffset,
+        latest_segment: CheckedOffset,
+    ) -> Result {
+        let mut start = from;
+        let mut latest_segment = latest_segment;
+        for _ in 0..count.unchecked_offset() {
+            let end = (start + POINTER_SIZE)?;
+            let (segment_start, count) =
+                check_segment_pointer::<Self>(buffer, start, end, latest_segment)?;
+            let segment_end = (segment_start + (count * Self::item_size())?)?;
+            latest_segment = Self::check_data(buffer, segment_start, count, segment_end)?;
+            start = end;
+        }
+        Ok(latest_segment)
+    }
+}
+
+// Size of the pointer to a segment: its position and the count of items.
+const POINTER_SIZE: Offset = 8;
+
+// Checks the pointer to a segment and returns the segment position and the count of items.
+fn check_segment_pointer<'a, T: SegmentField<'a>>(
+    buffer: &'a [u8],
+    pointer_from: CheckedOffset,
+    pointer_to: CheckedOffset,
+    latest_segment: CheckedOffset,
+) -> StdResult<(CheckedOffset, CheckedOffset), Error> {
+    debug_assert_eq!(
+        (pointer_to - pointer_from)?.unchecked_offset(),
+        POINTER_SIZE
+    );
+    let pointer_count_start: Offset = (pointer_from + 4)?.unchecked_offset();
+    let segment_start: CheckedOffset = LittleEndian::read_u32(
+        &buffer[pointer_from.unchecked_offset() as usize..pointer_count_start as usize],
+    ).into();
+    let count: CheckedOffset = LittleEndian::read_u32(
+        &buffer[pointer_count_start as usize..pointer_to.unchecked_offset() as usize],
+    ).into();
+
+    if segment_start < latest_segment {
+        return Err(Error::OverlappingSegment {
+            last_end: latest_segment.unchecked_offset(),
+            start: segment_start.unchecked_offset(),
+        });
+    } else if segment_start > latest_segment {
+        return Err(Error::SpaceBetweenSegments {
+            last_end: latest_segment.unchecked_offset(),
+            start: segment_start.unchecked_offset(),
+        });
+    }
+
+    let segment_end = (segment_start + (count * T::item_size())?)?;
+    if segment_end.unchecked_offset() > buffer.len() as u32 {
+        return Err(Error::IncorrectSegmentSize {
+            position: pointer_count_start,
+            value: count.unchecked_offset(),
+        });
+    }
+    Ok((segment_start, count))
 }
 
 impl<'a, T> Field<'a> for T
 where
     T: SegmentField<'a>,
 {
-    fn field_size() -> Offset {
-        8
-    }
+    const FIELD_SIZE: Offset = POINTER_SIZE;
 
     unsafe fn read(buffer: &'a [u8], from: Offset, to: Offset) -> T {
         let pos = LittleEndian::read_u32(&buffer[from as usize..from as usize + 4]);
@@ -76,42 +144,20 @@ where
         pointer_to: CheckedOffset,
         latest_segment: CheckedOffset,
     ) -> Result {
-        debug_assert_eq!(
-            (pointer_to - pointer_from)?.unchecked_offset(),
-            Self::field_size()
-        );
-        let pointer_count_start: Offset = (pointer_from + 4)?.unchecked_offset();
-        let segment_start: CheckedOffset = LittleEndian::read_u32(
-            &buffer[pointer_from.unchecked_offset() as usize..pointer_count_start as usize],
-        ).into();
-        let count: CheckedOffset = LittleEndian::read_u32(
-            &buffer[pointer_count_start as usize..pointer_to.unchecked_offset() as usize],
-        ).into();
-
-        if segment_start < latest_segment {
-            return Err(Error::OverlappingSegment {
-                last_end: latest_segment.unchecked_offset(),
-                start: segment_start.unchecked_offset(),
-            });
-        } else if segment_start > latest_segment {
-            return Err(Error::SpaceBetweenSegments {
-                last_end: latest_segment.unchecked_offset(),
-                start: segment_start.unchecked_offset(),
-            });
-        }
-
-        let segment_end = (segment_start + (count * Self::item_size())?)?;
-        if segment_end.unchecked_offset() > buffer.len() as u32 {
-            return Err(Error::IncorrectSegmentSize {
-                position: pointer_count_start,
-                value: count.unchecked_offset(),
-            });
-        }
-
-        let latest_segment = segment_end;
-
+        let (segment_start, count) =
+            check_segment_pointer::<T>(buffer, pointer_from, pointer_to, latest_segment)?;
+        let latest_segment = (segment_start + (count * Self::item_size())?)?;
         Self::check_data(buffer, segment_start, count, latest_segment)
     }
+
+    fn check_items(
+        buffer: &'a [u8],
+        from: CheckedOffset,
+        count: CheckedOffset,
+        latest_segment: CheckedOffset,
+    ) -> Result {
+        Self::check_segments(buffer, from, count, latest_segment)
+    }
 }
 
 impl<'a> SegmentField<'a> for &'a str {
@@ -152,21 +198,33 @@ impl<'a> SegmentField<'a> for &'a str {
     }
 }
 
+/// Signed message is stored as its serialized bytes. The signature depends on
+/// the network, so it isn't verified when the segment is checked; use
+/// `SignedMessage::verify` with the chain id instead, or the batch verifiers of the
+/// responses, such as `TransactionsResponse::verify_transactions`.
 impl<'a> SegmentField<'a> for SignedMessage {
     fn item_size() -> Offset {
         1
     }
 
     fn count(&self) -> Offset {
-        unimplemented!()
+        ::bincode::config()
+            .no_limit()
+            .serialized_size(self)
+            .expect("Could not compute size of SignedMessage.") as Offset
     }
 
-    unsafe fn from_buffer(buffer: &'a [u8], from: Offset, to: Offset) -> Self {
-        unimplemented!()
+    unsafe fn from_buffer(buffer: &'a [u8], from: Offset, count: Offset) -> Self {
+        let to = from + count * Self::item_size();
+        SignedMessage::deserialize_unverified(&buffer[from as usize..to as usize])
+            .expect("Signed message should be checked")
     }
 
     fn extend_buffer(&self, buffer: &mut Vec<u8>) {
-        unimplemented!()
+        ::bincode::config()
+            .no_limit()
+            .serialize_into(buffer, self)
+            .expect("Could not serialize SignedMessage.")
     }
 
     fn check_data(
@@ -175,11 +233,26 @@ impl<'a> SegmentField<'a> for SignedMessage {
         count: CheckedOffset,
         latest_segment: CheckedOffset,
     ) -> Result {
-        unimplemented!()
+        let size: CheckedOffset = (count * Self::item_size())?;
+        let to: CheckedOffset = (from + size)?;
+        let slice = &buffer[from.unchecked_offset() as usize..to.unchecked_offset() as usize];
+        let message = match SignedMessage::deserialize_unverified(slice) {
+            Ok(message) => message,
+            Err(e) => {
+                return Err(Error::Basic(format!("Incorrect signed message: {}", e).into()))
+            }
+        };
+        // Trailing bytes of the segment would be ignored otherwise.
+        if message.count() != size.unchecked_offset() {
+            return Err(Error::IncorrectSegmentSize {
+                position: from.unchecked_offset(),
+                value: size.unchecked_offset(),
+            });
+        }
+        Ok(latest_segment)
     }
 }
 
-
 impl<'a> SegmentField<'a> for RawMessage {
     fn item_size() -> Offset {
         1
@@ -189,12 +262,13 @@ impl<'a> SegmentField<'a> for RawMessage {
         self.as_ref().len() as Offset
     }
 
-    unsafe fn from_buffer(buffer: &'a [u8], from: Offset, to: Offset) -> Self {
-        unimplemented!()
+    unsafe fn from_buffer(buffer: &'a [u8], from: Offset, count: Offset) -> Self {
+        let to = from + count * Self::item_size();
+        RawMessage::from_vec(buffer[from as usize..to as usize].to_vec())
     }
 
     fn extend_buffer(&self, buffer: &mut Vec<u8>) {
-        unimplemented!()
+        buffer.extend_from_slice(self.as_ref())
     }
 
     fn check_data(
@@ -203,7 +277,25 @@ impl<'a> SegmentField<'a> for RawMessage {
         count: CheckedOffset,
         latest_segment: CheckedOffset,
     ) -> Result {
-        unimplemented!()
+        let size: CheckedOffset = (count * Self::item_size())?;
+        let to: CheckedOffset = (from + size)?;
+        let slice = &buffer[from.unchecked_offset() as usize..to.unchecked_offset() as usize];
+        if slice.len() < HEADER_LENGTH {
+            return Err(Error::UnexpectedlyShortRawMessage {
+                position: from.unchecked_offset(),
+                size: slice.len() as Offset,
+            });
+        }
+        // The header contains the length of the whole message.
+        let declared_size = LittleEndian::read_u32(&slice[6..10]);
+        if slice.len() as Offset != declared_size {
+            return Err(Error::IncorrectSizeOfRawMessage {
+                position: from.unchecked_offset(),
+                actual_size: slice.len() as Offset,
+                declared_size,
+            });
+        }
+        Ok(latest_segment)
     }
 }
 
@@ -252,49 +344,96 @@ where
         count: CheckedOffset,
         latest_segment: CheckedOffset,
     ) -> Result {
-        let mut start = from;
-        let mut latest_segment = latest_segment;
-
-        for _ in 0..count.unchecked_offset() {
-            latest_segment = T::check(buffer, start, (start + Self::item_size())?, latest_segment)?;
-            start = (start + Self::item_size())?;
-        }
-        Ok(latest_segment)
+        T::check_items(buffer, from, count, latest_segment)
     }
 }
 
+/// Bit vector is stored as the number of bits (LE-encoded `u32`)
+/// followed by the bits packed into bytes, most significant bit first,
+/// the same as `BitVec::to_bytes` does. Unused bits of the last byte must be zero.
 impl<'a> SegmentField<'a> for BitVec {
     fn item_size() -> Offset {
         1
     }
 
-    // TODO: reduce memory allocation (ECR-156)
     fn count(&self) -> Offset {
-        self.to_bytes().len() as Offset
+        (BIT_VEC_LENGTH_SIZE + bytes_for_bits(bit_vec_length(self) as usize)) as Offset
     }
 
     unsafe fn from_buffer(buffer: &'a [u8], from: Offset, count: Offset) -> Self {
-        let to = from + count * Self::item_size();
-        let slice = &buffer[from as usize..to as usize];
-        BitVec::from_bytes(slice)
+        let from = from as usize;
+        let to = from + (count * Self::item_size()) as usize;
+        let bits = LittleEndian::read_u32(&buffer[from..from + BIT_VEC_LENGTH_SIZE]);
+        let mut vec = BitVec::from_bytes(&buffer[from + BIT_VEC_LENGTH_SIZE..to]);
+        vec.truncate(bits as usize);
+        vec
     }
 
     fn extend_buffer(&self, buffer: &mut Vec<u8>) {
-        // TODO: avoid reallocation here using normal implementation of bitvec (ECR-156)
-        let slice = &self.to_bytes();
-        buffer.extend_from_slice(slice);
+        let mut length = [0; BIT_VEC_LENGTH_SIZE];
+        LittleEndian::write_u32(&mut length, bit_vec_length(self));
+        buffer.extend_from_slice(&length);
+
+        let bits = self.len();
+        buffer.extend((0..bytes_for_bits(bits)).map(|byte| {
+            (0..8)
+                .filter(|bit| self.get(byte * 8 + bit) == Some(true))
+                .fold(0u8, |acc, bit| acc | (0x80 >> bit))
+        }));
     }
 
     fn check_data(
-        _: &'a [u8],
-        _: CheckedOffset,
-        _: CheckedOffset,
+        buffer: &'a [u8],
+        from: CheckedOffset,
+        count: CheckedOffset,
         latest_segment: CheckedOffset,
     ) -> Result {
+        let size = (count * Self::item_size())?.unchecked_offset() as usize;
+        let start = from.unchecked_offset() as usize;
+        if size < BIT_VEC_LENGTH_SIZE {
+            return Err(Error::IncorrectSegmentSize {
+                position: from.unchecked_offset(),
+                value: count.unchecked_offset(),
+            });
+        }
+
+        let bits = LittleEndian::read_u32(&buffer[start..start + BIT_VEC_LENGTH_SIZE]) as usize;
+        if size - BIT_VEC_LENGTH_SIZE != bytes_for_bits(bits) {
+            return Err(Error::IncorrectSegmentSize {
+                position: from.unchecked_offset(),
+                value: count.unchecked_offset(),
+            });
+        }
+
+        let unused_bits = bytes_for_bits(bits) * 8 - bits;
+        if unused_bits > 0 {
+            let last = start + size - 1;
+            let padding = buffer[last] & ((1u8 << unused_bits) - 1);
+            if padding != 0 {
+                return Err(Error::IncorrectBitVecPadding {
+                    position: last as Offset,
+                    value: buffer[last],
+                });
+            }
+        }
         Ok(latest_segment)
     }
 }
 
+// The length prefix can't hold more bits, so longer vectors can't be written.
+fn bit_vec_length(vec: &BitVec) -> u32 {
+    assert!(
+        vec.len() <= u32::max_value() as usize,
+        "BitVec of {} bits is too long to be written",
+        vec.len()
+    );
+    vec.len() as u32
+}
+
+fn bytes_for_bits(bits: usize) -> usize {
+    bits / 8 + if bits % 8 == 0 { 0 } else { 1 }
+}
+
 impl<'a> SegmentField<'a> for &'a [u8] {
     fn item_size() -> Offset {
         1
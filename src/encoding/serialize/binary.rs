@@ -0,0 +1,896 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Serde `Serializer` and `Deserializer` for the Exonum binary layout.
+//!
+//! Any type implementing `Serialize` and `Deserialize` can be written with the same
+//! header and segments layout that `encoding_struct!` produces, so the bytes can be read
+//! back with `Field` and vice versa.
+//!
+//! | Serde data model | Binary representation |
+//! |------------------|-----------------------|
+//! | `bool`, integers, floats, `char` | in place, little endian (`char` as `u32`) |
+//! | `str`, bytes | segment with the raw bytes |
+//! | sequence | segment with the items written one after another |
+//! | `Option` | segment with zero or one item |
+//! | map | segment with `(key, value)` pairs |
+//! | tuple, array | elements in place, one after another |
+//! | structure, tuple structure | segment with a nested buffer |
+//! | enum | segment with a nested buffer: `u32` variant index followed by the fields |
+//! | newtype structure | same as the wrapped value |
+//!
+//! `Hash`, `PublicKey` and `Signature` from the `crypto` module are written in place
+//! as their fixed-size bytes, the same as the corresponding `Field` implementations do.
+//! Other newtype structures with the same names are written as the wrapped value.
+//!
+//! The top-level structure or enum is the buffer itself; other top-level values are
+//! written as a structure with a single field.
+//!
+//! Note that types declared with `encoding_struct!` and some foreign types, such as
+//! `DateTime` or `SocketAddr`, implement serde traits differently from their `Field`
+//! implementations, so their binary representations differ.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[macro_use] extern crate exonum;
+//! # #[macro_use] extern crate serde_derive;
+//! # extern crate serde;
+//! # extern crate serde_json;
+//! use exonum::encoding::serialize::binary;
+//!
+//! encoding_struct! {
+//!     struct Wallet {
+//!         name: &str,
+//!         balance: u64,
+//!     }
+//! }
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct PlainWallet {
+//!     name: String,
+//!     balance: u64,
+//! }
+//!
+//! # fn main() {
+//! let wallet = PlainWallet { name: "Alice".to_owned(), balance: 100 };
+//! let bytes = binary::to_bytes(&wallet).unwrap();
+//!
+//! let encoded: Wallet = exonum::storage::StorageValue::from_bytes(bytes.clone().into());
+//! assert_eq!(encoded.name(), "Alice");
+//! assert_eq!(encoded.balance(), 100);
+//! assert_eq!(binary::from_bytes::<PlainWallet>(&bytes).unwrap(), wallet);
+//! # }
+//! ```
+
+use byteorder::{ByteOrder, LittleEndian};
+use serde::{de, ser, Deserialize, Serialize};
+use serde::de::IntoDeserializer;
+
+use std::fmt::Display;
+use std::str;
+
+use crypto::{HASH_SIZE, PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH};
+use encoding::{Error, Offset};
+
+/// Result of the binary serialization.
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+// Size of the segment pointer: position and count.
+const SEGMENT_POINTER_SIZE: usize = 8;
+
+// Newtype structures wrapping fixed-size byte arrays which are written in place.
+// The crypto types are renamed for serde to these names, which are not valid
+// Rust identifiers, so user types can't collide with them.
+const FIXED_SIZE_BYTES: &[(&str, usize)] = &[
+    ("$exonum::Hash", HASH_SIZE),
+    ("$exonum::PublicKey", PUBLIC_KEY_LENGTH),
+    ("$exonum::Signature", SIGNATURE_LENGTH),
+];
+
+fn fixed_size_bytes(name: &str) -> Option<usize> {
+    FIXED_SIZE_BYTES
+        .iter()
+        .find(|&&(newtype, _)| newtype == name)
+        .map(|&(_, len)| len)
+}
+
+/// Serializes the value into the Exonum binary layout.
+pub fn to_bytes<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>> {
+    Ok(value.serialize(Serializer::new())?.into_bytes())
+}
+
+/// Deserializes the value from the Exonum binary layout.
+///
+/// The whole buffer must be used, and segments must follow each other without gaps
+/// or overlaps, as `Field::check` requires.
+pub fn from_bytes<'de, T: Deserialize<'de>>(bytes: &'de [u8]) -> Result<T> {
+    let mut deserializer = Deserializer::new(bytes);
+    let value = T::deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::Basic(msg.to_string().into())
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::Basic(msg.to_string().into())
+    }
+}
+
+#[derive(Debug)]
+enum Node {
+    // Bytes written in place.
+    Fixed(Vec<u8>),
+    // Nodes written in place one after another.
+    Inline(Vec<Node>),
+    // Segment with raw bytes.
+    Bytes(Vec<u8>),
+    // Segment with items.
+    Items(Vec<Node>),
+    // Segment with a nested buffer.
+    Buffer(Vec<u8>),
+}
+
+impl Node {
+    fn header_size(&self) -> usize {
+        match *self {
+            Node::Fixed(ref bytes) => bytes.len(),
+            Node::Inline(ref nodes) => nodes.iter().map(Node::header_size).sum(),
+            Node::Bytes(_) | Node::Items(_) | Node::Buffer(_) => SEGMENT_POINTER_SIZE,
+        }
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>, from: usize) {
+        match *self {
+            Node::Fixed(ref bytes) => buffer[from..from + bytes.len()].copy_from_slice(bytes),
+            Node::Inline(ref nodes) => write_inline(nodes, buffer, from),
+            Node::Bytes(ref bytes) | Node::Buffer(ref bytes) => {
+                write_pointer(buffer, from, bytes.len());
+                buffer.extend_from_slice(bytes);
+            }
+            Node::Items(ref items) => {
+                write_pointer(buffer, from, items.len());
+                let start = buffer.len();
+                let size = items.iter().map(Node::header_size).sum::<usize>();
+                buffer.resize(start + size, 0);
+                write_inline(items, buffer, start);
+            }
+        }
+    }
+}
+
+fn write_inline(nodes: &[Node], buffer: &mut Vec<u8>, from: usize) {
+    let mut from = from;
+    for node in nodes {
+        node.write(buffer, from);
+        from += node.header_size();
+    }
+}
+
+fn write_pointer(buffer: &mut Vec<u8>, from: usize, count: usize) {
+    let position = buffer.len() as Offset;
+    LittleEndian::write_u32(&mut buffer[from..from + 4], position);
+    LittleEndian::write_u32(
+        &mut buffer[from + 4..from + SEGMENT_POINTER_SIZE],
+        count as Offset,
+    );
+}
+
+// Builds a buffer of a structure with the given fields.
+fn structure(fields: &[Node]) -> Vec<u8> {
+    let mut buffer = vec![0; fields.iter().map(Node::header_size).sum()];
+    write_inline(fields, &mut buffer, 0);
+    buffer
+}
+
+fn variant_index(index: u32) -> Node {
+    let mut bytes = vec![0; 4];
+    LittleEndian::write_u32(&mut bytes, index);
+    Node::Fixed(bytes)
+}
+
+/// Value serialized by the [`Serializer`](struct.Serializer.html).
+#[derive(Debug)]
+pub struct Encoded(Node);
+
+impl Encoded {
+    /// Returns the buffer with the serialized value.
+    pub fn into_bytes(self) -> Vec<u8> {
+        match self.0 {
+            Node::Buffer(buffer) => buffer,
+            node => structure(&[node]),
+        }
+    }
+}
+
+/// Serde `Serializer` producing the Exonum binary layout.
+#[derive(Debug, Default)]
+pub struct Serializer {
+    fixed_bytes: Option<usize>,
+}
+
+impl Serializer {
+    /// Creates a new serializer.
+    pub fn new() -> Self {
+        Serializer { fixed_bytes: None }
+    }
+}
+
+macro_rules! serialize_fixed {
+    ($method:ident, $type:ty, $size:expr, $write:path) => {
+        fn $method(self, value: $type) -> Result<Encoded> {
+            let mut bytes = vec![0; $size];
+            $write(&mut bytes, value);
+            Ok(Encoded(Node::Fixed(bytes)))
+        }
+    };
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = Encoded;
+    type Error = Error;
+    type SerializeSeq = Compound;
+    type SerializeTuple = Compound;
+    type SerializeTupleStruct = Compound;
+    type SerializeTupleVariant = Compound;
+    type SerializeMap = Compound;
+    type SerializeStruct = Compound;
+    type SerializeStructVariant = Compound;
+
+    fn serialize_bool(self, value: bool) -> Result<Encoded> {
+        Ok(Encoded(Node::Fixed(vec![value as u8])))
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<Encoded> {
+        Ok(Encoded(Node::Fixed(vec![value as u8])))
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<Encoded> {
+        Ok(Encoded(Node::Fixed(vec![value])))
+    }
+
+    serialize_fixed!(serialize_i16, i16, 2, LittleEndian::write_i16);
+    serialize_fixed!(serialize_u16, u16, 2, LittleEndian::write_u16);
+    serialize_fixed!(serialize_i32, i32, 4, LittleEndian::write_i32);
+    serialize_fixed!(serialize_u32, u32, 4, LittleEndian::write_u32);
+    serialize_fixed!(serialize_i64, i64, 8, LittleEndian::write_i64);
+    serialize_fixed!(serialize_u64, u64, 8, LittleEndian::write_u64);
+    serialize_fixed!(serialize_f32, f32, 4, LittleEndian::write_f32);
+    serialize_fixed!(serialize_f64, f64, 8, LittleEndian::write_f64);
+
+    fn serialize_char(self, value: char) -> Result<Encoded> {
+        self.serialize_u32(value as u32)
+    }
+
+    fn serialize_str(self, value: &str) -> Result<Encoded> {
+        Ok(Encoded(Node::Bytes(value.as_bytes().to_vec())))
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<Encoded> {
+        match self.fixed_bytes {
+            Some(len) if len == value.len() => Ok(Encoded(Node::Fixed(value.to_vec()))),
+            Some(_) => Err(Error::Basic("Incorrect length of fixed-size bytes".into())),
+            None => Ok(Encoded(Node::Bytes(value.to_vec()))),
+        }
+    }
+
+    fn serialize_none(self) -> Result<Encoded> {
+        Ok(Encoded(Node::Items(Vec::new())))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Encoded> {
+        let item = value.serialize(Serializer::new())?;
+        Ok(Encoded(Node::Items(vec![item.0])))
+    }
+
+    fn serialize_unit(self) -> Result<Encoded> {
+        Ok(Encoded(Node::Fixed(Vec::new())))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Encoded> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        index: u32,
+        _variant: &'static str,
+    ) -> Result<Encoded> {
+        Ok(Encoded(Node::Buffer(structure(&[variant_index(index)]))))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Encoded> {
+        value.serialize(Serializer {
+            fixed_bytes: fixed_size_bytes(name),
+        })
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<Encoded> {
+        let value = value.serialize(Serializer::new())?;
+        Ok(Encoded(Node::Buffer(
+            structure(&[variant_index(index), value.0]),
+        )))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound> {
+        Ok(Compound::new(Kind::Items, Vec::new()))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Compound> {
+        Ok(Compound::new(Kind::Inline, Vec::new()))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Compound> {
+        Ok(Compound::new(Kind::Buffer, Vec::new()))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound> {
+        Ok(Compound::new(Kind::Buffer, vec![variant_index(index)]))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound> {
+        Ok(Compound::new(Kind::Items, Vec::new()))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Compound> {
+        Ok(Compound::new(Kind::Buffer, Vec::new()))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound> {
+        Ok(Compound::new(Kind::Buffer, vec![variant_index(index)]))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug)]
+enum Kind {
+    Inline,
+    Items,
+    Buffer,
+}
+
+/// Serializer of compound values: sequences, maps, tuples and structures.
+#[derive(Debug)]
+pub struct Compound {
+    kind: Kind,
+    nodes: Vec<Node>,
+    key: Option<Node>,
+}
+
+impl Compound {
+    fn new(kind: Kind, nodes: Vec<Node>) -> Self {
+        Compound {
+            kind,
+            nodes,
+            key: None,
+        }
+    }
+
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let value = value.serialize(Serializer::new())?;
+        self.nodes.push(value.0);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Encoded> {
+        let node = match self.kind {
+            Kind::Inline => Node::Inline(self.nodes),
+            Kind::Items => Node::Items(self.nodes),
+            Kind::Buffer => Node::Buffer(structure(&self.nodes)),
+        };
+        Ok(Encoded(node))
+    }
+}
+
+macro_rules! impl_serialize_compound {
+    ($($trait_name:ident :: $method:ident ($($name:ident),*));*) => {
+        $(
+            impl ser::$trait_name for Compound {
+                type Ok = Encoded;
+                type Error = Error;
+
+                fn $method<T: Serialize + ?Sized>(
+                    &mut self,
+                    $($name: &'static str,)*
+                    value: &T,
+                ) -> Result<()> {
+                    self.push(value)
+                }
+
+                fn end(self) -> Result<Encoded> {
+                    self.finish()
+                }
+            }
+        )*
+    };
+}
+
+impl_serialize_compound!{
+    SerializeSeq::serialize_element();
+    SerializeTuple::serialize_element();
+    SerializeTupleStruct::serialize_field();
+    SerializeTupleVariant::serialize_field();
+    SerializeStruct::serialize_field(_key);
+    SerializeStructVariant::serialize_field(_key)
+}
+
+impl ser::SerializeMap for Compound {
+    type Ok = Encoded;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        self.key = Some(key.serialize(Serializer::new())?.0);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let key = self.key
+            .take()
+            .ok_or_else(|| Error::Basic("Map value is serialized before the key".into()))?;
+        let value = value.serialize(Serializer::new())?;
+        self.nodes.push(Node::Inline(vec![key, value.0]));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Encoded> {
+        self.finish()
+    }
+}
+
+/// Serde `Deserializer` reading the Exonum binary layout.
+#[derive(Debug)]
+pub struct Deserializer<'de> {
+    buffer: &'de [u8],
+    position: usize,
+    // Ranges of the buffer occupied by segments, in the order of their pointers.
+    segments: Vec<(usize, usize)>,
+    // Top-level structure is read from the buffer itself.
+    root: bool,
+    fixed_bytes: Option<usize>,
+}
+
+impl<'de> Deserializer<'de> {
+    /// Creates a deserializer reading a value from the given buffer.
+    pub fn new(buffer: &'de [u8]) -> Self {
+        Deserializer {
+            buffer,
+            position: 0,
+            segments: Vec::new(),
+            root: true,
+            fixed_bytes: None,
+        }
+    }
+
+    fn nested(buffer: &'de [u8]) -> Self {
+        Deserializer {
+            root: false,
+            ..Deserializer::new(buffer)
+        }
+    }
+
+    /// Checks that the header and segments cover the whole buffer without gaps
+    /// and overlaps. As in `Field::check`, each segment should follow the previous one
+    /// in the order of the pointers, and the nested segments should follow their parent.
+    pub fn end(self) -> Result<()> {
+        let header = (0, self.position);
+        let mut last_end = 0;
+        for (start, end) in Some(header).into_iter().chain(self.segments) {
+            if start < last_end {
+                return Err(Error::OverlappingSegment {
+                    last_end: last_end as Offset,
+                    start: start as Offset,
+                });
+            } else if start > last_end {
+                return Err(Error::SpaceBetweenSegments {
+                    last_end: last_end as Offset,
+                    start: start as Offset,
+                });
+            }
+            last_end = end;
+        }
+        if last_end != self.buffer.len() {
+            return Err(Error::Basic("Unexpected bytes after the last segment".into()));
+        }
+        Ok(())
+    }
+
+    fn read(&mut self, size: usize) -> Result<&'de [u8]> {
+        self.root = false;
+        let from = self.position;
+        let to = from + size;
+        if to > self.buffer.len() {
+            return Err(Error::UnexpectedlyShortPayload {
+                actual_size: self.buffer.len() as Offset,
+                minimum_size: to as Offset,
+            });
+        }
+        self.position = to;
+        Ok(&self.buffer[from..to])
+    }
+
+    // Reads segment pointer, returns position and count of the segment.
+    fn read_pointer(&mut self) -> Result<(usize, usize)> {
+        let position = self.position;
+        let pointer = self.read(SEGMENT_POINTER_SIZE)?;
+        let start = LittleEndian::read_u32(&pointer[..4]) as usize;
+        let count = LittleEndian::read_u32(&pointer[4..]) as usize;
+        if start > self.buffer.len() {
+            return Err(Error::IncorrectSegmentReference {
+                position: position as Offset,
+                value: start as Offset,
+            });
+        }
+        Ok((start, count))
+    }
+
+    fn read_segment(&mut self) -> Result<&'de [u8]> {
+        let position = self.position;
+        let (start, count) = self.read_pointer()?;
+        if count > self.buffer.len() - start {
+            return Err(Error::IncorrectSegmentSize {
+                position: (position + 4) as Offset,
+                value: count as Offset,
+            });
+        }
+        self.segments.push((start, start + count));
+        Ok(&self.buffer[start..start + count])
+    }
+
+    // Reads items of the segment in place.
+    fn with_items<V, F>(&mut self, read: F) -> Result<V>
+    where
+        F: FnOnce(&mut Self, usize) -> Result<V>,
+    {
+        let position = self.position;
+        let (start, count) = self.read_pointer()?;
+        // Every supported item, except `()`, takes at least one byte.
+        if count > self.buffer.len() - start {
+            return Err(Error::IncorrectSegmentSize {
+                position: (position + 4) as Offset,
+                value: count as Offset,
+            });
+        }
+        // The end of the segment is known after its items are read, but the segment
+        // precedes the nested ones.
+        let index = self.segments.len();
+        self.segments.push((start, start));
+        let resume = self.position;
+        self.position = start;
+        let value = read(self, count)?;
+        self.segments[index].1 = self.position;
+        self.position = resume;
+        Ok(value)
+    }
+
+    // Reads a structure from a segment or, for the top-level structure, from the buffer.
+    fn with_structure<V, F>(&mut self, read: F) -> Result<V>
+    where
+        F: FnOnce(&mut Deserializer<'de>) -> Result<V>,
+    {
+        if self.root {
+            self.root = false;
+            return read(self);
+        }
+        let buffer = self.read_segment()?;
+        let mut nested = Deserializer::nested(buffer);
+        let value = read(&mut nested)?;
+        nested.end()?;
+        Ok(value)
+    }
+}
+
+fn visit_items<'de, V: de::Visitor<'de>>(
+    de: &mut Deserializer<'de>,
+    count: usize,
+    visitor: V,
+) -> Result<V::Value> {
+    let mut items = Items { de, remaining: count };
+    let value = visitor.visit_seq(&mut items)?;
+    items.end()?;
+    Ok(value)
+}
+
+macro_rules! deserialize_fixed {
+    ($method:ident, $visit:ident, $size:expr, $read:path) => {
+        fn $method<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+            let bytes = self.read($size)?;
+            visitor.$visit($read(bytes))
+        }
+    };
+}
+
+macro_rules! deserialize_unsupported {
+    ($($method:ident),*) => {
+        $(
+            fn $method<V: de::Visitor<'de>>(self, _visitor: V) -> Result<V::Value> {
+                Err(Error::Basic("Exonum binary format is not self-describing".into()))
+            }
+        )*
+    };
+}
+
+impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
+    type Error = Error;
+
+    deserialize_unsupported!(deserialize_any, deserialize_identifier, deserialize_ignored_any);
+
+    fn deserialize_bool<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let position = self.position;
+        match self.read(1)?[0] {
+            0 => visitor.visit_bool(false),
+            1 => visitor.visit_bool(true),
+            value => Err(Error::IncorrectBoolean {
+                position: position as Offset,
+                value,
+            }),
+        }
+    }
+
+    fn deserialize_i8<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i8(self.read(1)?[0] as i8)
+    }
+
+    fn deserialize_u8<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u8(self.read(1)?[0])
+    }
+
+    deserialize_fixed!(deserialize_i16, visit_i16, 2, LittleEndian::read_i16);
+    deserialize_fixed!(deserialize_u16, visit_u16, 2, LittleEndian::read_u16);
+    deserialize_fixed!(deserialize_i32, visit_i32, 4, LittleEndian::read_i32);
+    deserialize_fixed!(deserialize_u32, visit_u32, 4, LittleEndian::read_u32);
+    deserialize_fixed!(deserialize_i64, visit_i64, 8, LittleEndian::read_i64);
+    deserialize_fixed!(deserialize_u64, visit_u64, 8, LittleEndian::read_u64);
+    deserialize_fixed!(deserialize_f32, visit_f32, 4, LittleEndian::read_f32);
+    deserialize_fixed!(deserialize_f64, visit_f64, 8, LittleEndian::read_f64);
+
+    fn deserialize_char<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let value = LittleEndian::read_u32(self.read(4)?);
+        let value = ::std::char::from_u32(value)
+            .ok_or_else(|| Error::Basic("Incorrect char value".into()))?;
+        visitor.visit_char(value)
+    }
+
+    fn deserialize_str<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let position = self.position;
+        let bytes = self.read_segment()?;
+        let value = str::from_utf8(bytes).map_err(|error| Error::Utf8 {
+            position: position as Offset,
+            error,
+        })?;
+        visitor.visit_borrowed_str(value)
+    }
+
+    fn deserialize_string<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let bytes = match self.fixed_bytes.take() {
+            Some(len) => self.read(len)?,
+            None => self.read_segment()?,
+        };
+        visitor.visit_borrowed_bytes(bytes)
+    }
+
+    fn deserialize_byte_buf<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let position = self.position;
+        self.with_items(|de, count| match count {
+            0 => visitor.visit_none(),
+            1 => visitor.visit_some(de),
+            _ => Err(Error::IncorrectSegmentSize {
+                position: (position + 4) as Offset,
+                value: count as Offset,
+            }),
+        })
+    }
+
+    fn deserialize_unit<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.root = false;
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.fixed_bytes = fixed_size_bytes(name);
+        let value = visitor.visit_newtype_struct(&mut *self);
+        self.fixed_bytes = None;
+        value
+    }
+
+    fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.with_items(|de, count| visit_items(de, count, visitor))
+    }
+
+    fn deserialize_tuple<V: de::Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value> {
+        self.root = false;
+        visit_items(self, len, visitor)
+    }
+
+    fn deserialize_tuple_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.with_structure(|de| visit_items(de, len, visitor))
+    }
+
+    fn deserialize_map<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.with_items(|de, count| {
+            let mut items = Items { de, remaining: count };
+            let value = visitor.visit_map(&mut items)?;
+            items.end()?;
+            Ok(value)
+        })
+    }
+
+    fn deserialize_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.with_structure(|de| visit_items(de, fields.len(), visitor))
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.with_structure(|de| visitor.visit_enum(de))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+// Access to items written in place one after another.
+struct Items<'a, 'de: 'a> {
+    de: &'a mut Deserializer<'de>,
+    remaining: usize,
+}
+
+impl<'a, 'de> Items<'a, 'de> {
+    fn end(self) -> Result<()> {
+        if self.remaining != 0 {
+            return Err(Error::Basic("Not all items are read".into()));
+        }
+        Ok(())
+    }
+}
+
+impl<'a, 'de> de::SeqAccess<'de> for Items<'a, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'a, 'de> de::MapAccess<'de> for Items<'a, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'a, 'de> de::EnumAccess<'de> for &'a mut Deserializer<'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self)> {
+        let index = LittleEndian::read_u32(self.read(4)?);
+        let index: de::value::U32Deserializer<Error> = index.into_deserializer();
+        let value = seed.deserialize(index)?;
+        Ok((value, self))
+    }
+}
+
+impl<'a, 'de> de::VariantAccess<'de> for &'a mut Deserializer<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: de::Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value> {
+        visit_items(self, len, visitor)
+    }
+
+    fn struct_variant<V: de::Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        visit_items(self, fields.len(), visitor)
+    }
+}
@@ -0,0 +1,254 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Canonical form of the `ExonumJson` representation, suitable for signing and hashing.
+//!
+//! The canonical form is a deterministic serialization of a json value:
+//!
+//! - object keys are sorted by their Unicode code points, which is the same as
+//!   sorting by UTF-8 bytes;
+//! - there is no whitespace between tokens;
+//! - integers are written in decimal without leading zeros or exponent, floats with an
+//!   integer value (including `-0.0`) are written as integers, other floats use
+//!   the shortest representation that round-trips, with an exponent if the absolute
+//!   value is at least `1e21` or less than `1e-6`;
+//! - strings escape only `"`, `\` and control characters, control characters
+//!   without a short escape are written as `\u00xx` with lowercase hex digits.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate exonum;
+//! # extern crate serde_json;
+//! use exonum::encoding::serialize::canonical::to_canonical_string;
+//!
+//! # fn main() {
+//! let value = serde_json::from_str(r#"{ "b": [1.0, "é"], "a": null }"#).unwrap();
+//! assert_eq!(to_canonical_string(&value), r#"{"a":null,"b":[1,"é"]}"#);
+//! # }
+//! ```
+
+use serde_json::{Number, Value};
+
+use std::error::Error;
+use std::fmt::Write;
+
+use crypto::{hash, Hash};
+use super::json::ExonumJson;
+
+// Floats with an absolute value below this bound represent integers exactly.
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+/// Returns canonical form of the json value.
+pub fn to_canonical_string(value: &Value) -> String {
+    let mut out = String::new();
+    write_value(value, &mut out);
+    out
+}
+
+/// Returns canonical form of the json value as bytes.
+pub fn to_canonical_vec(value: &Value) -> Vec<u8> {
+    to_canonical_string(value).into_bytes()
+}
+
+/// Returns canonical form of the `ExonumJson` representation of the value.
+pub fn to_canonical_json<T: ExonumJson + ?Sized>(
+    value: &T,
+) -> Result<String, Box<Error + Send + Sync>> {
+    Ok(to_canonical_string(&value.serialize_field()?))
+}
+
+/// A trait for the ability to compute a hash of the canonical json of a value.
+///
+/// Unlike `CryptoHash`, the hash doesn't depend on the binary layout, so
+/// it could be computed by clients which work with json only.
+pub trait CanonicalJsonHash {
+    /// Returns hash of the canonical form of the `ExonumJson` representation.
+    fn canonical_json_hash(&self) -> Result<Hash, Box<Error + Send + Sync>>;
+}
+
+impl<T: ExonumJson> CanonicalJsonHash for T {
+    fn canonical_json_hash(&self) -> Result<Hash, Box<Error + Send + Sync>> {
+        Ok(hash(to_canonical_json(self)?.as_bytes()))
+    }
+}
+
+fn write_value(value: &Value, out: &mut String) {
+    match *value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(value) => out.push_str(if value { "true" } else { "false" }),
+        Value::Number(ref number) => write_number(number, out),
+        Value::String(ref string) => write_string(string, out),
+        Value::Array(ref items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(item, out);
+            }
+            out.push(']');
+        }
+        Value::Object(ref map) => {
+            let mut entries = map.iter().collect::<Vec<_>>();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(key, out);
+                out.push(':');
+                write_value(value, out);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn write_number(number: &Number, out: &mut String) {
+    if let Some(value) = number.as_u64() {
+        write!(out, "{}", value).unwrap();
+    } else if let Some(value) = number.as_i64() {
+        write!(out, "{}", value).unwrap();
+    } else {
+        let value = number.as_f64().expect("Number should be representable as f64");
+        let abs = value.abs();
+        if value.fract() == 0.0 && abs < MAX_SAFE_INTEGER {
+            write!(out, "{}", value as i64).unwrap();
+        } else if abs >= 1e21 || abs < 1e-6 {
+            write!(out, "{:e}", value).unwrap();
+        } else {
+            write!(out, "{}", value).unwrap();
+        }
+    }
+}
+
+fn write_string(string: &str, out: &mut String) {
+    out.push('"');
+    for ch in string.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => write!(out, "\\u{:04x}", ch as u32).unwrap(),
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use serde_json;
+
+    use crypto::{chain_id, hash, CryptoHash, HashAlgorithm, PublicKey, SecretKey, DEFAULT_NETWORK};
+    use encoding::serialize::FromHex;
+    use messages::{Message, Precommit, Propose};
+    use types::{Height, Round, ValidatorId};
+
+    use super::*;
+
+    fn canonical(json: &str) -> String {
+        to_canonical_string(&serde_json::from_str(json).unwrap())
+    }
+
+    #[test]
+    fn test_canonical_objects() {
+        assert_eq!(
+            canonical(r#" { "b" : { "z": 1, "y": [ ] }, "a": true, "ab": false } "#),
+            r#"{"a":true,"ab":false,"b":{"y":[],"z":1}}"#
+        );
+        assert_eq!(canonical(r#"{ "é": 1, "z": 2, "Z": 3 }"#), r#"{"Z":3,"z":2,"é":1}"#);
+    }
+
+    #[test]
+    fn test_canonical_numbers() {
+        assert_eq!(
+            canonical("[0, -0.0, 1.0, 1.5e0, 100e-2, -12, 18446744073709551615]"),
+            "[0,0,1,1.5,1,-12,18446744073709551615]"
+        );
+        assert_eq!(
+            canonical("[1e300, 0.1, 1.5e-7, -2.5E+21, 12345678.9]"),
+            "[1e300,0.1,1.5e-7,-2.5e21,12345678.9]"
+        );
+    }
+
+    #[test]
+    fn test_canonical_strings() {
+        assert_eq!(
+            canonical(r#""A\"\\\/\b\f\n\r\t\u0001\u001Fé😀""#),
+            "\"A\\\"\\\\/\\b\\f\\n\\r\\t\\u0001\\u001f\u{e9}\u{1f600}\""
+        );
+    }
+
+    #[test]
+    fn test_canonical_json_is_stable() {
+        let time = Utc.timestamp(1_500_000_000, 1);
+        let precommit = Precommit::new(
+            ValidatorId(1),
+            Height(2),
+            Round(3),
+            &HashAlgorithm::Sha256.hash(&[1]),
+            &HashAlgorithm::Sha256.hash(&[2]),
+            time,
+        );
+        let expected = concat!(
+            r#"{"block_hash":"#,
+            r#""dbc1b4c900ffe48d575b5da5c638040125f65db0fe3e24494b76ea986457d986","#,
+            r#""height":"2","propose_hash":"#,
+            r#""4bf5122f344554c53bde2ebb8cd2b7e3d1600ad631c385a5d7cce23c7785459a","#,
+            r#""round":3,"time":{"nanos":1,"secs":"1500000000"},"validator":1}"#
+        );
+        assert_eq!(to_canonical_json(&precommit).unwrap(), expected);
+        assert_eq!(
+            precommit.canonical_json_hash().unwrap(),
+            hash(expected.as_bytes())
+        );
+
+        // Deserialization doesn't change the canonical form.
+        let parsed: Precommit = serde_json::from_str(expected).unwrap();
+        assert_eq!(to_canonical_json(&parsed).unwrap(), expected);
+        assert_eq!(parsed.hash(), precommit.hash());
+    }
+
+    #[test]
+    fn test_canonical_json_of_signed_message() {
+        let secret_key = SecretKey::from_hex(
+            "0101010101010101010101010101010101010101010101010101010101010101\
+             8a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c",
+        ).unwrap();
+        let public_key = PublicKey::from_hex(
+            "8a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c",
+        ).unwrap();
+        let propose = Propose::new(ValidatorId(0), Height(1), Round(1), &hash(&[]), &[]);
+
+        let chain_id = chain_id(DEFAULT_NETWORK);
+        let first = Message::new(propose.clone(), public_key, &chain_id, &secret_key).unwrap();
+        let second = Message::new(propose, public_key, &chain_id, &secret_key).unwrap();
+        assert_eq!(
+            to_canonical_json(&first).unwrap(),
+            to_canonical_json(&second).unwrap()
+        );
+        assert_eq!(
+            first.canonical_json_hash().unwrap(),
+            second.canonical_json_hash().unwrap()
+        );
+    }
+}
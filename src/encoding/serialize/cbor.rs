@@ -0,0 +1,1202 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! CBOR ([RFC 8949]) representation of values.
+//!
+//! There are two ways to get CBOR of a value:
+//!
+//! - any `ExonumJson` type could be written in CBOR instead of json text, see
+//!   [`Format`]. Such CBOR has the same structure as the json value, e.g. hashes are
+//!   hex strings;
+//! - storage values implement [`CborValue`], which uses native CBOR types:
+//!   hashes and keys are byte strings, time is a tagged value and so on.
+//!   Structures declared with `encoding_struct!` are byte strings with their
+//!   binary representation, which is checked while reading.
+//!
+//! The encoder produces the core deterministic encoding: the shortest form of integers,
+//! lengths and floats, definite lengths and map keys sorted by their encoded bytes.
+//! The decoder accepts any well-formed CBOR, including indefinite lengths.
+//!
+//! [RFC 8949]: https://tools.ietf.org/html/rfc8949
+//! [`Format`]: ../enum.Format.html
+//! [`CborValue`]: trait.CborValue.html
+
+use chrono::{DateTime, Duration, SecondsFormat, TimeZone, Utc};
+use rust_decimal::Decimal;
+use serde_json::{self, Number, Value};
+use uuid::Uuid;
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crypto::{Hash, PublicKey, Signature};
+use encoding::Field;
+#[cfg(feature = "float_serialize")]
+use encoding::{F32, F64};
+use storage::StorageValue;
+use types::Round;
+use super::from_raw_checked;
+use super::json::{ExonumJson, ExonumJsonDeserialize, JsonError};
+
+/// Tag of the standard date/time string.
+pub const TAG_DATE_TIME_STRING: u64 = 0;
+/// Tag of the epoch-based date/time.
+pub const TAG_EPOCH_DATE_TIME: u64 = 1;
+/// Tag of the binary UUID.
+pub const TAG_UUID: u64 = 37;
+
+const MAJOR_UNSIGNED: u8 = 0;
+const MAJOR_NEGATIVE: u8 = 1;
+const MAJOR_BYTES: u8 = 2;
+const MAJOR_TEXT: u8 = 3;
+const MAJOR_ARRAY: u8 = 4;
+const MAJOR_MAP: u8 = 5;
+const MAJOR_TAG: u8 = 6;
+const MAJOR_SIMPLE: u8 = 7;
+
+const INDEFINITE: u8 = 31;
+const BREAK: u8 = 0xff;
+
+// Limit of nesting of the decoded data items, protects the stack.
+const MAX_DEPTH: usize = 128;
+
+/// CBOR data item.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataItem {
+    /// Unsigned integer.
+    Unsigned(u64),
+    /// Negative integer, equal to `-1 - n`.
+    Negative(u64),
+    /// Byte string.
+    Bytes(Vec<u8>),
+    /// Text string.
+    Text(String),
+    /// Array of data items.
+    Array(Vec<DataItem>),
+    /// Map of data items, in the order of the entries.
+    Map(Vec<(DataItem, DataItem)>),
+    /// Tagged data item.
+    Tag(u64, Box<DataItem>),
+    /// `false` or `true`.
+    Bool(bool),
+    /// `null`.
+    Null,
+    /// `undefined`.
+    Undefined,
+    /// Floating point number.
+    Float(f64),
+}
+
+impl DataItem {
+    /// Returns value of the integer item, if it fits into `i64`.
+    fn as_i64(&self) -> Option<i64> {
+        match *self {
+            DataItem::Unsigned(value) if value <= i64::max_value() as u64 => Some(value as i64),
+            DataItem::Negative(value) if value <= i64::max_value() as u64 => {
+                Some(-1 - value as i64)
+            }
+            _ => None,
+        }
+    }
+
+    fn from_i64(value: i64) -> Self {
+        if value < 0 {
+            DataItem::Negative((-1 - value) as u64)
+        } else {
+            DataItem::Unsigned(value as u64)
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        match *self {
+            DataItem::Unsigned(_) | DataItem::Negative(_) => "integer",
+            DataItem::Bytes(_) => "byte string",
+            DataItem::Text(_) => "text string",
+            DataItem::Array(_) => "array",
+            DataItem::Map(_) => "map",
+            DataItem::Tag(..) => "tag",
+            DataItem::Bool(_) => "bool",
+            DataItem::Null => "null",
+            DataItem::Undefined => "undefined",
+            DataItem::Float(_) => "float",
+        }
+    }
+}
+
+/// Error of decoding CBOR.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CborError {
+    /// Buffer ends in the middle of a data item.
+    UnexpectedEnd,
+    /// Byte doesn't start a well-formed data item.
+    InvalidHeader {
+        /// Position of the byte.
+        position: usize,
+        /// Value of the byte.
+        byte: u8,
+    },
+    /// Text string is not valid UTF-8.
+    InvalidUtf8 {
+        /// Position of the text string.
+        position: usize,
+    },
+    /// Buffer contains bytes after the data item.
+    TrailingBytes {
+        /// Position of the first unused byte.
+        position: usize,
+    },
+    /// Data items are nested too deep.
+    TooDeep,
+    /// Data item has unexpected type.
+    UnexpectedItem {
+        /// Expected type.
+        expected: &'static str,
+        /// Actual type.
+        found: &'static str,
+    },
+    /// Value doesn't fit into the type.
+    OutOfRange {
+        /// Name of the type.
+        type_name: &'static str,
+    },
+    /// Byte string is not a correct binary representation of the structure.
+    InvalidStructure {
+        /// Name of the type.
+        type_name: &'static str,
+        /// Description of the error.
+        error: String,
+    },
+}
+
+impl CborError {
+    fn unexpected(expected: &'static str, found: &DataItem) -> Self {
+        CborError::UnexpectedItem {
+            expected,
+            found: found.type_name(),
+        }
+    }
+}
+
+impl fmt::Display for CborError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CborError::UnexpectedEnd => write!(f, "Unexpected end of the buffer"),
+            CborError::InvalidHeader { position, byte } => {
+                write!(f, "Invalid initial byte {:#04x} at position {}", byte, position)
+            }
+            CborError::InvalidUtf8 { position } => {
+                write!(f, "Invalid UTF-8 in text string at position {}", position)
+            }
+            CborError::TrailingBytes { position } => {
+                write!(f, "Unexpected bytes after the data item at position {}", position)
+            }
+            CborError::TooDeep => write!(f, "Data items are nested deeper than {}", MAX_DEPTH),
+            CborError::UnexpectedItem { expected, found } => {
+                write!(f, "Expected {}, found {}", expected, found)
+            }
+            CborError::OutOfRange { type_name } => {
+                write!(f, "Value is out of range of {}", type_name)
+            }
+            CborError::InvalidStructure { type_name, ref error } => {
+                write!(f, "Invalid binary representation of {}: {}", type_name, error)
+            }
+        }
+    }
+}
+
+impl Error for CborError {
+    fn description(&self) -> &str {
+        match *self {
+            CborError::UnexpectedEnd => "Unexpected end of the buffer",
+            CborError::InvalidHeader { .. } => "Invalid initial byte",
+            CborError::InvalidUtf8 { .. } => "Invalid UTF-8 in text string",
+            CborError::TrailingBytes { .. } => "Unexpected bytes after the data item",
+            CborError::TooDeep => "Data items are nested too deep",
+            CborError::UnexpectedItem { .. } => "Unexpected data item",
+            CborError::OutOfRange { .. } => "Value is out of range",
+            CborError::InvalidStructure { .. } => "Invalid binary representation",
+        }
+    }
+}
+
+impl From<CborError> for JsonError {
+    fn from(e: CborError) -> Self {
+        JsonError::invalid_value(format!("Invalid CBOR: {}", e))
+    }
+}
+
+/// Returns encoded data item.
+pub fn encode(item: &DataItem) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    write_item(item, &mut buffer);
+    buffer
+}
+
+/// Decodes a single data item, which should take the whole buffer.
+pub fn decode(buffer: &[u8]) -> Result<DataItem, CborError> {
+    let mut decoder = Decoder { buffer, pos: 0 };
+    let item = decoder.read_item(0)?;
+    if decoder.pos != buffer.len() {
+        return Err(CborError::TrailingBytes {
+            position: decoder.pos,
+        });
+    }
+    Ok(item)
+}
+
+fn write_header(major: u8, argument: u64, buffer: &mut Vec<u8>) {
+    let major = major << 5;
+    if argument < 24 {
+        buffer.push(major | argument as u8);
+    } else if argument <= u64::from(u8::max_value()) {
+        buffer.push(major | 24);
+        buffer.push(argument as u8);
+    } else if argument <= u64::from(u16::max_value()) {
+        buffer.push(major | 25);
+        buffer.extend_from_slice(&(argument as u16).to_be_bytes());
+    } else if argument <= u64::from(u32::max_value()) {
+        buffer.push(major | 26);
+        buffer.extend_from_slice(&(argument as u32).to_be_bytes());
+    } else {
+        buffer.push(major | 27);
+        buffer.extend_from_slice(&argument.to_be_bytes());
+    }
+}
+
+fn write_item(item: &DataItem, buffer: &mut Vec<u8>) {
+    match *item {
+        DataItem::Unsigned(value) => write_header(MAJOR_UNSIGNED, value, buffer),
+        DataItem::Negative(value) => write_header(MAJOR_NEGATIVE, value, buffer),
+        DataItem::Bytes(ref bytes) => {
+            write_header(MAJOR_BYTES, bytes.len() as u64, buffer);
+            buffer.extend_from_slice(bytes);
+        }
+        DataItem::Text(ref text) => {
+            write_header(MAJOR_TEXT, text.len() as u64, buffer);
+            buffer.extend_from_slice(text.as_bytes());
+        }
+        DataItem::Array(ref items) => {
+            write_header(MAJOR_ARRAY, items.len() as u64, buffer);
+            for item in items {
+                write_item(item, buffer);
+            }
+        }
+        DataItem::Map(ref entries) => {
+            write_header(MAJOR_MAP, entries.len() as u64, buffer);
+            let mut entries = entries
+                .iter()
+                .map(|&(ref key, ref value)| (encode(key), value))
+                .collect::<Vec<_>>();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            for (key, value) in entries {
+                buffer.extend_from_slice(&key);
+                write_item(value, buffer);
+            }
+        }
+        DataItem::Tag(tag, ref item) => {
+            write_header(MAJOR_TAG, tag, buffer);
+            write_item(item, buffer);
+        }
+        DataItem::Bool(value) => write_header(MAJOR_SIMPLE, if value { 21 } else { 20 }, buffer),
+        DataItem::Null => write_header(MAJOR_SIMPLE, 22, buffer),
+        DataItem::Undefined => write_header(MAJOR_SIMPLE, 23, buffer),
+        DataItem::Float(value) => write_float(value, buffer),
+    }
+}
+
+// Floats are written in the shortest form that preserves the value, NaN is always `0xf97e00`.
+fn write_float(value: f64, buffer: &mut Vec<u8>) {
+    let major = MAJOR_SIMPLE << 5;
+    if value.is_nan() {
+        buffer.extend_from_slice(&[major | 25, 0x7e, 0x00]);
+    } else if let Some(half) = f64_to_f16(value) {
+        buffer.push(major | 25);
+        buffer.extend_from_slice(&half.to_be_bytes());
+    } else if f64::from(value as f32).to_bits() == value.to_bits() {
+        buffer.push(major | 26);
+        buffer.extend_from_slice(&(value as f32).to_bits().to_be_bytes());
+    } else {
+        buffer.push(major | 27);
+        buffer.extend_from_slice(&value.to_bits().to_be_bytes());
+    }
+}
+
+fn f16_to_f64(half: u16) -> f64 {
+    let exponent = i32::from(half >> 10 & 0x1f);
+    let mantissa = f64::from(half & 0x3ff);
+    let value = match exponent {
+        0 => mantissa * 2f64.powi(-24),
+        0x1f if mantissa == 0.0 => ::std::f64::INFINITY,
+        0x1f => ::std::f64::NAN,
+        _ => (1024.0 + mantissa) * 2f64.powi(exponent - 25),
+    };
+    if half & 0x8000 == 0 {
+        value
+    } else {
+        -value
+    }
+}
+
+// Returns half precision representation of the value, if it's exact.
+fn f64_to_f16(value: f64) -> Option<u16> {
+    let single = value as f32;
+    if f64::from(single).to_bits() != value.to_bits() {
+        return None;
+    }
+    let bits = single.to_bits();
+    let sign = (bits >> 16 & 0x8000) as u16;
+    let exponent = (bits >> 23 & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+
+    let half = if exponent == 0xff {
+        sign | 0x7c00
+    } else {
+        let exponent = exponent - 127 + 15;
+        if exponent >= 0x1f {
+            return None;
+        } else if exponent <= 0 {
+            if exponent < -10 {
+                sign
+            } else {
+                sign | ((mantissa | 0x80_0000) >> (14 - exponent)) as u16
+            }
+        } else {
+            sign | (exponent << 10) as u16 | (mantissa >> 13) as u16
+        }
+    };
+    if f16_to_f64(half).to_bits() == value.to_bits() {
+        Some(half)
+    } else {
+        None
+    }
+}
+
+struct Decoder<'a> {
+    buffer: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn read_bytes(&mut self, len: u64) -> Result<&'a [u8], CborError> {
+        if len > (self.buffer.len() - self.pos) as u64 {
+            return Err(CborError::UnexpectedEnd);
+        }
+        let bytes = &self.buffer[self.pos..self.pos + len as usize];
+        self.pos += len as usize;
+        Ok(bytes)
+    }
+
+    fn read_uint(&mut self, size: u64) -> Result<u64, CborError> {
+        Ok(self.read_bytes(size)?
+            .iter()
+            .fold(0, |acc, byte| acc << 8 | u64::from(*byte)))
+    }
+
+    // Returns the major type, the additional information and the argument,
+    // which is `None` for the indefinite length.
+    fn read_header(&mut self) -> Result<(u8, u8, Option<u64>), CborError> {
+        let position = self.pos;
+        let byte = self.read_bytes(1)?[0];
+        let (major, info) = (byte >> 5, byte & 0x1f);
+        let argument = match info {
+            0..=23 => Some(u64::from(info)),
+            24 => Some(self.read_uint(1)?),
+            25 => Some(self.read_uint(2)?),
+            26 => Some(self.read_uint(4)?),
+            27 => Some(self.read_uint(8)?),
+            INDEFINITE if major >= MAJOR_BYTES && major != MAJOR_TAG => None,
+            _ => return Err(CborError::InvalidHeader { position, byte }),
+        };
+        Ok((major, info, argument))
+    }
+
+    fn is_break(&mut self) -> Result<bool, CborError> {
+        match self.buffer.get(self.pos) {
+            Some(&BREAK) => {
+                self.pos += 1;
+                Ok(true)
+            }
+            Some(_) => Ok(false),
+            None => Err(CborError::UnexpectedEnd),
+        }
+    }
+
+    // Reads a definite or indefinite string of the given major type.
+    fn read_string(&mut self, major: u8, len: Option<u64>) -> Result<Vec<u8>, CborError> {
+        match len {
+            Some(len) => Ok(self.read_bytes(len)?.to_vec()),
+            None => {
+                let mut bytes = Vec::new();
+                while !self.is_break()? {
+                    let position = self.pos;
+                    match self.read_header()? {
+                        (chunk_major, _, Some(len)) if chunk_major == major => {
+                            bytes.extend_from_slice(self.read_bytes(len)?)
+                        }
+                        _ => {
+                            return Err(CborError::InvalidHeader {
+                                position,
+                                byte: self.buffer[position],
+                            })
+                        }
+                    }
+                }
+                Ok(bytes)
+            }
+        }
+    }
+
+    fn read_item(&mut self, depth: usize) -> Result<DataItem, CborError> {
+        if depth > MAX_DEPTH {
+            return Err(CborError::TooDeep);
+        }
+        let position = self.pos;
+        let (major, info, argument) = self.read_header()?;
+        let item = match (major, argument) {
+            (MAJOR_UNSIGNED, Some(value)) => DataItem::Unsigned(value),
+            (MAJOR_NEGATIVE, Some(value)) => DataItem::Negative(value),
+            (MAJOR_BYTES, len) => DataItem::Bytes(self.read_string(major, len)?),
+            (MAJOR_TEXT, len) => {
+                let text = String::from_utf8(self.read_string(major, len)?)
+                    .map_err(|_| CborError::InvalidUtf8 { position })?;
+                DataItem::Text(text)
+            }
+            (MAJOR_ARRAY, len) => {
+                let mut items = Vec::new();
+                match len {
+                    Some(len) => for _ in 0..len {
+                        items.push(self.read_item(depth + 1)?);
+                    },
+                    None => while !self.is_break()? {
+                        items.push(self.read_item(depth + 1)?);
+                    },
+                }
+                DataItem::Array(items)
+            }
+            (MAJOR_MAP, len) => {
+                let mut entries = Vec::new();
+                match len {
+                    Some(len) => for _ in 0..len {
+                        let key = self.read_item(depth + 1)?;
+                        entries.push((key, self.read_item(depth + 1)?));
+                    },
+                    None => while !self.is_break()? {
+                        let key = self.read_item(depth + 1)?;
+                        entries.push((key, self.read_item(depth + 1)?));
+                    },
+                }
+                DataItem::Map(entries)
+            }
+            (MAJOR_TAG, Some(tag)) => DataItem::Tag(tag, Box::new(self.read_item(depth + 1)?)),
+            (MAJOR_SIMPLE, Some(value)) => match info {
+                20 => DataItem::Bool(false),
+                21 => DataItem::Bool(true),
+                22 => DataItem::Null,
+                23 => DataItem::Undefined,
+                25 => DataItem::Float(f16_to_f64(value as u16)),
+                26 => DataItem::Float(f64::from(f32::from_bits(value as u32))),
+                27 => DataItem::Float(f64::from_bits(value)),
+                _ => {
+                    return Err(CborError::InvalidHeader {
+                        position,
+                        byte: self.buffer[position],
+                    })
+                }
+            },
+            _ => {
+                return Err(CborError::InvalidHeader {
+                    position,
+                    byte: self.buffer[position],
+                })
+            }
+        };
+        Ok(item)
+    }
+}
+
+/// Converts json value into the data item of the same structure.
+pub fn json_to_cbor(value: &Value) -> DataItem {
+    match *value {
+        Value::Null => DataItem::Null,
+        Value::Bool(value) => DataItem::Bool(value),
+        Value::Number(ref number) => if let Some(value) = number.as_u64() {
+            DataItem::Unsigned(value)
+        } else if let Some(value) = number.as_i64() {
+            DataItem::from_i64(value)
+        } else {
+            DataItem::Float(number.as_f64().expect("Number should be representable as f64"))
+        },
+        Value::String(ref string) => DataItem::Text(string.clone()),
+        Value::Array(ref items) => DataItem::Array(items.iter().map(json_to_cbor).collect()),
+        Value::Object(ref map) => DataItem::Map(
+            map.iter()
+                .map(|(key, value)| (DataItem::Text(key.clone()), json_to_cbor(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// Converts data item into json value.
+///
+/// Byte strings become hex strings and tags are dropped, integer map keys become strings,
+/// as in the `ExonumJson` representation of maps.
+pub fn cbor_to_json(item: DataItem) -> Result<Value, CborError> {
+    Ok(match item {
+        DataItem::Unsigned(value) => Value::from(value),
+        DataItem::Negative(_) => match item.as_i64() {
+            Some(value) => Value::from(value),
+            None => return Err(CborError::OutOfRange { type_name: "i64" }),
+        },
+        DataItem::Bytes(bytes) => Value::String(::hex::encode(bytes)),
+        DataItem::Text(text) => Value::String(text),
+        DataItem::Array(items) => Value::Array(items
+            .into_iter()
+            .map(cbor_to_json)
+            .collect::<Result<_, _>>()?),
+        DataItem::Map(entries) => {
+            let mut map = serde_json::Map::new();
+            for (key, value) in entries {
+                let key = match key {
+                    DataItem::Text(key) => key,
+                    DataItem::Unsigned(key) => key.to_string(),
+                    ref key @ DataItem::Negative(_) => match key.as_i64() {
+                        Some(key) => key.to_string(),
+                        None => return Err(CborError::OutOfRange { type_name: "i64" }),
+                    },
+                    ref key => return Err(CborError::unexpected("text string", key)),
+                };
+                map.insert(key, cbor_to_json(value)?);
+            }
+            Value::Object(map)
+        }
+        DataItem::Tag(_, item) => cbor_to_json(*item)?,
+        DataItem::Bool(value) => Value::Bool(value),
+        DataItem::Null => Value::Null,
+        ref item @ DataItem::Undefined => return Err(CborError::unexpected("json value", item)),
+        DataItem::Float(value) => match Number::from_f64(value) {
+            Some(number) => Value::Number(number),
+            None => return Err(CborError::OutOfRange { type_name: "json number" }),
+        },
+    })
+}
+
+/// Returns CBOR of the `ExonumJson` representation of the value.
+pub fn to_vec_json<T: ExonumJson + ?Sized>(value: &T) -> Result<Vec<u8>, Box<Error + Send + Sync>> {
+    Ok(encode(&json_to_cbor(&value.serialize_field()?)))
+}
+
+/// Reads value from CBOR of its `ExonumJson` representation.
+pub fn from_slice_json<T: ExonumJsonDeserialize>(bytes: &[u8]) -> Result<T, JsonError> {
+    T::deserialize(&cbor_to_json(decode(bytes)?)?)
+}
+
+/// Native CBOR representation of a value.
+pub trait CborValue: Sized {
+    /// Returns the value as data item.
+    fn to_cbor(&self) -> DataItem;
+
+    /// Reads the value from data item.
+    fn from_cbor(item: DataItem) -> Result<Self, CborError>;
+}
+
+/// Returns CBOR of the value.
+pub fn to_vec<T: CborValue>(value: &T) -> Vec<u8> {
+    encode(&value.to_cbor())
+}
+
+/// Reads value from CBOR.
+pub fn from_slice<T: CborValue>(bytes: &[u8]) -> Result<T, CborError> {
+    T::from_cbor(decode(bytes)?)
+}
+
+impl CborValue for () {
+    fn to_cbor(&self) -> DataItem {
+        DataItem::Null
+    }
+
+    fn from_cbor(item: DataItem) -> Result<Self, CborError> {
+        match item {
+            DataItem::Null => Ok(()),
+            item => Err(CborError::unexpected("null", &item)),
+        }
+    }
+}
+
+impl CborValue for bool {
+    fn to_cbor(&self) -> DataItem {
+        DataItem::Bool(*self)
+    }
+
+    fn from_cbor(item: DataItem) -> Result<Self, CborError> {
+        match item {
+            DataItem::Bool(value) => Ok(value),
+            item => Err(CborError::unexpected("bool", &item)),
+        }
+    }
+}
+
+macro_rules! impl_cbor_unsigned {
+    ($($typename:ty),*) => {$(
+        impl CborValue for $typename {
+            fn to_cbor(&self) -> DataItem {
+                DataItem::Unsigned(u64::from(*self))
+            }
+
+            fn from_cbor(item: DataItem) -> Result<Self, CborError> {
+                match item {
+                    DataItem::Unsigned(value) if value <= <$typename>::max_value() as u64 => {
+                        Ok(value as $typename)
+                    }
+                    DataItem::Unsigned(_) | DataItem::Negative(_) => Err(CborError::OutOfRange {
+                        type_name: stringify!($typename),
+                    }),
+                    item => Err(CborError::unexpected("integer", &item)),
+                }
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_cbor_signed {
+    ($($typename:ty),*) => {$(
+        impl CborValue for $typename {
+            fn to_cbor(&self) -> DataItem {
+                DataItem::from_i64(i64::from(*self))
+            }
+
+            fn from_cbor(item: DataItem) -> Result<Self, CborError> {
+                match item.as_i64() {
+                    Some(value) if value >= i64::from(<$typename>::min_value())
+                        && value <= i64::from(<$typename>::max_value()) => Ok(value as $typename),
+                    _ => match item {
+                        DataItem::Unsigned(_) | DataItem::Negative(_) => {
+                            Err(CborError::OutOfRange { type_name: stringify!($typename) })
+                        }
+                        item => Err(CborError::unexpected("integer", &item)),
+                    },
+                }
+            }
+        }
+    )*};
+}
+
+impl_cbor_unsigned!(u8, u16, u32, u64);
+impl_cbor_signed!(i8, i16, i32, i64);
+
+#[cfg(feature = "float_serialize")]
+impl CborValue for F32 {
+    fn to_cbor(&self) -> DataItem {
+        DataItem::Float(f64::from(self.get()))
+    }
+
+    fn from_cbor(item: DataItem) -> Result<Self, CborError> {
+        match item {
+            DataItem::Float(value) if f64::from(value as f32).to_bits() == value.to_bits() => {
+                F32::try_from(value as f32).ok_or(CborError::OutOfRange { type_name: "F32" })
+            }
+            DataItem::Float(_) => Err(CborError::OutOfRange { type_name: "F32" }),
+            item => Err(CborError::unexpected("float", &item)),
+        }
+    }
+}
+
+#[cfg(feature = "float_serialize")]
+impl CborValue for F64 {
+    fn to_cbor(&self) -> DataItem {
+        DataItem::Float(self.get())
+    }
+
+    fn from_cbor(item: DataItem) -> Result<Self, CborError> {
+        match item {
+            DataItem::Float(value) => {
+                F64::try_from(value).ok_or(CborError::OutOfRange { type_name: "F64" })
+            }
+            item => Err(CborError::unexpected("float", &item)),
+        }
+    }
+}
+
+impl CborValue for Round {
+    fn to_cbor(&self) -> DataItem {
+        self.0.to_cbor()
+    }
+
+    fn from_cbor(item: DataItem) -> Result<Self, CborError> {
+        u32::from_cbor(item).map(Round)
+    }
+}
+
+fn bytes_from_cbor(item: DataItem) -> Result<Vec<u8>, CborError> {
+    match item {
+        DataItem::Bytes(bytes) => Ok(bytes),
+        item => Err(CborError::unexpected("byte string", &item)),
+    }
+}
+
+macro_rules! impl_cbor_crypto {
+    ($($typename:ident),*) => {$(
+        impl CborValue for $typename {
+            fn to_cbor(&self) -> DataItem {
+                DataItem::Bytes(self.as_ref().to_vec())
+            }
+
+            fn from_cbor(item: DataItem) -> Result<Self, CborError> {
+                $typename::from_slice(&bytes_from_cbor(item)?).ok_or(CborError::OutOfRange {
+                    type_name: stringify!($typename),
+                })
+            }
+        }
+    )*};
+}
+
+impl_cbor_crypto!(Hash, PublicKey, Signature);
+
+/// Reads a structure from the byte string with its binary representation,
+/// used by `encoding_struct!`.
+#[doc(hidden)]
+pub fn struct_from_cbor<T>(item: DataItem, type_name: &'static str) -> Result<T, CborError>
+where
+    T: StorageValue + for<'a> Field<'a>,
+{
+    from_raw_checked(bytes_from_cbor(item)?).map_err(|e| CborError::InvalidStructure {
+        type_name,
+        error: e.to_string(),
+    })
+}
+
+impl CborValue for Vec<u8> {
+    fn to_cbor(&self) -> DataItem {
+        DataItem::Bytes(self.clone())
+    }
+
+    fn from_cbor(item: DataItem) -> Result<Self, CborError> {
+        bytes_from_cbor(item)
+    }
+}
+
+impl CborValue for String {
+    fn to_cbor(&self) -> DataItem {
+        DataItem::Text(self.clone())
+    }
+
+    fn from_cbor(item: DataItem) -> Result<Self, CborError> {
+        match item {
+            DataItem::Text(text) => Ok(text),
+            item => Err(CborError::unexpected("text string", &item)),
+        }
+    }
+}
+
+/// Time with whole seconds is written as the epoch-based date/time, otherwise as
+/// the standard date/time string to keep nanoseconds.
+impl CborValue for DateTime<Utc> {
+    fn to_cbor(&self) -> DataItem {
+        if self.timestamp_subsec_nanos() == 0 {
+            DataItem::Tag(
+                TAG_EPOCH_DATE_TIME,
+                Box::new(DataItem::from_i64(self.timestamp())),
+            )
+        } else {
+            DataItem::Tag(
+                TAG_DATE_TIME_STRING,
+                Box::new(DataItem::Text(self.to_rfc3339_opts(SecondsFormat::AutoSi, true))),
+            )
+        }
+    }
+
+    fn from_cbor(item: DataItem) -> Result<Self, CborError> {
+        let out_of_range = CborError::OutOfRange {
+            type_name: "DateTime",
+        };
+        match item {
+            DataItem::Tag(TAG_DATE_TIME_STRING, item) => match *item {
+                DataItem::Text(text) => DateTime::parse_from_rfc3339(&text)
+                    .map(|time| time.with_timezone(&Utc))
+                    .map_err(|_| out_of_range),
+                item => Err(CborError::unexpected("text string", &item)),
+            },
+            DataItem::Tag(TAG_EPOCH_DATE_TIME, item) => match *item {
+                DataItem::Float(value) => {
+                    let secs = value.floor();
+                    let nanos = ((value - secs) * 1e9).round();
+                    if !value.is_finite() || secs.abs() >= 1e17 || nanos >= 1e9 {
+                        return Err(out_of_range);
+                    }
+                    Utc.timestamp_opt(secs as i64, nanos as u32)
+                        .single()
+                        .ok_or(out_of_range)
+                }
+                item => match item.as_i64() {
+                    Some(secs) => Utc.timestamp_opt(secs, 0).single().ok_or(out_of_range),
+                    None => Err(CborError::unexpected("integer", &item)),
+                },
+            },
+            item => Err(CborError::unexpected("date/time tag", &item)),
+        }
+    }
+}
+
+/// Written as a map with `secs` and `nanos`, like in the `ExonumJson` representation.
+impl CborValue for Duration {
+    fn to_cbor(&self) -> DataItem {
+        let secs = self.num_seconds();
+        let nanos = (*self - Duration::seconds(secs))
+            .num_nanoseconds()
+            .expect("Nanoseconds should fit into i64");
+        DataItem::Map(vec![
+            (DataItem::Text("secs".to_owned()), DataItem::from_i64(secs)),
+            (DataItem::Text("nanos".to_owned()), DataItem::from_i64(nanos)),
+        ])
+    }
+
+    fn from_cbor(item: DataItem) -> Result<Self, CborError> {
+        let entries = match item {
+            DataItem::Map(entries) => entries,
+            item => return Err(CborError::unexpected("map", &item)),
+        };
+        let field = |name: &str| {
+            entries
+                .iter()
+                .find(|&&(ref key, _)| *key == DataItem::Text(name.to_owned()))
+                .and_then(|&(_, ref value)| value.as_i64())
+                .ok_or(CborError::UnexpectedItem {
+                    expected: "integer",
+                    found: "map",
+                })
+        };
+        let (secs, nanos) = (field("secs")?, field("nanos")?);
+        let out_of_range = CborError::OutOfRange {
+            type_name: "Duration",
+        };
+        // Range checks instead of `abs`, which overflows for `i64::min_value()`.
+        let max_secs = i64::max_value() / 1000;
+        if secs < -max_secs || secs > max_secs || nanos <= -1_000_000_000
+            || nanos >= 1_000_000_000
+        {
+            return Err(out_of_range);
+        }
+        Duration::seconds(secs)
+            .checked_add(&Duration::nanoseconds(nanos))
+            .ok_or(out_of_range)
+    }
+}
+
+impl CborValue for Uuid {
+    fn to_cbor(&self) -> DataItem {
+        DataItem::Tag(TAG_UUID, Box::new(DataItem::Bytes(self.as_bytes().to_vec())))
+    }
+
+    fn from_cbor(item: DataItem) -> Result<Self, CborError> {
+        match item {
+            DataItem::Tag(TAG_UUID, item) => Uuid::from_bytes(&bytes_from_cbor(*item)?)
+                .map_err(|_| CborError::OutOfRange { type_name: "Uuid" }),
+            item => Err(CborError::unexpected("UUID tag", &item)),
+        }
+    }
+}
+
+/// Written as a text string, like in the `ExonumJson` representation.
+impl CborValue for Decimal {
+    fn to_cbor(&self) -> DataItem {
+        DataItem::Text(self.to_string())
+    }
+
+    fn from_cbor(item: DataItem) -> Result<Self, CborError> {
+        Decimal::from_str(&String::from_cbor(item)?).map_err(|_| CborError::OutOfRange {
+            type_name: "Decimal",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+    use hex;
+
+    use crypto::{chain_id, gen_keypair, hash, DEFAULT_NETWORK};
+    use encoding::serialize::Format;
+    use messages::{Message, Precommit, SignedMessage, Status};
+    use storage::StorageValue;
+    use types::{Height, ValidatorId};
+
+    use super::*;
+
+    fn encoded(item: &DataItem) -> String {
+        hex::encode(encode(item))
+    }
+
+    fn decoded(bytes: &str) -> DataItem {
+        decode(&hex::decode(bytes).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_rfc_vectors() {
+        let vectors = vec![
+            ("00", DataItem::Unsigned(0)),
+            ("17", DataItem::Unsigned(23)),
+            ("1818", DataItem::Unsigned(24)),
+            ("1903e8", DataItem::Unsigned(1000)),
+            ("1a000f4240", DataItem::Unsigned(1_000_000)),
+            ("1b000000e8d4a51000", DataItem::Unsigned(1_000_000_000_000)),
+            ("1bffffffffffffffff", DataItem::Unsigned(u64::max_value())),
+            ("3bffffffffffffffff", DataItem::Negative(u64::max_value())),
+            ("20", DataItem::Negative(0)),
+            ("3863", DataItem::Negative(99)),
+            ("f90000", DataItem::Float(0.0)),
+            ("f98000", DataItem::Float(-0.0)),
+            ("f93c00", DataItem::Float(1.0)),
+            ("fb3ff199999999999a", DataItem::Float(1.1)),
+            ("f93e00", DataItem::Float(1.5)),
+            ("f97bff", DataItem::Float(65504.0)),
+            ("fa47c35000", DataItem::Float(100_000.0)),
+            ("fa7f7fffff", DataItem::Float(f64::from(::std::f32::MAX))),
+            ("fb7e37e43c8800759c", DataItem::Float(1.0e300)),
+            ("f90001", DataItem::Float(5.960464477539063e-8)),
+            ("f90400", DataItem::Float(0.00006103515625)),
+            ("f9c400", DataItem::Float(-4.0)),
+            ("f97c00", DataItem::Float(::std::f64::INFINITY)),
+            ("f9fc00", DataItem::Float(::std::f64::NEG_INFINITY)),
+            ("f4", DataItem::Bool(false)),
+            ("f5", DataItem::Bool(true)),
+            ("f6", DataItem::Null),
+            ("f7", DataItem::Undefined),
+            (
+                "c074323031332d30332d32315432303a30343a30305a",
+                DataItem::Tag(0, Box::new(DataItem::Text("2013-03-21T20:04:00Z".to_owned()))),
+            ),
+            ("c11a514b67b0", DataItem::Tag(1, Box::new(DataItem::Unsigned(1_363_896_240)))),
+            ("40", DataItem::Bytes(Vec::new())),
+            ("4401020304", DataItem::Bytes(vec![1, 2, 3, 4])),
+            ("60", DataItem::Text(String::new())),
+            ("62c3bc", DataItem::Text("\u{fc}".to_owned())),
+            ("80", DataItem::Array(Vec::new())),
+            (
+                "8301820203820405",
+                DataItem::Array(vec![
+                    DataItem::Unsigned(1),
+                    DataItem::Array(vec![DataItem::Unsigned(2), DataItem::Unsigned(3)]),
+                    DataItem::Array(vec![DataItem::Unsigned(4), DataItem::Unsigned(5)]),
+                ]),
+            ),
+            (
+                "a201020304",
+                DataItem::Map(vec![
+                    (DataItem::Unsigned(1), DataItem::Unsigned(2)),
+                    (DataItem::Unsigned(3), DataItem::Unsigned(4)),
+                ]),
+            ),
+        ];
+        for (bytes, item) in vectors {
+            assert_eq!(encoded(&item), bytes);
+            assert_eq!(decoded(bytes), item);
+        }
+        assert!(match decoded("f97e00") {
+            DataItem::Float(value) => value.is_nan(),
+            _ => false,
+        });
+        assert_eq!(encoded(&DataItem::Float(::std::f64::NAN)), "f97e00");
+    }
+
+    #[test]
+    fn test_indefinite_lengths() {
+        assert_eq!(decoded("5f42010243030405ff"), DataItem::Bytes(vec![1, 2, 3, 4, 5]));
+        assert_eq!(
+            decoded("7f657374726561646d696e67ff"),
+            DataItem::Text("streaming".to_owned())
+        );
+        assert_eq!(
+            decoded("9f018202039f0405ffff"),
+            DataItem::Array(vec![
+                DataItem::Unsigned(1),
+                DataItem::Array(vec![DataItem::Unsigned(2), DataItem::Unsigned(3)]),
+                DataItem::Array(vec![DataItem::Unsigned(4), DataItem::Unsigned(5)]),
+            ])
+        );
+        assert_eq!(
+            decoded("bf61610161629f0203ffff"),
+            DataItem::Map(vec![
+                (DataItem::Text("a".to_owned()), DataItem::Unsigned(1)),
+                (
+                    DataItem::Text("b".to_owned()),
+                    DataItem::Array(vec![DataItem::Unsigned(2), DataItem::Unsigned(3)]),
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_deterministic_map_keys() {
+        let map = DataItem::Map(vec![
+            (DataItem::Text("aa".to_owned()), DataItem::Unsigned(1)),
+            (DataItem::Text("b".to_owned()), DataItem::Unsigned(2)),
+            (DataItem::Unsigned(100), DataItem::Unsigned(3)),
+            (DataItem::Negative(0), DataItem::Unsigned(4)),
+        ]);
+        assert_eq!(encoded(&map), "a41864032004616202626161 01".replace(' ', ""));
+    }
+
+    #[test]
+    fn test_malformed_items() {
+        let errors = vec![
+            ("", CborError::UnexpectedEnd),
+            ("18", CborError::UnexpectedEnd),
+            ("4201", CborError::UnexpectedEnd),
+            ("1c", CborError::InvalidHeader { position: 0, byte: 0x1c }),
+            ("1f", CborError::InvalidHeader { position: 0, byte: 0x1f }),
+            ("ff", CborError::InvalidHeader { position: 0, byte: 0xff }),
+            ("5f01ff", CborError::InvalidHeader { position: 1, byte: 0x01 }),
+            ("7f4100ff", CborError::InvalidHeader { position: 1, byte: 0x41 }),
+            ("f818", CborError::InvalidHeader { position: 0, byte: 0xf8 }),
+            ("62c328", CborError::InvalidUtf8 { position: 0 }),
+            ("0000", CborError::TrailingBytes { position: 1 }),
+            ("9f01", CborError::UnexpectedEnd),
+        ];
+        for (bytes, error) in errors {
+            assert_eq!(decode(&hex::decode(bytes).unwrap()), Err(error), "{}", bytes);
+        }
+        let deep = vec![0x81; MAX_DEPTH + 1];
+        assert_eq!(decode(&deep), Err(CborError::TooDeep));
+    }
+
+    #[test]
+    fn test_storage_values() {
+        let (public_key, _) = gen_keypair();
+        let hash = hash(&[1, 2, 3]);
+        assert_eq!(to_vec(&hash)[..2], [0x58, 0x20]);
+        assert_eq!(from_slice::<Hash>(&to_vec(&hash)), Ok(hash));
+        assert_eq!(from_slice::<PublicKey>(&to_vec(&public_key)), Ok(public_key));
+        assert!(from_slice::<Hash>(&to_vec(&vec![1u8, 2])).is_err());
+
+        let out_of_range = |type_name| CborError::OutOfRange { type_name };
+        assert_eq!(from_slice::<u8>(&to_vec(&300u16)).unwrap_err(), out_of_range("u8"));
+        assert_eq!(from_slice::<i64>(&to_vec(&i64::min_value())), Ok(i64::min_value()));
+        assert_eq!(from_slice::<i8>(&to_vec(&-129i16)).unwrap_err(), out_of_range("i8"));
+        assert_eq!(from_slice::<u64>(&to_vec(&-1i8)).unwrap_err(), out_of_range("u64"));
+        assert_eq!(from_slice::<String>(&to_vec(&"\u{fc}".to_owned())), Ok("\u{fc}".to_owned()));
+        assert_eq!(from_slice::<Round>(&to_vec(&Round(7))), Ok(Round(7)));
+        assert_eq!(from_slice::<()>(&to_vec(&())), Ok(()));
+
+        let duration = Duration::seconds(-5) + Duration::nanoseconds(-7);
+        assert_eq!(from_slice::<Duration>(&to_vec(&duration)), Ok(duration));
+        let uuid = Uuid::from_bytes(&[7; 16]).unwrap();
+        assert_eq!(to_vec(&uuid)[..3], [0xd8, 0x25, 0x50]);
+        assert_eq!(from_slice::<Uuid>(&to_vec(&uuid)), Ok(uuid));
+        let decimal = Decimal::from_str("-12.0500").unwrap();
+        assert_eq!(from_slice::<Decimal>(&to_vec(&decimal)), Ok(decimal));
+    }
+
+    #[test]
+    fn test_encoding_struct() {
+        let status = Status::new(Height(3), &hash(&[3]));
+        let bytes = to_vec(&status);
+        assert_eq!(decode(&bytes), Ok(DataItem::Bytes(status.clone().into_bytes())));
+        assert_eq!(from_slice::<Status>(&bytes), Ok(status.clone()));
+
+        let mut raw = status.into_bytes();
+        raw.pop();
+        match from_slice::<Status>(&to_vec(&raw)) {
+            Err(CborError::InvalidStructure { type_name, .. }) => assert_eq!(type_name, "Status"),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_duration_out_of_range() {
+        let duration = |secs, nanos| {
+            encode(&DataItem::Map(vec![
+                (DataItem::Text("secs".to_owned()), secs),
+                (DataItem::Text("nanos".to_owned()), nanos),
+            ]))
+        };
+        let out_of_range = Err(CborError::OutOfRange {
+            type_name: "Duration",
+        });
+        let zero = DataItem::Unsigned(0);
+        let min = DataItem::Negative(i64::max_value() as u64);
+        assert_eq!(from_slice::<Duration>(&duration(min.clone(), zero.clone())), out_of_range);
+        assert_eq!(from_slice::<Duration>(&duration(zero.clone(), min)), out_of_range);
+
+        let max_secs = i64::max_value() / 1000;
+        let max = Duration::seconds(max_secs);
+        let bytes = duration(DataItem::from_i64(max_secs), zero);
+        assert_eq!(from_slice::<Duration>(&bytes), Ok(max));
+        let bytes = duration(DataItem::from_i64(max_secs), DataItem::from_i64(999_999_999));
+        assert_eq!(from_slice::<Duration>(&bytes), out_of_range);
+    }
+
+    #[test]
+    fn test_tagged_time() {
+        let time = Utc.timestamp(1_363_896_240, 0);
+        assert_eq!(hex::encode(to_vec(&time)), "c11a514b67b0");
+        assert_eq!(from_slice(&to_vec(&time)), Ok(time));
+
+        let time = Utc.timestamp(1_363_896_240, 500_000_001);
+        assert_eq!(
+            time.to_cbor(),
+            DataItem::Tag(0, Box::new(DataItem::Text("2013-03-21T20:04:00.500000001Z".to_owned())))
+        );
+        assert_eq!(from_slice(&to_vec(&time)), Ok(time));
+
+        let float = encode(&DataItem::Tag(1, Box::new(DataItem::Float(1_363_896_240.5))));
+        assert_eq!(from_slice(&float), Ok(Utc.timestamp(1_363_896_240, 500_000_000)));
+        let before_epoch = encode(&DataItem::Tag(1, Box::new(DataItem::Negative(0))));
+        assert_eq!(from_slice(&before_epoch), Ok(Utc.timestamp(-1, 0)));
+        assert!(from_slice::<DateTime<Utc>>(&to_vec(&1u8)).is_err());
+    }
+
+    #[test]
+    fn test_exonum_json_in_cbor() {
+        let precommit = Precommit::new(
+            ValidatorId(1),
+            Height(2),
+            Round(3),
+            &hash(&[1]),
+            &hash(&[2]),
+            Utc.timestamp(1_500_000_000, 1),
+        );
+        let bytes = Format::Cbor.to_vec(&precommit).unwrap();
+        assert_eq!(bytes, to_vec_json(&precommit).unwrap());
+        assert_eq!(Format::Cbor.from_slice::<Precommit>(&bytes).unwrap(), precommit);
+        assert!(bytes.len() < Format::Json.to_vec(&precommit).unwrap().len());
+
+        let (public_key, secret_key) = gen_keypair();
+        let message = Message::new(precommit, public_key, &chain_id(DEFAULT_NETWORK), &secret_key)
+            .unwrap();
+        let signed: SignedMessage = message.into();
+        let bytes = Format::Cbor.to_vec(&signed).unwrap();
+        assert_eq!(Format::Cbor.from_slice::<SignedMessage>(&bytes).unwrap(), signed);
+
+        let error = Format::Cbor.from_slice::<Precommit>(&[0xa1, 0x01]).unwrap_err();
+        assert!(error.to_string().contains("Invalid CBOR"));
+    }
+
+    #[test]
+    fn test_cbor_to_json() {
+        let item = DataItem::Map(vec![(DataItem::Bytes(vec![1]), DataItem::Null)]);
+        let error = cbor_to_json(item).unwrap_err();
+        assert_eq!(
+            error,
+            CborError::UnexpectedItem {
+                expected: "text string",
+                found: "byte string"
+            }
+        );
+        let item = decoded("a301616120f9c4006161c24101");
+        assert_eq!(
+            cbor_to_json(item).unwrap().to_string(),
+            r#"{"-1":-4.0,"1":"a","a":"01"}"#
+        );
+    }
+}
@@ -28,22 +28,30 @@ use chrono::{DateTime, Duration, TimeZone, Utc};
 use uuid::Uuid;
 use rust_decimal::Decimal;
 
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, Hash as StdHash};
 use std::net::SocketAddr;
 use std::error::Error;
+use std::fmt;
+use std::str::{self, FromStr};
 
-use crypto::{Hash, PublicKey, Signature};
+use crypto::{AddressError, Hash, PublicKey, PublicKeyAddress, Signature};
 use types::{Height, Round, ValidatorId};
 use encoding::{Field, Offset};
-use messages::SignedMessage;
-use old_messages::RawMessage;
-use super::WriteBufferWrapper;
-// TODO: should we implement serialize for: `SecretKey`, `Seed` (ECR-156)?
+use messages::{BlockRequest, BlockResponse, Connect, ConsensusMessage, Message, PeersRequest,
+               Precommit, Prevote, PrevotesRequest, Propose, ProposeRequest, Protocol,
+               ProtocolMessage, RawTransaction, RequestMessage, SignedMessage, Status,
+               TransactionsRequest, TransactionsResponse, WithoutEncodingStatus};
+use old_messages::{MessageWriter, RawMessage, HEADER_LENGTH};
+use super::{FromHexError, WriteBufferWrapper};
+use super::text::{BinaryEncoding, DecodeError, JsonConfig};
+// `SecretKey` and `Seed` are not serialized, unless wrapped into `crypto::ExposeSecret`.
 
 macro_rules! impl_default_deserialize_owned {
     (@impl $name:ty) => {
         impl $crate::encoding::serialize::json::ExonumJsonDeserialize for $name {
             fn deserialize(value: &$crate::encoding::serialize::json::reexport::Value)
-                -> Result<Self, Box<::std::error::Error>> {
+                -> Result<Self, $crate::encoding::serialize::json::JsonError> {
                 use $crate::encoding::serialize::json::reexport::from_value;
                 Ok(from_value(value.clone())?)
             }
@@ -70,23 +78,406 @@ pub trait ExonumJson {
         Self: Sized;
     /// serialize field as `json::Value`
     fn serialize_field(&self) -> Result<Value, Box<Error + Send + Sync>>;
+
+    /// write deserialized field in buffer on place, binary data is decoded
+    /// according to the configuration.
+    fn deserialize_field_with<B: WriteBufferWrapper>(
+        value: &Value,
+        buffer: &mut B,
+        from: Offset,
+        to: Offset,
+        config: &JsonConfig,
+    ) -> Result<(), Box<Error>>
+    where
+        Self: Sized,
+    {
+        let _ = config;
+        Self::deserialize_field(value, buffer, from, to)
+    }
+
+    /// serialize field as `json::Value`, binary data is encoded
+    /// according to the configuration.
+    fn serialize_field_with(&self, config: &JsonConfig) -> Result<Value, Box<Error + Send + Sync>> {
+        let _ = config;
+        self.serialize_field()
+    }
 }
 
 /// `ExonumJsonDeserialize` is trait for objects that could be constructed from exonum json.
 pub trait ExonumJsonDeserialize {
     /// deserialize `json` value.
-    fn deserialize(value: &Value) -> Result<Self, Box<Error>>
+    fn deserialize(value: &Value) -> Result<Self, JsonError>
     where
         Self: Sized;
+
+    /// deserialize `json` value, binary data is decoded according to the configuration.
+    fn deserialize_with(value: &Value, config: &JsonConfig) -> Result<Self, JsonError>
+    where
+        Self: Sized,
+    {
+        let _ = config;
+        Self::deserialize(value)
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// `ExonumJsonSerialize` is trait for objects that could be converted to exonum json.
+///
+/// It is implemented for every `ExonumJson` type and also for options, tuples and maps,
+/// which have no binary layout and are read back through `ExonumJsonDeserialize`.
+pub trait ExonumJsonSerialize {
+    /// serialize value as `json::Value`.
+    fn serialize_json(&self) -> Result<Value, Box<Error + Send + Sync>> {
+        self.serialize_json_with(&JsonConfig::default())
+    }
+
+    /// serialize value as `json::Value`, binary data is encoded according to the configuration.
+    fn serialize_json_with(&self, config: &JsonConfig) -> Result<Value, Box<Error + Send + Sync>>;
+}
+
+impl<T: ExonumJson + ?Sized> ExonumJsonSerialize for T {
+    fn serialize_json_with(&self, config: &JsonConfig) -> Result<Value, Box<Error + Send + Sync>> {
+        self.serialize_field_with(config)
+    }
+}
+
+/// Deserializes exonum json from bytes, which should be a valid UTF-8 string.
+pub fn from_slice<T: ExonumJsonDeserialize>(bytes: &[u8]) -> Result<T, JsonError> {
+    let string = str::from_utf8(bytes).map_err(|e| {
+        JsonError::new(JsonErrorKind::InvalidUtf8 {
+            valid_up_to: e.valid_up_to(),
+        })
+    })?;
+    let value: Value = serde_json::from_str(string)?;
+    T::deserialize(&value)
+}
+
+/// Kind of the exonum json deserialization error.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonErrorKind {
+    /// Object has no field with the required name.
+    MissingField,
+    /// Value has unexpected json type.
+    WrongType {
+        /// Expected json type.
+        expected: &'static str,
+    },
+    /// String is not a valid hex representation of the value.
+    InvalidHex(FromHexError),
+    /// String is not a valid base64 or base58 representation of the value.
+    InvalidEncoding {
+        /// Expected encoding.
+        encoding: BinaryEncoding,
+        /// Decoding error.
+        error: DecodeError,
+    },
+    /// String is not a valid address of the public key in the configured network.
+    InvalidAddress(AddressError),
+    /// Integer does not fit into the field type.
+    OutOfRange {
+        /// Integer as it appears in json.
+        value: String,
+        /// Name of the field type.
+        type_name: &'static str,
+    },
+    /// Input is not a valid UTF-8 string.
+    InvalidUtf8 {
+        /// Length of the valid prefix of the input.
+        valid_up_to: usize,
+    },
+    /// Signature of the message does not match its content in the configured network.
+    InvalidSignature,
+    /// Value has correct type but is invalid for other reasons.
+    InvalidValue(String),
+}
+
+/// Error of exonum json deserialization.
+///
+/// Error contains JSON pointer (RFC 6901) to the value which caused it,
+/// the pointer is empty if the error relates to the whole document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonError {
+    pointer: String,
+    kind: JsonErrorKind,
+}
+
+impl JsonError {
+    /// Creates error of the given kind related to the whole document.
+    pub fn new(kind: JsonErrorKind) -> Self {
+        JsonError {
+            pointer: String::new(),
+            kind,
+        }
+    }
+
+    /// Creates error for a missing field of the object.
+    pub fn missing_field(name: &str) -> Self {
+        Self::new(JsonErrorKind::MissingField).at_field(name)
+    }
+
+    /// Creates error for a value with unexpected json type.
+    pub fn wrong_type(expected: &'static str) -> Self {
+        Self::new(JsonErrorKind::WrongType { expected })
+    }
+
+    /// Creates error for an integer that doesn't fit into the field type.
+    pub fn out_of_range<V: ToString>(value: V, type_name: &'static str) -> Self {
+        Self::new(JsonErrorKind::OutOfRange {
+            value: value.to_string(),
+            type_name,
+        })
+    }
+
+    /// Creates error for an otherwise invalid value.
+    pub fn invalid_value<S: Into<String>>(message: S) -> Self {
+        Self::new(JsonErrorKind::InvalidValue(message.into()))
+    }
+
+    /// Returns JSON pointer to the value which caused the error.
+    pub fn pointer(&self) -> &str {
+        &self.pointer
+    }
+
+    /// Returns kind of the error.
+    pub fn kind(&self) -> &JsonErrorKind {
+        &self.kind
+    }
+
+    /// Moves the error into the field of the enclosing object.
+    pub fn at_field(mut self, name: &str) -> Self {
+        let name = name.replace('~', "~0").replace('/', "~1");
+        self.pointer = format!("/{}{}", name, self.pointer);
+        self
+    }
+
+    /// Moves the error into the item of the enclosing array.
+    pub fn at_index(mut self, index: usize) -> Self {
+        self.pointer = format!("/{}{}", index, self.pointer);
+        self
+    }
+}
+
+impl fmt::Display for JsonErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            JsonErrorKind::MissingField => write!(f, "Missing field"),
+            JsonErrorKind::WrongType { expected } => {
+                write!(f, "Invalid type, expected {}", expected)
+            }
+            JsonErrorKind::InvalidHex(ref e) => write!(f, "Invalid hex: {}", e),
+            JsonErrorKind::InvalidEncoding {
+                encoding,
+                ref error,
+            } => write!(f, "Invalid {}: {}", encoding, error),
+            JsonErrorKind::InvalidAddress(ref e) => write!(f, "Invalid address: {}", e),
+            JsonErrorKind::OutOfRange {
+                ref value,
+                type_name,
+            } => write!(f, "Integer {} is out of range for {}", value, type_name),
+            JsonErrorKind::InvalidUtf8 { valid_up_to } => {
+                write!(f, "Invalid UTF-8 after {} bytes", valid_up_to)
+            }
+            JsonErrorKind::InvalidSignature => {
+                write!(f, "Message signature does not match its content")
+            }
+            JsonErrorKind::InvalidValue(ref message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.pointer.is_empty() {
+            write!(f, "{}", self.kind)
+        } else {
+            write!(f, "{} at `{}`", self.kind, self.pointer)
+        }
+    }
+}
+
+impl Error for JsonError {
+    fn description(&self) -> &str {
+        match self.kind {
+            JsonErrorKind::MissingField => "Missing field",
+            JsonErrorKind::WrongType { .. } => "Invalid type",
+            JsonErrorKind::InvalidHex(_) => "Invalid hex",
+            JsonErrorKind::InvalidEncoding { .. } => "Invalid binary encoding",
+            JsonErrorKind::InvalidAddress(_) => "Invalid address",
+            JsonErrorKind::OutOfRange { .. } => "Integer is out of range",
+            JsonErrorKind::InvalidUtf8 { .. } => "Invalid UTF-8",
+            JsonErrorKind::InvalidSignature => "Invalid signature",
+            JsonErrorKind::InvalidValue(_) => "Invalid value",
+        }
+    }
+}
+
+// Errors of `ExonumJson::deserialize_field` keep their kind and pointer
+// if they were produced by exonum json.
+impl From<Box<Error>> for JsonError {
+    fn from(error: Box<Error>) -> Self {
+        match error.downcast::<JsonError>() {
+            Ok(error) => *error,
+            Err(error) => JsonError::invalid_value(error.to_string()),
+        }
+    }
+}
+
+impl From<serde_json::Error> for JsonError {
+    fn from(error: serde_json::Error) -> Self {
+        JsonError::invalid_value(error.to_string())
+    }
+}
+
+impl From<FromHexError> for JsonError {
+    fn from(error: FromHexError) -> Self {
+        JsonError::new(JsonErrorKind::InvalidHex(error))
+    }
+}
+
+impl From<::encoding::Error> for JsonError {
+    fn from(error: ::encoding::Error) -> Self {
+        JsonError::invalid_value(error.to_string())
+    }
+}
+
+fn object_from_json(value: &Value) -> Result<&serde_json::Map<String, Value>, JsonError> {
+    value.as_object().ok_or_else(|| JsonError::wrong_type("object"))
+}
+
+fn field_from_json<'a>(
+    object: &'a serde_json::Map<String, Value>,
+    name: &str,
+) -> Result<&'a Value, JsonError> {
+    object.get(name).ok_or_else(|| JsonError::missing_field(name))
+}
+
+// Parses the field of the object, errors of parsing point into the field.
+fn parse_field<T, F>(
+    object: &serde_json::Map<String, Value>,
+    name: &str,
+    parse: F,
+) -> Result<T, JsonError>
+where
+    F: FnOnce(&Value) -> Result<T, JsonError>,
+{
+    let value = field_from_json(object, name)?;
+    parse(value).map_err(|e| e.at_field(name))
+}
+
+fn str_from_json(value: &Value) -> Result<&str, JsonError> {
+    value.as_str().ok_or_else(|| JsonError::wrong_type("string"))
+}
+
+fn hex_from_json<T: FromHex<Error = FromHexError>>(value: &Value) -> Result<T, JsonError> {
+    Ok(T::from_hex(str_from_json(value)?)?)
+}
+
+fn bytes_from_json(value: &Value, encoding: BinaryEncoding) -> Result<Vec<u8>, JsonError> {
+    encoding
+        .decode(str_from_json(value)?)
+        .map_err(|error| match error {
+            DecodeError::Hex(e) => JsonError::from(e),
+            error => JsonError::new(JsonErrorKind::InvalidEncoding { encoding, error }),
+        })
+}
+
+macro_rules! impl_crypto_from_json {
+    ($($typename:ident { $($overrides:item)* }),*) => {$(
+        impl CryptoFromJson for $typename {
+            $($overrides)*
+
+            fn crypto_from_json(value: &Value, encoding: BinaryEncoding) -> Result<Self, JsonError> {
+                // Hex keeps the errors of `FromHex`.
+                if encoding == BinaryEncoding::Hex {
+                    return hex_from_json(value);
+                }
+                let bytes = bytes_from_json(value, encoding)?;
+                $typename::from_slice(&bytes).ok_or_else(|| {
+                    JsonError::invalid_value(format!(
+                        "Invalid length of {}: {} bytes",
+                        stringify!($typename),
+                        bytes.len()
+                    ))
+                })
+            }
+        }
+
+        // Owned values are written in the same way as the fields, rather than as serde arrays.
+        impl ExonumJsonDeserialize for $typename {
+            fn deserialize(value: &Value) -> Result<Self, JsonError> {
+                Self::deserialize_with(value, &JsonConfig::default())
+            }
+
+            fn deserialize_with(value: &Value, config: &JsonConfig) -> Result<Self, JsonError> {
+                Self::crypto_from_json_with(value, config)
+            }
+        }
+    )*};
+}
+
+// Hashes, keys and signatures with the configurable encoding.
+trait CryptoFromJson: Sized + AsRef<[u8]> {
+    fn crypto_from_json(value: &Value, encoding: BinaryEncoding) -> Result<Self, JsonError>;
+
+    fn crypto_from_json_with(value: &Value, config: &JsonConfig) -> Result<Self, JsonError> {
+        Self::crypto_from_json(value, config.crypto())
+    }
+
+    fn crypto_to_json_with(&self, config: &JsonConfig) -> Result<Value, AddressError> {
+        Ok(Value::String(config.crypto().encode(self.as_ref())))
+    }
+}
+
+impl_crypto_from_json!(Hash {}, Signature {}, PublicKey {
+    // Keys are written as addresses if the configuration selects a network.
+    fn crypto_from_json_with(value: &Value, config: &JsonConfig) -> Result<Self, JsonError> {
+        match config.addresses() {
+            Some(network) => PublicKeyAddress::parse(network, str_from_json(value)?)
+                .map(|address| address.value())
+                .map_err(|e| JsonError::new(JsonErrorKind::InvalidAddress(e))),
+            None => Self::crypto_from_json(value, config.crypto()),
+        }
+    }
+
+    fn crypto_to_json_with(&self, config: &JsonConfig) -> Result<Value, AddressError> {
+        match config.addresses() {
+            Some(network) => {
+                let address = PublicKeyAddress::new(network, *self)?;
+                Ok(Value::String(address.to_string()))
+            }
+            None => Ok(Value::String(config.crypto().encode(self.as_ref()))),
+        }
+    }
+});
+
+// Integers up to 32 bits are represented as json numbers.
+fn int_from_json(value: &Value, min: i64, max: i64, type_name: &'static str) -> Result<i64, JsonError> {
+    match value.as_i64() {
+        Some(number) if number >= min && number <= max => Ok(number),
+        _ if value.is_i64() || value.is_u64() => Err(JsonError::out_of_range(value, type_name)),
+        _ => Err(JsonError::wrong_type("integer")),
+    }
+}
+
+// 64-bit integers are represented as strings.
+fn bigint_from_json<T: FromStr>(value: &Value, type_name: &'static str) -> Result<T, JsonError> {
+    let string = str_from_json(value)?;
+    string.parse().map_err(|_| {
+        let digits = string.trim_start_matches('-');
+        if !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()) {
+            JsonError::out_of_range(string, type_name)
+        } else {
+            JsonError::invalid_value(format!("`{}` is not an integer", string))
+        }
+    })
+}
+
+#[derive(Serialize, Debug)]
 struct TimestampHelper {
     secs: String,
     nanos: u32,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Debug)]
 struct DurationHelper {
     secs: String,
     nanos: i32,
@@ -102,7 +493,12 @@ macro_rules! impl_deserialize_int {
                                                          to: Offset)
                 -> Result<(), Box<Error>>
             {
-                let number = value.as_i64().ok_or("Can't cast json as integer")?;
+                let number = int_from_json(
+                    value,
+                    i64::from(<$typename>::min_value()),
+                    i64::from(<$typename>::max_value()),
+                    stringify!($typename),
+                )?;
                 buffer.write(from, to, number as $typename);
                 Ok(())
             }
@@ -124,8 +520,7 @@ macro_rules! impl_deserialize_bigint {
                                                         to: Offset)
             -> Result<(), Box<Error>>
             {
-                let string = value.as_str().ok_or("Can't cast json as string")?;
-                let val: $typename =  string.parse()?;
+                let val: $typename = bigint_from_json(value, stringify!($typename))?;
                 buffer.write(from, to, val);
                 Ok(())
             }
@@ -147,15 +542,29 @@ macro_rules! impl_deserialize_hex_segment {
                                                         to: Offset)
                 -> Result<(), Box<Error>>
             {
-                let string = value.as_str().ok_or("Can't cast json as string")?;
-                let val = <$typename as FromHex>:: from_hex(string)?;
+                Self::deserialize_field_with(value, buffer, from, to, &JsonConfig::default())
+            }
+
+            fn serialize_field(&self) -> Result<Value, Box<Error + Send + Sync>> {
+                self.serialize_field_with(&JsonConfig::default())
+            }
+
+            fn deserialize_field_with<B: WriteBufferWrapper>(value: &Value,
+                                                             buffer: &mut B,
+                                                             from: Offset,
+                                                             to: Offset,
+                                                             config: &JsonConfig)
+                -> Result<(), Box<Error>>
+            {
+                let val = <$typename as CryptoFromJson>::crypto_from_json_with(value, config)?;
                 buffer.write(from, to, &val);
                 Ok(())
             }
 
-            fn serialize_field(&self) -> Result<Value, Box<Error + Send + Sync>> {
-                let hex_str = $crate::encoding::serialize::encode_hex(&self[..]);
-                Ok(Value::String(hex_str))
+            fn serialize_field_with(&self, config: &JsonConfig)
+                -> Result<Value, Box<Error + Send + Sync>>
+            {
+                Ok(self.crypto_to_json_with(config)?)
             }
         }
     };
@@ -166,7 +575,7 @@ impl_deserialize_int!{u8; u16; u32; i8; i16; i32}
 impl_deserialize_bigint!{u64; i64}
 impl_deserialize_hex_segment!{Hash; PublicKey; Signature}
 impl_default_deserialize_owned!{u8; u16; u32; i8; i16; i32; u64; i64}
-impl_default_deserialize_owned!{Hash; PublicKey; Signature; bool}
+impl_default_deserialize_owned!{bool}
 
 impl ExonumJson for bool {
     fn deserialize_field<B: WriteBufferWrapper>(
@@ -175,7 +584,9 @@ impl ExonumJson for bool {
         from: Offset,
         to: Offset,
     ) -> Result<(), Box<Error>> {
-        let val = value.as_bool().ok_or("Can't cast json as bool")?;
+        let val = value
+            .as_bool()
+            .ok_or_else(|| JsonError::wrong_type("boolean"))?;
         buffer.write(from, to, val);
         Ok(())
     }
@@ -192,7 +603,7 @@ impl<'a> ExonumJson for &'a str {
         from: Offset,
         to: Offset,
     ) -> Result<(), Box<Error>> {
-        let val = value.as_str().ok_or("Can't cast json as string")?;
+        let val = str_from_json(value)?;
         buffer.write(from, to, val);
         Ok(())
     }
@@ -209,8 +620,14 @@ impl ExonumJson for DateTime<Utc> {
         from: Offset,
         to: Offset,
     ) -> Result<(), Box<Error>> {
-        let helper: TimestampHelper = serde_json::from_value(value.clone())?;
-        let date_time = Utc.timestamp(helper.secs.parse()?, helper.nanos);
+        let obj = object_from_json(value)?;
+        let secs: i64 = parse_field(obj, "secs", |secs| bigint_from_json(secs, "i64"))?;
+        let nanos = parse_field(obj, "nanos", |nanos| {
+            int_from_json(nanos, 0, i64::from(u32::max_value()), "u32")
+        })?;
+        let date_time = Utc.timestamp_opt(secs, nanos as u32)
+            .single()
+            .ok_or_else(|| JsonError::invalid_value("Timestamp is out of range"))?;
         buffer.write(from, to, date_time);
         Ok(())
     }
@@ -231,11 +648,19 @@ impl ExonumJson for Duration {
         from: Offset,
         to: Offset,
     ) -> Result<(), Box<Error>> {
-        let helper: DurationHelper = serde_json::from_value(value.clone())?;
-        let seconds = helper.secs.parse()?;
+        let obj = object_from_json(value)?;
+        let seconds: i64 = parse_field(obj, "secs", |secs| bigint_from_json(secs, "i64"))?;
+        let nanos = parse_field(obj, "nanos", |nanos| {
+            int_from_json(
+                nanos,
+                i64::from(i32::min_value()),
+                i64::from(i32::max_value()),
+                "i32",
+            )
+        })?;
 
         let seconds_duration = Duration::seconds(seconds);
-        let nanos_duration = Duration::nanoseconds(i64::from(helper.nanos));
+        let nanos_duration = Duration::nanoseconds(nanos);
 
         let result = seconds_duration.checked_add(&nanos_duration);
         match result {
@@ -243,10 +668,10 @@ impl ExonumJson for Duration {
                 buffer.write(from, to, duration);
                 Ok(())
             }
-            None => Err(format!(
+            None => Err(JsonError::invalid_value(format!(
                 "Can't deserialize Duration: {} secs, {} nanos",
-                seconds, helper.nanos
-            ))?,
+                seconds, nanos
+            )))?,
         }
     }
 
@@ -271,7 +696,7 @@ impl ExonumJson for SocketAddr {
         from: Offset,
         to: Offset,
     ) -> Result<(), Box<Error>> {
-        let addr: SocketAddr = serde_json::from_value(value.clone())?;
+        let addr: SocketAddr = serde_json::from_value(value.clone()).map_err(JsonError::from)?;
         buffer.write(from, to, addr);
         Ok(())
     }
@@ -288,21 +713,36 @@ impl<'a> ExonumJson for &'a [Hash] {
         from: Offset,
         to: Offset,
     ) -> Result<(), Box<Error>> {
-        let arr = value.as_array().ok_or("Can't cast json as array")?;
+        Self::deserialize_field_with(value, buffer, from, to, &JsonConfig::default())
+    }
+
+    fn serialize_field(&self) -> Result<Value, Box<Error + Send + Sync>> {
+        self.serialize_field_with(&JsonConfig::default())
+    }
+
+    fn deserialize_field_with<B: WriteBufferWrapper>(
+        value: &Value,
+        buffer: &mut B,
+        from: Offset,
+        to: Offset,
+        config: &JsonConfig,
+    ) -> Result<(), Box<Error>> {
+        let arr = value
+            .as_array()
+            .ok_or_else(|| JsonError::wrong_type("array"))?;
         let mut vec: Vec<Hash> = Vec::new();
-        for el in arr {
-            let string = el.as_str().ok_or("Can't cast json as string")?;
-            let hash = <Hash as FromHex>::from_hex(string)?;
+        for (i, el) in arr.iter().enumerate() {
+            let hash = Hash::crypto_from_json(el, config.crypto()).map_err(|e| e.at_index(i))?;
             vec.push(hash)
         }
         buffer.write(from, to, vec.as_slice());
         Ok(())
     }
 
-    fn serialize_field(&self) -> Result<Value, Box<Error + Send + Sync>> {
+    fn serialize_field_with(&self, config: &JsonConfig) -> Result<Value, Box<Error + Send + Sync>> {
         let mut vec = Vec::new();
         for hash in self.iter() {
-            vec.push(hash.serialize_field()?)
+            vec.push(hash.serialize_field_with(config)?)
         }
         Ok(Value::Array(vec))
     }
@@ -314,17 +754,73 @@ impl<'a> ExonumJson for &'a [u8] {
         from: Offset,
         to: Offset,
     ) -> Result<(), Box<Error>> {
-        let bytes = value.as_str().ok_or("Can't cast json as string")?;
-        let arr = <Vec<u8> as FromHex>::from_hex(bytes)?;
+        Self::deserialize_field_with(value, buffer, from, to, &JsonConfig::default())
+    }
+
+    fn serialize_field(&self) -> Result<Value, Box<Error + Send + Sync>> {
+        self.serialize_field_with(&JsonConfig::default())
+    }
+
+    fn deserialize_field_with<B: WriteBufferWrapper>(
+        value: &Value,
+        buffer: &mut B,
+        from: Offset,
+        to: Offset,
+        config: &JsonConfig,
+    ) -> Result<(), Box<Error>> {
+        let arr = bytes_from_json(value, config.bytes())?;
         buffer.write(from, to, arr.as_slice());
         Ok(())
     }
 
-    fn serialize_field(&self) -> Result<Value, Box<Error + Send + Sync>> {
-        Ok(Value::String(::encoding::serialize::encode_hex(self)))
+    fn serialize_field_with(&self, config: &JsonConfig) -> Result<Value, Box<Error + Send + Sync>> {
+        Ok(Value::String(config.bytes().encode(self)))
     }
 }
 
+// Raw message has the envelope of the message JSON, but its type is unknown,
+// so `body` contains the encoded bytes of the payload.
+fn raw_message_to_json(
+    message: &RawMessage,
+    config: &JsonConfig,
+) -> Result<Value, Box<Error + Send + Sync>> {
+    let mut map = serde_json::Map::new();
+    map.insert(
+        "body".to_string(),
+        Value::String(config.bytes().encode(&message.body()[HEADER_LENGTH..])),
+    );
+    map.insert(
+        "signature".to_string(),
+        message.signature().serialize_field_with(&config.for_field("signature"))?,
+    );
+    map.insert("message_id".to_string(), message.message_type().serialize_field()?);
+    map.insert("service_id".to_string(), message.service_id().serialize_field()?);
+    map.insert("protocol_version".to_string(), message.version().serialize_field()?);
+    Ok(Value::Object(map))
+}
+
+fn raw_message_from_json(value: &Value, config: &JsonConfig) -> Result<RawMessage, JsonError> {
+    let obj = object_from_json(value)?;
+
+    let body = parse_field(obj, "body", |body| bytes_from_json(body, config.bytes()))?;
+    let signature = parse_field(obj, "signature", |signature| {
+        Signature::crypto_from_json(signature, config.for_field("signature").crypto())
+    })?;
+    let message_id = parse_field(obj, "message_id", |id| {
+        int_from_json(id, 0, i64::from(u16::max_value()), "u16")
+    })? as u16;
+    let service_id = parse_field(obj, "service_id", |id| {
+        int_from_json(id, 0, i64::from(u16::max_value()), "u16")
+    })? as u16;
+    let version = parse_field(obj, "protocol_version", |version| {
+        int_from_json(version, 0, i64::from(u8::max_value()), "u8")
+    })? as u8;
+
+    let mut writer = MessageWriter::new(version, service_id, message_id, body.len());
+    writer.write_payload(&body);
+    Ok(RawMessage::new(writer.append_signature(&signature)))
+}
+
 impl ExonumJson for Vec<RawMessage> {
     fn deserialize_field<B: WriteBufferWrapper>(
         value: &Value,
@@ -332,25 +828,55 @@ impl ExonumJson for Vec<RawMessage> {
         from: Offset,
         to: Offset,
     ) -> Result<(), Box<Error>> {
-        unimplemented!()
+        Self::deserialize_field_with(value, buffer, from, to, &JsonConfig::default())
     }
 
     fn serialize_field(&self) -> Result<Value, Box<Error + Send + Sync>> {
-        unimplemented!()
+        self.serialize_field_with(&JsonConfig::default())
     }
-}
 
+    fn deserialize_field_with<B: WriteBufferWrapper>(
+        value: &Value,
+        buffer: &mut B,
+        from: Offset,
+        to: Offset,
+        config: &JsonConfig,
+    ) -> Result<(), Box<Error>> {
+        let items = value
+            .as_array()
+            .ok_or_else(|| JsonError::wrong_type("array"))?;
+        let mut vec = Vec::with_capacity(items.len());
+        for (i, item) in items.iter().enumerate() {
+            vec.push(raw_message_from_json(item, config).map_err(|e| e.at_index(i))?);
+        }
+        buffer.write(from, to, vec);
+        Ok(())
+    }
+
+    fn serialize_field_with(&self, config: &JsonConfig) -> Result<Value, Box<Error + Send + Sync>> {
+        let items = self.iter()
+            .map(|message| raw_message_to_json(message, config))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Value::Array(items))
+    }
+}
 
 impl<T> ExonumJsonDeserialize for Vec<T>
 where
     T: ExonumJsonDeserialize,
     for<'a> Vec<T>: Field<'a>,
 {
-    fn deserialize(value: &Value) -> Result<Self, Box<Error>> {
-        let bytes = value.as_array().ok_or("Can't cast json as array")?;
+    fn deserialize(value: &Value) -> Result<Self, JsonError> {
+        Self::deserialize_with(value, &JsonConfig::default())
+    }
+
+    fn deserialize_with(value: &Value, config: &JsonConfig) -> Result<Self, JsonError> {
+        let bytes = value
+            .as_array()
+            .ok_or_else(|| JsonError::wrong_type("array"))?;
         let mut vec: Vec<_> = Vec::new();
-        for el in bytes {
-            let obj = T::deserialize(el)?;
+        for (i, el) in bytes.iter().enumerate() {
+            let obj = T::deserialize_with(el, config).map_err(|e| e.at_index(i))?;
             vec.push(obj);
         }
 
@@ -358,12 +884,22 @@ where
     }
 }
 
-// TODO remove `ExonumJsonDeserialize` needs
-// after it remove impl `ExonumJsonDeserialize` for all types expect struct (ECR-156)
+// Values which could be only written in place are deserialized through a scratch buffer.
+#[allow(unsafe_code)]
+fn value_from_json<T>(value: &Value, config: &JsonConfig) -> Result<T, JsonError>
+where
+    T: ExonumJson + for<'a> Field<'a>,
+{
+    let size = T::field_size();
+    let mut buffer = vec![0; size as usize];
+    T::deserialize_field_with(value, &mut buffer, 0, size, config)?;
+    T::check(&buffer, 0.into(), size.into(), size.into())?;
+    Ok(unsafe { T::read(&buffer, 0, size) })
+}
+
 impl<T> ExonumJson for Vec<T>
 where
-    T: ExonumJsonDeserialize + ExonumJson,
-    for<'a> Vec<T>: Field<'a>,
+    T: ExonumJson + for<'a> Field<'a>,
 {
     fn deserialize_field<B: WriteBufferWrapper>(
         value: &Value,
@@ -371,25 +907,195 @@ where
         from: Offset,
         to: Offset,
     ) -> Result<(), Box<Error>> {
-        let bytes = value.as_array().ok_or("Can't cast json as array")?;
-        let mut vec: Vec<_> = Vec::new();
-        for el in bytes {
-            let obj = T::deserialize(el)?;
-            vec.push(obj);
+        Self::deserialize_field_with(value, buffer, from, to, &JsonConfig::default())
+    }
+
+    fn serialize_field(&self) -> Result<Value, Box<Error + Send + Sync>> {
+        self.serialize_field_with(&JsonConfig::default())
+    }
+
+    fn deserialize_field_with<B: WriteBufferWrapper>(
+        value: &Value,
+        buffer: &mut B,
+        from: Offset,
+        to: Offset,
+        config: &JsonConfig,
+    ) -> Result<(), Box<Error>> {
+        let items = value
+            .as_array()
+            .ok_or_else(|| JsonError::wrong_type("array"))?;
+        let mut vec: Vec<T> = Vec::with_capacity(items.len());
+        for (i, item) in items.iter().enumerate() {
+            vec.push(value_from_json(item, config).map_err(|e| e.at_index(i))?);
         }
         buffer.write(from, to, vec);
         Ok(())
     }
 
-    fn serialize_field(&self) -> Result<Value, Box<Error + Send + Sync>> {
+    fn serialize_field_with(&self, config: &JsonConfig) -> Result<Value, Box<Error + Send + Sync>> {
         let mut vec = Vec::new();
         for item in self {
-            vec.push(item.serialize_field()?);
+            vec.push(item.serialize_field_with(config)?);
         }
         Ok(Value::Array(vec))
     }
 }
 
+/// `None` is represented as `null`.
+impl<T: ExonumJsonSerialize> ExonumJsonSerialize for Option<T> {
+    fn serialize_json_with(&self, config: &JsonConfig) -> Result<Value, Box<Error + Send + Sync>> {
+        match *self {
+            Some(ref value) => value.serialize_json_with(config),
+            None => Ok(Value::Null),
+        }
+    }
+}
+
+impl<T> ExonumJsonDeserialize for Option<T>
+where
+    T: ExonumJson + for<'a> Field<'a>,
+{
+    fn deserialize(value: &Value) -> Result<Self, JsonError> {
+        Self::deserialize_with(value, &JsonConfig::default())
+    }
+
+    fn deserialize_with(value: &Value, config: &JsonConfig) -> Result<Self, JsonError> {
+        if value.is_null() {
+            Ok(None)
+        } else {
+            Ok(Some(value_from_json(value, config)?))
+        }
+    }
+}
+
+// Tuples are represented as arrays of their items.
+macro_rules! impl_tuple_json {
+    ($len:expr; $(($name:ident, $index:tt)),+) => {
+        impl<$($name: ExonumJsonSerialize),+> ExonumJsonSerialize for ($($name,)+) {
+            fn serialize_json_with(&self, config: &JsonConfig)
+                -> Result<Value, Box<Error + Send + Sync>>
+            {
+                Ok(Value::Array(vec![$(self.$index.serialize_json_with(config)?),+]))
+            }
+        }
+
+        impl<$($name),+> ExonumJsonDeserialize for ($($name,)+)
+        where
+            $($name: ExonumJson + for<'a> Field<'a>),+
+        {
+            fn deserialize(value: &Value) -> Result<Self, JsonError> {
+                Self::deserialize_with(value, &JsonConfig::default())
+            }
+
+            fn deserialize_with(value: &Value, config: &JsonConfig) -> Result<Self, JsonError> {
+                let items = value
+                    .as_array()
+                    .ok_or_else(|| JsonError::wrong_type("array"))?;
+                if items.len() != $len {
+                    return Err(JsonError::invalid_value(format!(
+                        "Expected array of {} items, got {}",
+                        $len,
+                        items.len()
+                    )));
+                }
+                Ok(($(
+                    value_from_json(&items[$index], config).map_err(|e| e.at_index($index))?,
+                )+))
+            }
+        }
+    };
+}
+
+impl_tuple_json!{2; (T0, 0), (T1, 1)}
+impl_tuple_json!{3; (T0, 0), (T1, 1), (T2, 2)}
+impl_tuple_json!{4; (T0, 0), (T1, 1), (T2, 2), (T3, 3)}
+
+// Maps are represented as objects, keys are the json of the key if it is a string,
+// or the decimal notation if it is a number.
+fn map_to_json<'b, K, V, I>(entries: I, config: &JsonConfig) -> Result<Value, Box<Error + Send + Sync>>
+where
+    K: ExonumJsonSerialize + 'b,
+    V: ExonumJsonSerialize + 'b,
+    I: Iterator<Item = (&'b K, &'b V)>,
+{
+    let mut map = serde_json::Map::new();
+    for (key, value) in entries {
+        let key = match key.serialize_json_with(config)? {
+            Value::String(key) => key,
+            Value::Number(key) => key.to_string(),
+            key => Err(format!("Map key {} is neither a string nor a number", key))?,
+        };
+        map.insert(key, value.serialize_json_with(config)?);
+    }
+    Ok(Value::Object(map))
+}
+
+fn map_from_json<K, V, M>(value: &Value, config: &JsonConfig) -> Result<M, JsonError>
+where
+    K: ExonumJson + for<'a> Field<'a>,
+    V: ExonumJson + for<'a> Field<'a>,
+    M: ::std::iter::FromIterator<(K, V)>,
+{
+    let obj = object_from_json(value)?;
+    obj.iter()
+        .map(|(key, value)| {
+            let parsed_key = value_from_json(&Value::String(key.clone()), config)
+                .or_else(|e| match serde_json::from_str::<serde_json::Number>(key) {
+                    Ok(number) => value_from_json(&Value::Number(number), config),
+                    Err(_) => Err(e),
+                });
+            let entry = parsed_key.and_then(|key| Ok((key, value_from_json(value, config)?)));
+            entry.map_err(|e| e.at_field(key))
+        })
+        .collect()
+}
+
+impl<K: ExonumJsonSerialize, V: ExonumJsonSerialize> ExonumJsonSerialize for BTreeMap<K, V> {
+    fn serialize_json_with(&self, config: &JsonConfig) -> Result<Value, Box<Error + Send + Sync>> {
+        map_to_json(self.iter(), config)
+    }
+}
+
+impl<K, V> ExonumJsonDeserialize for BTreeMap<K, V>
+where
+    K: ExonumJson + Ord + for<'a> Field<'a>,
+    V: ExonumJson + for<'a> Field<'a>,
+{
+    fn deserialize(value: &Value) -> Result<Self, JsonError> {
+        Self::deserialize_with(value, &JsonConfig::default())
+    }
+
+    fn deserialize_with(value: &Value, config: &JsonConfig) -> Result<Self, JsonError> {
+        map_from_json(value, config)
+    }
+}
+
+impl<K, V, S> ExonumJsonSerialize for HashMap<K, V, S>
+where
+    K: ExonumJsonSerialize + Eq + StdHash,
+    V: ExonumJsonSerialize,
+    S: BuildHasher,
+{
+    fn serialize_json_with(&self, config: &JsonConfig) -> Result<Value, Box<Error + Send + Sync>> {
+        map_to_json(self.iter(), config)
+    }
+}
+
+impl<K, V, S> ExonumJsonDeserialize for HashMap<K, V, S>
+where
+    K: ExonumJson + Eq + StdHash + for<'a> Field<'a>,
+    V: ExonumJson + for<'a> Field<'a>,
+    S: BuildHasher + Default,
+{
+    fn deserialize(value: &Value) -> Result<Self, JsonError> {
+        Self::deserialize_with(value, &JsonConfig::default())
+    }
+
+    fn deserialize_with(value: &Value, config: &JsonConfig) -> Result<Self, JsonError> {
+        map_from_json(value, config)
+    }
+}
+
 impl ExonumJson for BitVec {
     fn deserialize_field<B: WriteBufferWrapper>(
         value: &Value,
@@ -397,17 +1103,20 @@ impl ExonumJson for BitVec {
         from: Offset,
         to: Offset,
     ) -> Result<(), Box<Error>> {
-        let string = value.as_str().ok_or("Can't cast json as string")?;
-        let mut vec = BitVec::new();
-        for (i, ch) in string.chars().enumerate() {
+        let string = str_from_json(value)?;
+        let mut vec = BitVec::with_capacity(string.len());
+        for ch in string.chars() {
             let val = if ch == '1' {
                 true
             } else if ch == '0' {
                 false
             } else {
-                Err(format!("BitVec should contain only 0 or 1, not {}", ch))?
+                Err(JsonError::invalid_value(format!(
+                    "BitVec should contain only 0 or 1, not {}",
+                    ch
+                )))?
             };
-            vec.set(i, val);
+            vec.push(val);
         }
         buffer.write(from, to, vec);
         Ok(())
@@ -434,7 +1143,7 @@ impl ExonumJson for Height {
         from: Offset,
         to: Offset,
     ) -> Result<(), Box<Error>> {
-        let val: u64 = value.as_str().ok_or("Can't cast json as string")?.parse()?;
+        let val: u64 = bigint_from_json(value, "Height")?;
         buffer.write(from, to, Height(val));
         Ok(())
     }
@@ -452,7 +1161,7 @@ impl ExonumJson for Round {
         from: Offset,
         to: Offset,
     ) -> Result<(), Box<Error>> {
-        let number = value.as_i64().ok_or("Can't cast json as integer")?;
+        let number = int_from_json(value, 0, i64::from(u32::max_value()), "Round")?;
         buffer.write(from, to, Round(number as u32));
         Ok(())
     }
@@ -470,7 +1179,7 @@ impl ExonumJson for ValidatorId {
         from: Offset,
         to: Offset,
     ) -> Result<(), Box<Error>> {
-        let number = value.as_i64().ok_or("Can't cast json as integer")?;
+        let number = int_from_json(value, 0, i64::from(u16::max_value()), "ValidatorId")?;
         buffer.write(from, to, ValidatorId(number as u16));
         Ok(())
     }
@@ -488,7 +1197,7 @@ impl ExonumJson for Uuid {
         from: Offset,
         to: Offset,
     ) -> Result<(), Box<Error>> {
-        let uuid: Self = serde_json::from_value(value.clone())?;
+        let uuid: Self = serde_json::from_value(value.clone()).map_err(JsonError::from)?;
         buffer.write(from, to, uuid);
         Ok(())
     }
@@ -505,7 +1214,7 @@ impl ExonumJson for Decimal {
         from: Offset,
         to: Offset,
     ) -> Result<(), Box<Error>> {
-        let decimal: Self = serde_json::from_value(value.clone())?;
+        let decimal: Self = serde_json::from_value(value.clone()).map_err(JsonError::from)?;
         buffer.write(from, to, decimal);
         Ok(())
     }
@@ -515,6 +1224,178 @@ impl ExonumJson for Decimal {
     }
 }
 
+/// `ExonumJson` representation of the payloads of signed messages.
+///
+/// Structures are written with the configuration of the enclosing message,
+/// types without `ExonumJson` representation keep their `serde` one.
+pub trait ProtocolJson: Sized {
+    /// Serializes the payload, binary data is encoded according to the configuration.
+    fn protocol_to_json(&self, config: &JsonConfig) -> Result<Value, Box<Error + Send + Sync>>;
+
+    /// Deserializes the payload, binary data is decoded according to the configuration.
+    fn protocol_from_json(value: &Value, config: &JsonConfig) -> Result<Self, JsonError>;
+}
+
+macro_rules! impl_protocol_json {
+    (@struct $($name:ty),*) => {
+        $(
+        impl ProtocolJson for $name {
+            fn protocol_to_json(
+                &self,
+                config: &JsonConfig,
+            ) -> Result<Value, Box<Error + Send + Sync>> {
+                self.serialize_field_with(config)
+            }
+
+            fn protocol_from_json(value: &Value, config: &JsonConfig) -> Result<Self, JsonError> {
+                <Self as ExonumJsonDeserialize>::deserialize_with(value, config)
+            }
+        }
+        )*
+    };
+    (@serde $($name:ty),*) => {
+        $(
+        impl ProtocolJson for $name {
+            fn protocol_to_json(
+                &self,
+                _config: &JsonConfig,
+            ) -> Result<Value, Box<Error + Send + Sync>> {
+                Ok(serde_json::to_value(self)?)
+            }
+
+            fn protocol_from_json(value: &Value, _config: &JsonConfig) -> Result<Self, JsonError> {
+                Ok(serde_json::from_value(value.clone())?)
+            }
+        }
+        )*
+    };
+    // Enums keep the externally tagged form of `serde`, i.e. `{ "Variant": payload }`.
+    (@enum $name:ident { $($variant:ident($payload:ty)),* }) => {
+        impl ProtocolJson for $name {
+            fn protocol_to_json(
+                &self,
+                config: &JsonConfig,
+            ) -> Result<Value, Box<Error + Send + Sync>> {
+                let (variant, payload) = match *self {
+                    $( $name::$variant(ref payload) => {
+                        (stringify!($variant), payload.protocol_to_json(config)?)
+                    } )*
+                };
+                let mut map = serde_json::Map::new();
+                map.insert(variant.to_string(), payload);
+                Ok(Value::Object(map))
+            }
+
+            fn protocol_from_json(value: &Value, config: &JsonConfig) -> Result<Self, JsonError> {
+                let obj = object_from_json(value)?;
+                let mut variants = obj.iter();
+                let (variant, payload) = match (variants.next(), variants.next()) {
+                    (Some(variant), None) => variant,
+                    _ => return Err(JsonError::invalid_value(
+                        concat!("Expected exactly one variant of ", stringify!($name), "."),
+                    )),
+                };
+                match variant.as_str() {
+                    $( stringify!($variant) => {
+                        let payload = <$payload as ProtocolJson>::protocol_from_json(payload, config)
+                            .map_err(|e| e.at_field(variant))?;
+                        Ok($name::$variant(payload))
+                    } )*
+                    _ => Err(JsonError::invalid_value(format!(
+                        concat!("Unknown variant `{}` of ", stringify!($name), "."),
+                        variant
+                    ))),
+                }
+            }
+        }
+    };
+}
+
+impl_protocol_json!(@struct Connect, Status, BlockResponse, TransactionsResponse, Propose,
+                    Prevote, Precommit, ProposeRequest, TransactionsRequest, PrevotesRequest,
+                    PeersRequest, BlockRequest);
+impl_protocol_json!(@serde RawTransaction, WithoutEncodingStatus);
+impl_protocol_json!(@enum Protocol {
+    Transaction(RawTransaction),
+    Connect(Connect),
+    Status(Status),
+    WithoutEncodingStatus(WithoutEncodingStatus),
+    Block(BlockResponse),
+    Consensus(ConsensusMessage),
+    Request(RequestMessage),
+    TransactionsBatch(TransactionsResponse)
+});
+impl_protocol_json!(@enum ConsensusMessage {
+    Propose(Propose),
+    Prevote(Prevote),
+    Precommit(Precommit)
+});
+impl_protocol_json!(@enum RequestMessage {
+    Propose(ProposeRequest),
+    Transactions(TransactionsRequest),
+    Prevotes(PrevotesRequest),
+    Peers(PeersRequest),
+    Block(BlockRequest)
+});
+
+// Signed message is represented as an object with `author`, `version`, `protocol`
+// and `signature` fields, where `protocol` is the JSON of the payload passed by the caller.
+fn signed_message_to_json(
+    message: &SignedMessage,
+    protocol: Value,
+    config: &JsonConfig,
+) -> Result<Value, Box<Error + Send + Sync>> {
+    let authorised_message = &message.authorised_message;
+    let mut map = serde_json::Map::new();
+    map.insert(
+        "author".to_string(),
+        (&authorised_message.author).serialize_field_with(&config.for_field("author"))?,
+    );
+    map.insert(
+        "version".to_string(),
+        authorised_message.version.serialize_field()?,
+    );
+    map.insert("protocol".to_string(), protocol);
+    map.insert(
+        "signature".to_string(),
+        (&message.signature).serialize_field_with(&config.for_field("signature"))?,
+    );
+    Ok(Value::Object(map))
+}
+
+// Parses JSON of a signed message. The signature depends on the network,
+// so it is verified only if the config has the chain id.
+fn signed_message_from_json<T>(
+    value: &Value,
+    config: &JsonConfig,
+) -> Result<(T, SignedMessage), JsonError>
+where
+    T: ProtocolJson + Into<Protocol> + Clone,
+{
+    let obj = object_from_json(value)?;
+
+    let author = parse_field(obj, "author", |author| {
+        PublicKey::crypto_from_json_with(author, &config.for_field("author"))
+    })?;
+    let version = parse_field(obj, "version", |version| {
+        int_from_json(version, 0, i64::from(u8::max_value()), "u8")
+    })? as u8;
+    let payload = parse_field(obj, "protocol", |protocol| {
+        T::protocol_from_json(protocol, &config.for_field("protocol"))
+    })?;
+    let signature = parse_field(obj, "signature", |signature| {
+        Signature::crypto_from_json(signature, config.for_field("signature").crypto())
+    })?;
+
+    let message = SignedMessage::from_parts(version, author, payload.clone().into(), signature);
+    if let Some(chain_id) = config.chain_id() {
+        message
+            .verify(chain_id)
+            .map_err(|_| JsonError::new(JsonErrorKind::InvalidSignature).at_field("signature"))?;
+    }
+    Ok((payload, message))
+}
+
 impl ExonumJson for SignedMessage {
     fn deserialize_field<B: WriteBufferWrapper>(
         value: &Value,
@@ -522,18 +1403,95 @@ impl ExonumJson for SignedMessage {
         from: Offset,
         to: Offset,
     ) -> Result<(), Box<Error>> {
-        unimplemented!()
+        Self::deserialize_field_with(value, buffer, from, to, &JsonConfig::default())
+    }
+
+    fn serialize_field(&self) -> Result<Value, Box<Error + Send + Sync>> {
+        self.serialize_field_with(&JsonConfig::default())
+    }
+
+    fn deserialize_field_with<B: WriteBufferWrapper>(
+        value: &Value,
+        buffer: &mut B,
+        from: Offset,
+        to: Offset,
+        config: &JsonConfig,
+    ) -> Result<(), Box<Error>> {
+        let message = <Self as ExonumJsonDeserialize>::deserialize_with(value, config)?;
+        buffer.write(from, to, message);
+        Ok(())
+    }
+
+    fn serialize_field_with(&self, config: &JsonConfig) -> Result<Value, Box<Error + Send + Sync>> {
+        let protocol = self.authorised_message
+            .protocol
+            .protocol_to_json(&config.for_field("protocol"))?;
+        signed_message_to_json(self, protocol, config)
+    }
+}
+
+impl ExonumJsonDeserialize for SignedMessage {
+    fn deserialize(value: &Value) -> Result<Self, JsonError> {
+        Self::deserialize_with(value, &JsonConfig::default())
+    }
+
+    fn deserialize_with(value: &Value, config: &JsonConfig) -> Result<Self, JsonError> {
+        let (_, message) = signed_message_from_json::<Protocol>(value, config)?;
+        Ok(message)
+    }
+}
+
+/// `Message` has the same fields as `SignedMessage`, but `protocol` contains
+/// the JSON of the typed payload itself rather than of the `Protocol` enum,
+/// i.e. `{ "height": .. }` instead of `{ "Status": { "height": .. } }`.
+impl<T> ExonumJson for Message<T>
+where
+    T: ProtocolMessage + ProtocolJson,
+{
+    fn deserialize_field<B: WriteBufferWrapper>(
+        value: &Value,
+        buffer: &mut B,
+        from: Offset,
+        to: Offset,
+    ) -> Result<(), Box<Error>> {
+        Self::deserialize_field_with(value, buffer, from, to, &JsonConfig::default())
     }
 
     fn serialize_field(&self) -> Result<Value, Box<Error + Send + Sync>> {
-        unimplemented!()
+        self.serialize_field_with(&JsonConfig::default())
+    }
+
+    fn deserialize_field_with<B: WriteBufferWrapper>(
+        value: &Value,
+        buffer: &mut B,
+        from: Offset,
+        to: Offset,
+        config: &JsonConfig,
+    ) -> Result<(), Box<Error>> {
+        let message = <Self as ExonumJsonDeserialize>::deserialize_with(value, config)?;
+        let message: SignedMessage = message.into();
+        buffer.write(from, to, message);
+        Ok(())
+    }
+
+    fn serialize_field_with(&self, config: &JsonConfig) -> Result<Value, Box<Error + Send + Sync>> {
+        let payload: &T = self.as_ref();
+        let protocol = payload.protocol_to_json(&config.for_field("protocol"))?;
+        signed_message_to_json(self.as_ref(), protocol, config)
     }
 }
 
-impl ExonumJsonDeserialize for SignedMessage
+impl<T> ExonumJsonDeserialize for Message<T>
+where
+    T: ProtocolMessage + ProtocolJson,
 {
-    fn deserialize(value: &Value) -> Result<Self, Box<Error>> {
-        unimplemented!()
+    fn deserialize(value: &Value) -> Result<Self, JsonError> {
+        Self::deserialize_with(value, &JsonConfig::default())
+    }
+
+    fn deserialize_with(value: &Value, config: &JsonConfig) -> Result<Self, JsonError> {
+        let (payload, message) = signed_message_from_json::<T>(value, config)?;
+        Message::from_parts(payload, message).map_err(|e| JsonError::invalid_value(e.to_string()))
     }
 }
 
@@ -549,7 +1507,9 @@ mod tests {
     #![allow(unsafe_code)]
 
     use super::*;
+    use crypto::{chain_id, gen_keypair, hash, DEFAULT_NETWORK};
     use encoding::CheckedOffset;
+    use messages::{Status, TransactionsResponse, WithoutEncodingStatus, PROTOCOL_MAJOR_VERSION};
 
     #[test]
     fn exonum_json_for_duration_round_trip() {
@@ -591,4 +1551,183 @@ mod tests {
         }
     }
 
+    #[test]
+    fn exonum_json_for_bit_vec_keeps_length() {
+        let mut bits = BitVec::from_elem(5, false);
+        bits.set(1, true);
+        bits.set(4, true);
+
+        let serialized = bits.serialize_field().expect("Can't serialize bit vector");
+        assert_eq!(serialized, Value::String("01001".to_string()));
+
+        let mut buffer = vec![0; BitVec::field_size() as usize];
+        BitVec::deserialize_field(&serialized, &mut buffer, 0, 8)
+            .expect("Can't deserialize bit vector");
+        BitVec::check(&buffer, 0.into(), 8.into(), 8.into())
+            .expect("Incorrect result of deserialization");
+        let result: BitVec = unsafe { BitVec::read(&buffer, 0, 8) };
+        assert_eq!(result, bits);
+    }
+
+    #[test]
+    fn exonum_json_errors_have_kinds() {
+        fn deserialize<T: ExonumJson>(value: Value) -> JsonError {
+            let mut buffer = vec![0; 8];
+            let err = T::deserialize_field(&value, &mut buffer, 0, 8).unwrap_err();
+            JsonError::from(err)
+        }
+
+        assert_eq!(
+            *deserialize::<u8>(Value::from(256)).kind(),
+            JsonErrorKind::OutOfRange {
+                value: "256".to_string(),
+                type_name: "u8",
+            }
+        );
+        assert_eq!(
+            *deserialize::<i64>(Value::from(1)).kind(),
+            JsonErrorKind::WrongType { expected: "string" }
+        );
+        assert_eq!(
+            *deserialize::<Height>(Value::from("-1")).kind(),
+            JsonErrorKind::OutOfRange {
+                value: "-1".to_string(),
+                type_name: "Height",
+            }
+        );
+        assert_eq!(
+            *deserialize::<&[u8]>(Value::from("0g")).kind(),
+            JsonErrorKind::InvalidHex(FromHexError::InvalidHexCharacter { c: 'g', index: 1 })
+        );
+
+        let hashes = Value::Array(vec![Value::from(hash(&[]).to_hex()), Value::from("00")]);
+        let err = deserialize::<&[Hash]>(hashes);
+        assert_eq!(err.pointer(), "/1");
+        assert_eq!(err.to_string(), "Invalid hex: Invalid string length at `/1`");
+
+        let time = serde_json::from_str(r#"{ "secs": "1" }"#).unwrap();
+        let err = deserialize::<DateTime<Utc>>(time);
+        assert_eq!(err.pointer(), "/nanos");
+        assert_eq!(*err.kind(), JsonErrorKind::MissingField);
+
+        let err = from_slice::<u64>(b"\"1\xff\"").unwrap_err();
+        assert_eq!(*err.kind(), JsonErrorKind::InvalidUtf8 { valid_up_to: 2 });
+    }
+
+    #[test]
+    fn exonum_json_error_pointer_escapes_names() {
+        let err = JsonError::missing_field("a/b~c").at_index(2).at_field("items");
+        assert_eq!(err.pointer(), "/items/2/a~1b~0c");
+    }
+
+    fn signed_status() -> Message<Status> {
+        let (pub_key, secret_key) = gen_keypair();
+        let status = Status::new(Height(2), &hash(&[1]));
+        Message::new(status, pub_key, &chain_id(DEFAULT_NETWORK), &secret_key).unwrap()
+    }
+
+    #[test]
+    fn exonum_json_for_signed_message_round_trip() {
+        let message = signed_status();
+        let signed: SignedMessage = message.clone().into();
+
+        let json = signed.serialize_field().expect("Can't serialize message");
+        assert_eq!(json["author"], Value::String(message.author().to_hex()));
+        assert_eq!(json["version"], Value::from(PROTOCOL_MAJOR_VERSION));
+        assert_eq!(json["protocol"]["Status"]["height"], Value::String("2".to_string()));
+        let result = <SignedMessage as ExonumJsonDeserialize>::deserialize(&json)
+            .expect("Can't deserialize message");
+        assert_eq!(result, signed);
+
+        let typed = message.serialize_field().expect("Can't serialize message");
+        assert_eq!(typed["protocol"], json["protocol"]["Status"]);
+        assert_eq!(typed["signature"], json["signature"]);
+        let result = <Message<Status> as ExonumJsonDeserialize>::deserialize(&typed)
+            .expect("Can't deserialize message");
+        assert_eq!(result, message);
+    }
+
+    #[test]
+    fn exonum_json_for_signed_message_applies_config_to_protocol() {
+        let message = signed_status();
+        let signed: SignedMessage = message.clone().into();
+        let config = JsonConfig::new().with_crypto(BinaryEncoding::Base64);
+        let last_hash = BinaryEncoding::Base64.encode(hash(&[1]).as_ref());
+
+        let json = signed.serialize_field_with(&config).expect("Can't serialize message");
+        assert_eq!(json["protocol"]["Status"]["last_hash"], Value::String(last_hash));
+        let result = <SignedMessage as ExonumJsonDeserialize>::deserialize_with(&json, &config)
+            .expect("Can't deserialize message");
+        assert_eq!(result, signed);
+        assert!(<SignedMessage as ExonumJsonDeserialize>::deserialize(&json).is_err());
+
+        let typed = message.serialize_field_with(&config).expect("Can't serialize message");
+        assert_eq!(typed["protocol"], json["protocol"]["Status"]);
+        let result = <Message<Status> as ExonumJsonDeserialize>::deserialize_with(&typed, &config)
+            .expect("Can't deserialize message");
+        assert_eq!(result, message);
+    }
+
+    #[test]
+    fn exonum_json_for_signed_message_verifies_signature() {
+        let config = JsonConfig::new().with_chain_id(chain_id(DEFAULT_NETWORK));
+        let signed: SignedMessage = signed_status().into();
+        let json = signed.serialize_field().unwrap();
+        let result = <SignedMessage as ExonumJsonDeserialize>::deserialize_with(&json, &config);
+        assert_eq!(result.unwrap(), signed);
+
+        let other = JsonConfig::new().with_chain_id(::crypto::chain_id("testnet"));
+        let err = <SignedMessage as ExonumJsonDeserialize>::deserialize_with(&json, &other)
+            .unwrap_err();
+        assert_eq!(err.kind(), &JsonErrorKind::InvalidSignature);
+        assert_eq!(err.pointer(), "/signature");
+
+        let mut changed = json.clone();
+        changed["protocol"]["Status"]["height"] = Value::String("3".to_string());
+        let err = <SignedMessage as ExonumJsonDeserialize>::deserialize_with(&changed, &config)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Message signature does not match its content at `/signature`"
+        );
+        // Without the chain id the signature is read as is.
+        let result = <SignedMessage as ExonumJsonDeserialize>::deserialize(&changed).unwrap();
+        assert!(result.verify(&chain_id(DEFAULT_NETWORK)).is_err());
+
+        let mut changed = signed_status().serialize_field().unwrap();
+        changed["author"] = Value::String(gen_keypair().0.to_hex());
+        let err = <Message<Status> as ExonumJsonDeserialize>::deserialize_with(&changed, &config)
+            .unwrap_err();
+        assert_eq!(err.kind(), &JsonErrorKind::InvalidSignature);
+
+        let mut changed = json.clone();
+        changed.as_object_mut().unwrap().remove("signature");
+        let err = <SignedMessage as ExonumJsonDeserialize>::deserialize(&changed).unwrap_err();
+        assert_eq!(err.pointer(), "/signature");
+    }
+
+    #[test]
+    fn exonum_json_for_signed_message_segments() {
+        let (pub_key, secret_key) = gen_keypair();
+        let status = WithoutEncodingStatus {
+            height: Height(2),
+            last_hash: hash(&[1]),
+        };
+        let message = Message::new(status, pub_key, &chain_id(DEFAULT_NETWORK), &secret_key);
+        let signed: SignedMessage = message.unwrap().into();
+        let response = TransactionsResponse::new(&pub_key, vec![signed.clone(), signed]);
+
+        let json = response.serialize_field().expect("Can't serialize response");
+        let result = <TransactionsResponse as ExonumJsonDeserialize>::deserialize(&json)
+            .expect("Can't deserialize response");
+        assert_eq!(result, response);
+        assert_eq!(result.transactions(), response.transactions());
+
+        let config = JsonConfig::new().with_chain_id(chain_id(DEFAULT_NETWORK));
+        let mut changed = json.clone();
+        changed["transactions"][1]["author"] = Value::String(gen_keypair().0.to_hex());
+        let result = TransactionsResponse::deserialize_with(&changed, &config);
+        let err = result.unwrap_err();
+        assert_eq!(err.pointer(), "/transactions/1/signature");
+    }
 }
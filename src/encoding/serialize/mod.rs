@@ -13,13 +13,22 @@
 // limitations under the License.
 
 //! Serialize structure into specific format.
-//! Currently support only json.
+//! Currently support json, CBOR and the Exonum binary layout,
+//! as well as canonical json and JSON Schema of the json representation.
 //! This module is a pack of superstructures over serde `Serializer`\`Deserializer`
 
 pub use hex::{decode as decode_hex, encode as encode_hex, FromHex, FromHexError, ToHex};
 
+use serde::de::{self, Deserializer, Error as DeError, SeqAccess, Visitor};
+use serde_json;
+
+use std::error::Error;
+use std::fmt;
+
 use super::Offset;
 use encoding::Field;
+use storage::StorageValue;
+use self::json::{ExonumJson, ExonumJsonDeserialize, JsonError};
 
 /// implement exonum serialization\deserialization based on serde `Serialize`\ `Deserialize`
 ///
@@ -36,7 +45,7 @@ macro_rules! implement_exonum_serializer {
         impl $crate::encoding::serialize::json::ExonumJsonDeserialize for $name {
             fn deserialize(
                 value: &$crate::encoding::serialize::json::reexport::Value,
-            ) -> Result<$name, Box<::std::error::Error>> {
+            ) -> Result<$name, $crate::encoding::serialize::json::JsonError> {
                 use $crate::encoding::serialize::json::reexport::from_value;
                 Ok(from_value(value.clone())?)
             }
@@ -74,6 +83,47 @@ macro_rules! implement_exonum_serializer {
 /// implement serializing wrappers and methods for json
 #[macro_use]
 pub mod json;
+pub mod binary;
+pub mod canonical;
+pub mod cbor;
+pub mod schema;
+pub mod text;
+
+/// Self-describing format of the `ExonumJson` representation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Json text.
+    Json,
+    /// CBOR with the same structure as json.
+    Cbor,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Format::Json
+    }
+}
+
+impl Format {
+    /// Writes the value in this format.
+    pub fn to_vec<T: ExonumJson + ?Sized>(
+        self,
+        value: &T,
+    ) -> Result<Vec<u8>, Box<Error + Send + Sync>> {
+        match self {
+            Format::Json => Ok(serde_json::to_vec(&value.serialize_field()?)?),
+            Format::Cbor => cbor::to_vec_json(value),
+        }
+    }
+
+    /// Reads the value written in this format.
+    pub fn from_slice<T: ExonumJsonDeserialize>(self, bytes: &[u8]) -> Result<T, JsonError> {
+        match self {
+            Format::Json => json::from_slice(bytes),
+            Format::Cbor => cbor::from_slice_json(bytes),
+        }
+    }
+}
 
 /// `WriteBufferWrapper` is a trait specific for writing fields in place.
 #[doc(hidden)]
@@ -95,6 +145,59 @@ impl WriteBufferWrapper for Vec<u8> {
     }
 }
 
+/// Reads the raw buffer of a structure, written as bytes by a binary format,
+/// and checks it.
+#[doc(hidden)]
+pub fn deserialize_raw<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: StorageValue + for<'a> Field<'a>,
+    D: Deserializer<'de>,
+{
+    struct RawVisitor;
+
+    impl<'de> Visitor<'de> for RawVisitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("bytes of the structure")
+        }
+
+        fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Vec<u8>, E> {
+            Ok(value.to_vec())
+        }
+
+        fn visit_byte_buf<E: de::Error>(self, value: Vec<u8>) -> Result<Vec<u8>, E> {
+            Ok(value)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+            let mut value = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+            while let Some(byte) = seq.next_element()? {
+                value.push(byte);
+            }
+            Ok(value)
+        }
+    }
+
+    let raw = deserializer.deserialize_byte_buf(RawVisitor)?;
+    from_raw_checked(raw).map_err(D::Error::custom)
+}
+
+/// Checks the raw buffer of a structure the same way as the structure is checked
+/// in a message.
+#[doc(hidden)]
+pub fn from_raw_checked<T>(raw: Vec<u8>) -> Result<T, ::encoding::Error>
+where
+    T: StorageValue + for<'a> Field<'a>,
+{
+    // The structure is checked as a segment, pointed by the header of the buffer.
+    let header_size = T::field_size();
+    let mut buffer = vec![0; header_size as usize];
+    raw.write(&mut buffer, 0, header_size);
+    T::check(&buffer, 0.into(), header_size.into(), header_size.into())?;
+    Ok(T::from_bytes(raw.into()))
+}
+
 /// Reexport of `serde` specific traits, this reexports
 /// provide compatibility layer with important `serde` version.
 pub mod reexport {
@@ -0,0 +1,344 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! JSON Schema documents describing the `ExonumJson` representation of types.
+//!
+//! `JsonSchema` is implemented for all field types and for the structures declared with
+//! `encoding_struct!`, `encoding_layout!` and `messages!`. Schemas follow the `ExonumJson`
+//! conventions: 64-bit integers are strings, hashes and keys are hex strings,
+//! `DateTime` is an object with `secs` and `nanos` fields.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate exonum;
+//! use exonum::encoding::serialize::schema::{json_schema_document, JsonSchema};
+//! use exonum::messages::Status;
+//!
+//! # fn main() {
+//! let schema = json_schema_document::<Status>();
+//! assert_eq!(schema["title"], "Status");
+//! assert_eq!(schema["properties"]["height"]["type"], "string");
+//! # }
+//! ```
+
+use serde_json::{Map, Value};
+use bit_vec::BitVec;
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+use rust_decimal::Decimal;
+
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+
+use crypto::{Hash, PublicKey, Signature, HASH_SIZE, PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH};
+use types::{Height, Round, ValidatorId};
+use messages::{Message, ProtocolMessage, SignedMessage};
+
+/// Version of the JSON Schema specification used by the documents.
+pub const JSON_SCHEMA_VERSION: &str = "http://json-schema.org/draft-07/schema#";
+
+/// Trait for types with a known JSON representation.
+pub trait JsonSchema {
+    /// Returns JSON Schema of the `ExonumJson` representation of the type.
+    fn json_schema() -> Value;
+}
+
+/// Returns a standalone JSON Schema document for the type.
+pub fn json_schema_document<T: JsonSchema + ?Sized>() -> Value {
+    let mut schema = T::json_schema();
+    if let Value::Object(ref mut map) = schema {
+        map.insert(
+            "$schema".to_string(),
+            Value::String(JSON_SCHEMA_VERSION.to_string()),
+        );
+    }
+    schema
+}
+
+fn schema(entries: Vec<(&str, Value)>) -> Value {
+    let map = entries
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect::<Map<String, Value>>();
+    Value::Object(map)
+}
+
+fn string(value: &str) -> Value {
+    Value::String(value.to_string())
+}
+
+/// Returns schema of an object with the given properties, all of them are required.
+#[doc(hidden)]
+pub fn object_schema(title: &str, properties: Vec<(&str, Value)>) -> Value {
+    let required = properties
+        .iter()
+        .map(|&(name, _)| string(name))
+        .collect();
+    schema(vec![
+        ("title", string(title)),
+        ("type", string("object")),
+        ("properties", schema(properties)),
+        ("required", Value::Array(required)),
+        ("additionalProperties", Value::Bool(false)),
+    ])
+}
+
+fn integer_schema(minimum: i64, maximum: u64) -> Value {
+    schema(vec![
+        ("type", string("integer")),
+        ("minimum", minimum.into()),
+        ("maximum", maximum.into()),
+    ])
+}
+
+fn pattern_schema(pattern: &str) -> Value {
+    schema(vec![("type", string("string")), ("pattern", string(pattern))])
+}
+
+fn hex_schema(len: usize) -> Value {
+    pattern_schema(&format!("^[0-9a-fA-F]{{{}}}$", len * 2))
+}
+
+fn array_schema(items: Value) -> Value {
+    schema(vec![("type", string("array")), ("items", items)])
+}
+
+macro_rules! impl_json_schema {
+    (@impl $typename:ty => $schema:expr) => {
+        impl<'a> JsonSchema for $typename {
+            fn json_schema() -> Value {
+                $schema
+            }
+        }
+    };
+    ($($name:ty => $schema:expr);* $(;)*) =>
+        ($(impl_json_schema!{@impl $name => $schema})*);
+}
+
+impl_json_schema! {
+    u8 => integer_schema(0, u64::from(u8::max_value()));
+    u16 => integer_schema(0, u64::from(u16::max_value()));
+    u32 => integer_schema(0, u64::from(u32::max_value()));
+    i8 => integer_schema(i64::from(i8::min_value()), i8::max_value() as u64);
+    i16 => integer_schema(i64::from(i16::min_value()), i16::max_value() as u64);
+    i32 => integer_schema(i64::from(i32::min_value()), i32::max_value() as u64);
+    u64 => pattern_schema("^[0-9]{1,20}$");
+    i64 => pattern_schema("^-?[0-9]{1,19}$");
+    bool => schema(vec![("type", string("boolean"))]);
+    &'a str => schema(vec![("type", string("string"))]);
+    &'a [u8] => pattern_schema("^([0-9a-fA-F]{2})*$");
+    &'a Hash => hex_schema(HASH_SIZE);
+    &'a PublicKey => hex_schema(PUBLIC_KEY_LENGTH);
+    &'a Signature => hex_schema(SIGNATURE_LENGTH);
+    &'a [Hash] => array_schema(hex_schema(HASH_SIZE));
+    BitVec => pattern_schema("^[01]*$");
+    Height => <u64 as JsonSchema>::json_schema();
+    Round => <u32 as JsonSchema>::json_schema();
+    ValidatorId => <u16 as JsonSchema>::json_schema();
+    DateTime<Utc> => object_schema("DateTime", vec![
+        ("secs", <i64 as JsonSchema>::json_schema()),
+        ("nanos", <u32 as JsonSchema>::json_schema()),
+    ]);
+    Duration => object_schema("Duration", vec![
+        ("secs", <i64 as JsonSchema>::json_schema()),
+        ("nanos", <i32 as JsonSchema>::json_schema()),
+    ]);
+    SocketAddr => schema(vec![("type", string("string"))]);
+    Uuid => schema(vec![("type", string("string")), ("format", string("uuid"))]);
+    Decimal => pattern_schema("^-?[0-9]+(\\.[0-9]+)?$");
+}
+
+impl<T: JsonSchema> JsonSchema for Vec<T> {
+    fn json_schema() -> Value {
+        array_schema(T::json_schema())
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for Option<T> {
+    fn json_schema() -> Value {
+        schema(vec![
+            (
+                "anyOf",
+                Value::Array(vec![schema(vec![("type", string("null"))]), T::json_schema()]),
+            ),
+        ])
+    }
+}
+
+macro_rules! impl_tuple_json_schema {
+    ($len:expr; $($name:ident),+) => {
+        impl<$($name: JsonSchema),+> JsonSchema for ($($name,)+) {
+            fn json_schema() -> Value {
+                schema(vec![
+                    ("type", string("array")),
+                    ("items", Value::Array(vec![$($name::json_schema()),+])),
+                    ("minItems", $len.into()),
+                    ("maxItems", $len.into()),
+                ])
+            }
+        }
+    };
+}
+
+impl_tuple_json_schema!{2; T0, T1}
+impl_tuple_json_schema!{3; T0, T1, T2}
+impl_tuple_json_schema!{4; T0, T1, T2, T3}
+
+// Keys of maps are always strings in json, so only values are described.
+fn map_schema(values: Value) -> Value {
+    schema(vec![
+        ("type", string("object")),
+        ("additionalProperties", values),
+    ])
+}
+
+impl<K, V: JsonSchema> JsonSchema for BTreeMap<K, V> {
+    fn json_schema() -> Value {
+        map_schema(V::json_schema())
+    }
+}
+
+impl<K, V: JsonSchema, S> JsonSchema for HashMap<K, V, S> {
+    fn json_schema() -> Value {
+        map_schema(V::json_schema())
+    }
+}
+
+fn signed_message_schema(title: &str, protocol: Value) -> Value {
+    object_schema(
+        title,
+        vec![
+            ("author", hex_schema(PUBLIC_KEY_LENGTH)),
+            ("version", <u8 as JsonSchema>::json_schema()),
+            ("protocol", protocol),
+            ("signature", hex_schema(SIGNATURE_LENGTH)),
+        ],
+    )
+}
+
+impl JsonSchema for SignedMessage {
+    fn json_schema() -> Value {
+        signed_message_schema("SignedMessage", schema(vec![("type", string("object"))]))
+    }
+}
+
+impl<T: ProtocolMessage + JsonSchema> JsonSchema for Message<T> {
+    fn json_schema() -> Value {
+        signed_message_schema("Message", T::json_schema())
+    }
+}
+
+#[cfg(feature = "float_serialize")]
+impl_json_schema! {
+    super::super::F32 => schema(vec![("type", string("number"))]);
+    super::super::F64 => schema(vec![("type", string("number"))]);
+}
+
+#[cfg(test)]
+mod tests {
+    use crypto::{chain_id, gen_keypair, hash, DEFAULT_NETWORK};
+    use messages::{Precommit, Propose, Status};
+    use encoding::serialize::json::ExonumJson;
+
+    use super::*;
+
+    // Checks that the JSON object has exactly the properties declared in the schema.
+    fn assert_properties_match(schema: &Value, value: &Value) {
+        let properties = schema["properties"].as_object().unwrap();
+        let object = value.as_object().unwrap();
+        let mut declared = properties.keys().collect::<Vec<_>>();
+        let mut actual = object.keys().collect::<Vec<_>>();
+        declared.sort();
+        actual.sort();
+        assert_eq!(declared, actual);
+    }
+
+    #[test]
+    fn test_json_schema_of_struct() {
+        let schema = json_schema_document::<Propose>();
+        assert_eq!(schema["$schema"], string(JSON_SCHEMA_VERSION));
+        assert_eq!(schema["title"], string("Propose"));
+        assert_eq!(schema["type"], string("object"));
+        assert_eq!(schema["additionalProperties"], Value::Bool(false));
+        assert_eq!(
+            schema["required"],
+            Value::Array(
+                ["validator", "height", "round", "prev_hash", "transactions"]
+                    .iter()
+                    .map(|name| string(name))
+                    .collect()
+            )
+        );
+
+        let properties = &schema["properties"];
+        assert_eq!(properties["validator"]["type"], string("integer"));
+        assert_eq!(properties["validator"]["maximum"], Value::from(65_535));
+        assert_eq!(properties["height"]["pattern"], string("^[0-9]{1,20}$"));
+        assert_eq!(properties["prev_hash"]["pattern"], string("^[0-9a-fA-F]{64}$"));
+        assert_eq!(properties["transactions"]["type"], string("array"));
+        assert_eq!(
+            properties["transactions"]["items"]["pattern"],
+            string("^[0-9a-fA-F]{64}$")
+        );
+
+        let propose = Propose::new(
+            ValidatorId(1),
+            Height(2),
+            Round(3),
+            &hash(&[]),
+            &[hash(&[1])],
+        );
+        assert_properties_match(&schema, &propose.serialize_field().unwrap());
+    }
+
+    #[test]
+    fn test_json_schema_of_time() {
+        let schema = Precommit::json_schema();
+        let time = &schema["properties"]["time"];
+        assert_eq!(time["type"], string("object"));
+        assert_eq!(time["properties"]["secs"]["type"], string("string"));
+        assert_eq!(time["properties"]["nanos"]["type"], string("integer"));
+
+        let precommit = Precommit::new(
+            ValidatorId(1),
+            Height(2),
+            Round(3),
+            &hash(&[1]),
+            &hash(&[2]),
+            Utc::now(),
+        );
+        let json = precommit.serialize_field().unwrap();
+        assert_properties_match(&schema, &json);
+        assert_properties_match(time, &json["time"]);
+    }
+
+    #[test]
+    fn test_json_schema_of_signed_message() {
+        let schema = Message::<Status>::json_schema();
+        assert_eq!(schema["properties"]["protocol"], Status::json_schema());
+        assert_eq!(
+            schema["properties"]["signature"]["pattern"],
+            string("^[0-9a-fA-F]{128}$")
+        );
+
+        let (pub_key, secret_key) = gen_keypair();
+        let status = Status::new(Height(2), &hash(&[]));
+        let message =
+            Message::new(status, pub_key, &chain_id(DEFAULT_NETWORK), &secret_key).unwrap();
+        let json = message.serialize_field().unwrap();
+        assert_properties_match(&schema, &json);
+        assert_properties_match(&schema["properties"]["protocol"], &json["protocol"]);
+    }
+}
@@ -0,0 +1,410 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Text encodings of binary data in the `ExonumJson` representation.
+//!
+//! By default hashes, keys, signatures and byte blobs are written as lowercase hex.
+//! `JsonConfig` selects another encoding for blobs, for hashes, keys and signatures,
+//! or for particular fields of structures; it is accepted by
+//! `ExonumJson::serialize_field_with`, `ExonumJson::deserialize_field_with`
+//! and `ExonumJsonDeserialize::deserialize_with`. Public keys may also be written
+//! as addresses of a network, see `JsonConfig::with_addresses`. Signatures of the
+//! messages are verified while reading if the chain id is set, see
+//! `JsonConfig::with_chain_id`.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate exonum;
+//! use exonum::encoding::serialize::json::ExonumJson;
+//! use exonum::encoding::serialize::text::{BinaryEncoding, JsonConfig};
+//!
+//! # fn main() {
+//! let config = JsonConfig::new().with_bytes(BinaryEncoding::Base64);
+//! let bytes: &[u8] = &[1, 2, 3];
+//! assert_eq!(bytes.serialize_field_with(&config).unwrap(), "AQID");
+//! # }
+//! ```
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crypto::Hash;
+use super::{decode_hex, encode_hex, FromHexError};
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE58_ALPHABET: &[u8; 58] =
+    b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Text encoding of binary data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BinaryEncoding {
+    /// Lowercase hex, uppercase digits are accepted while decoding.
+    Hex,
+    /// Base64 with the standard alphabet and padding (RFC 4648).
+    Base64,
+    /// Base58 with the Bitcoin alphabet.
+    Base58,
+}
+
+impl Default for BinaryEncoding {
+    fn default() -> Self {
+        BinaryEncoding::Hex
+    }
+}
+
+impl fmt::Display for BinaryEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            BinaryEncoding::Hex => "hex",
+            BinaryEncoding::Base64 => "base64",
+            BinaryEncoding::Base58 => "base58",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Error of decoding text into binary data.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodeError {
+    /// Hex string is invalid.
+    Hex(FromHexError),
+    /// Character is not allowed by the encoding.
+    InvalidCharacter {
+        /// Invalid character.
+        c: char,
+        /// Position of the character in the string.
+        index: usize,
+    },
+    /// String length is not allowed by the encoding.
+    InvalidLength,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DecodeError::Hex(ref e) => write!(f, "{}", e),
+            DecodeError::InvalidCharacter { c, index } => {
+                write!(f, "Invalid character {:?} at position {}", c, index)
+            }
+            DecodeError::InvalidLength => write!(f, "Invalid string length"),
+        }
+    }
+}
+
+impl BinaryEncoding {
+    /// Encodes bytes as a string.
+    pub fn encode(&self, bytes: &[u8]) -> String {
+        match *self {
+            BinaryEncoding::Hex => encode_hex(bytes),
+            BinaryEncoding::Base64 => encode_base64(bytes),
+            BinaryEncoding::Base58 => encode_base58(bytes),
+        }
+    }
+
+    /// Decodes bytes from a string.
+    pub fn decode(&self, string: &str) -> Result<Vec<u8>, DecodeError> {
+        match *self {
+            BinaryEncoding::Hex => decode_hex(string).map_err(DecodeError::Hex),
+            BinaryEncoding::Base64 => decode_base64(string),
+            BinaryEncoding::Base58 => decode_base58(string),
+        }
+    }
+}
+
+/// Configuration of the `ExonumJson` representation of binary data.
+///
+/// A field override applies to the field and to everything nested in it,
+/// including the public keys written as addresses.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JsonConfig {
+    bytes: BinaryEncoding,
+    crypto: BinaryEncoding,
+    addresses: Option<String>,
+    chain_id: Option<Hash>,
+    fields: BTreeMap<String, BinaryEncoding>,
+}
+
+impl JsonConfig {
+    /// Creates configuration with hex encoding everywhere.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets encoding of byte blobs.
+    pub fn with_bytes(mut self, encoding: BinaryEncoding) -> Self {
+        self.bytes = encoding;
+        self
+    }
+
+    /// Sets encoding of hashes, public keys and signatures.
+    pub fn with_crypto(mut self, encoding: BinaryEncoding) -> Self {
+        self.crypto = encoding;
+        self
+    }
+
+    /// Writes public keys as addresses of the network instead of the encoding of
+    /// hashes, public keys and signatures. Addresses of other networks are rejected
+    /// while reading.
+    pub fn with_addresses<S: Into<String>>(mut self, network: S) -> Self {
+        self.addresses = Some(network.into());
+        self
+    }
+
+    /// Verifies signatures of the messages in the network with the given chain id while
+    /// reading. Without the chain id signatures are read as is.
+    pub fn with_chain_id(mut self, chain_id: Hash) -> Self {
+        self.chain_id = Some(chain_id);
+        self
+    }
+
+    /// Sets encoding of all binary data inside the structure fields with the given name.
+    pub fn with_field<S: Into<String>>(mut self, name: S, encoding: BinaryEncoding) -> Self {
+        self.fields.insert(name.into(), encoding);
+        self
+    }
+
+    /// Returns encoding of byte blobs.
+    pub fn bytes(&self) -> BinaryEncoding {
+        self.bytes
+    }
+
+    /// Returns encoding of hashes, public keys and signatures.
+    pub fn crypto(&self) -> BinaryEncoding {
+        self.crypto
+    }
+
+    /// Returns the network of the public key addresses, if the keys are written as addresses.
+    pub fn addresses(&self) -> Option<&str> {
+        self.addresses.as_ref().map(String::as_str)
+    }
+
+    /// Returns the chain id the signatures of the messages are verified with.
+    pub fn chain_id(&self) -> Option<&Hash> {
+        self.chain_id.as_ref()
+    }
+
+    /// Returns configuration for the value of the structure field with the given name.
+    pub fn for_field(&self, name: &str) -> Cow<JsonConfig> {
+        match self.fields.get(name) {
+            Some(&encoding) => {
+                let mut config = self.clone().with_bytes(encoding).with_crypto(encoding);
+                config.addresses = None;
+                Cow::Owned(config)
+            }
+            None => Cow::Borrowed(self),
+        }
+    }
+}
+
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity((bytes.len() + 2) / 3 * 4);
+    for chunk in bytes.chunks(3) {
+        let mut group = [0_u8; 3];
+        group[..chunk.len()].copy_from_slice(chunk);
+        let bits = u32::from(group[0]) << 16 | u32::from(group[1]) << 8 | u32::from(group[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (bits >> (18 - 6 * i)) & 0x3f;
+                out.push(BASE64_ALPHABET[index as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn decode_base64(string: &str) -> Result<Vec<u8>, DecodeError> {
+    let bytes = string.as_bytes();
+    if bytes.len() % 4 != 0 {
+        return Err(DecodeError::InvalidLength);
+    }
+    let padding = bytes.iter().rev().take(2).take_while(|&&b| b == b'=').count();
+    let mut out = Vec::with_capacity(bytes.len() / 4 * 3);
+    for (chunk_index, chunk) in bytes.chunks(4).enumerate() {
+        let is_last = (chunk_index + 1) * 4 == bytes.len();
+        let data_len = if is_last { 4 - padding } else { 4 };
+        let mut bits = 0_u32;
+        for (i, &byte) in chunk.iter().enumerate() {
+            let value = if i < data_len {
+                BASE64_ALPHABET.iter().position(|&c| c == byte)
+            } else {
+                None
+            };
+            let value = match value {
+                Some(value) => value as u32,
+                None if i >= data_len && byte == b'=' => 0,
+                None => {
+                    return Err(DecodeError::InvalidCharacter {
+                        c: string[chunk_index * 4 + i..].chars().next().unwrap(),
+                        index: chunk_index * 4 + i,
+                    })
+                }
+            };
+            bits = bits << 6 | value;
+        }
+        let group = [(bits >> 16) as u8, (bits >> 8) as u8, bits as u8];
+        // Unused bits of the last group must be zero, so every string has one decoding.
+        if group[data_len - 1..].iter().any(|&b| b != 0) && data_len < 4 {
+            return Err(DecodeError::InvalidLength);
+        }
+        out.extend_from_slice(&group[..data_len - 1]);
+    }
+    Ok(out)
+}
+
+fn encode_base58(bytes: &[u8]) -> String {
+    let zeros = bytes.iter().take_while(|&&b| b == 0).count();
+    // Digits of the number in base 58, least significant first.
+    let mut digits: Vec<u8> = Vec::with_capacity(bytes.len() * 138 / 100 + 1);
+    for &byte in &bytes[zeros..] {
+        let mut carry = u32::from(byte);
+        for digit in &mut digits {
+            carry += u32::from(*digit) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let mut out = String::with_capacity(zeros + digits.len());
+    for _ in 0..zeros {
+        out.push('1');
+    }
+    for &digit in digits.iter().rev() {
+        out.push(BASE58_ALPHABET[digit as usize] as char);
+    }
+    out
+}
+
+fn decode_base58(string: &str) -> Result<Vec<u8>, DecodeError> {
+    let zeros = string.chars().take_while(|&c| c == '1').count();
+    // Bytes of the number, least significant first.
+    let mut bytes: Vec<u8> = Vec::with_capacity(string.len() * 733 / 1000 + 1);
+    for (index, c) in string.char_indices().skip(zeros) {
+        let value = base58_digit(c).ok_or(DecodeError::InvalidCharacter { c, index })?;
+        let mut carry = u32::from(value);
+        for byte in &mut bytes {
+            carry += u32::from(*byte) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    let mut out = vec![0; zeros];
+    out.extend(bytes.iter().rev());
+    Ok(out)
+}
+
+// Returns the value of a base58 digit, the alphabet skips `0`, `I`, `O` and `l`.
+fn base58_digit(c: char) -> Option<u8> {
+    let (first, offset) = match c {
+        '1'..='9' => ('1', 0),
+        'A'..='H' => ('A', 9),
+        'J'..='N' => ('J', 17),
+        'P'..='Z' => ('P', 22),
+        'a'..='k' => ('a', 33),
+        'm'..='z' => ('m', 44),
+        _ => return None,
+    };
+    Some(c as u8 - first as u8 + offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_vectors() {
+        // Test vectors from RFC 4648.
+        let vectors = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for &(input, output) in &vectors {
+            assert_eq!(BinaryEncoding::Base64.encode(input.as_bytes()), output);
+            assert_eq!(
+                BinaryEncoding::Base64.decode(output).unwrap(),
+                input.as_bytes()
+            );
+        }
+        assert_eq!(
+            BinaryEncoding::Base64.encode(&[0xfb, 0xff]),
+            "+/8="
+        );
+    }
+
+    #[test]
+    fn test_base64_rejects_invalid_strings() {
+        let decode = |s| BinaryEncoding::Base64.decode(s).unwrap_err();
+        assert_eq!(decode("Zg="), DecodeError::InvalidLength);
+        assert_eq!(decode("Zh=="), DecodeError::InvalidLength);
+        assert_eq!(
+            decode("Z-=="),
+            DecodeError::InvalidCharacter { c: '-', index: 1 }
+        );
+        assert_eq!(
+            decode("Z==g"),
+            DecodeError::InvalidCharacter { c: '=', index: 1 }
+        );
+    }
+
+    #[test]
+    fn test_base58_vectors() {
+        let vectors: [(&[u8], &str); 5] = [
+            (b"", ""),
+            (&[0], "1"),
+            (&[0, 0, 1], "112"),
+            (b"hello world", "StV1DL6CwTryKyV"),
+            (&[0xff; 4], "7YXq9G"),
+        ];
+        for &(input, output) in &vectors {
+            assert_eq!(BinaryEncoding::Base58.encode(input), output);
+            assert_eq!(BinaryEncoding::Base58.decode(output).unwrap(), input);
+        }
+        assert_eq!(
+            BinaryEncoding::Base58.decode("10").unwrap_err(),
+            DecodeError::InvalidCharacter { c: '0', index: 1 }
+        );
+    }
+
+    #[test]
+    fn test_field_config() {
+        let config = JsonConfig::new()
+            .with_bytes(BinaryEncoding::Base64)
+            .with_field("author", BinaryEncoding::Base58);
+        assert_eq!(config.bytes(), BinaryEncoding::Base64);
+        assert_eq!(config.crypto(), BinaryEncoding::Hex);
+        assert_eq!(config.for_field("payload").crypto(), BinaryEncoding::Hex);
+        assert_eq!(config.for_field("author").crypto(), BinaryEncoding::Base58);
+        assert_eq!(config.for_field("author").bytes(), BinaryEncoding::Base58);
+
+        let config = config.with_addresses("exonum");
+        assert_eq!(config.for_field("payload").addresses(), Some("exonum"));
+        assert_eq!(config.for_field("author").addresses(), None);
+    }
+}
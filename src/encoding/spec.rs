@@ -52,17 +52,72 @@
 /// println!("Two integers: {:?}", s);
 /// # }
 /// ```
+///
+/// # Validation rules
+///
+/// A field can be followed by a `where` clause with a boolean expression, in which the field
+/// is available by its name. Structure-wide rules are listed in a `where` clause after
+/// the structure body and can use all the fields. Rules are checked after the layout checks
+/// in `Field::check`, and when the structure is deserialized from JSON; a failed rule is
+/// reported as [`Error::Validation`] with the name of the field (or of the structure).
+///
+/// [`Error::Validation`]: ./encoding/enum.Error.html#variant.Validation
+///
+/// ```
+/// #[macro_use] extern crate exonum;
+///
+/// encoding_struct! {
+///     struct Range {
+///         name: &str where !name.is_empty(),
+///         from: u64,
+///         to: u64,
+///     }
+///     where from <= to
+/// }
+/// # fn main() {}
+/// ```
+///
+/// # Hash domain
+///
+/// The structure is hashed as its raw buffer prefixed with the tag of its [`Domain`].
+/// The domain is `Struct`, unless the structure belongs to a more specific one,
+/// for example, it is a block header. Such a domain is specified before
+/// the structure attributes.
+///
+/// [`Domain`]: ./crypto/enum.Domain.html
+///
+/// ```
+/// #[macro_use] extern crate exonum;
+///
+/// encoding_struct! {
+///     #![domain = Block]
+///     /// Header of a block.
+///     struct Header {
+///         height: u64,
+///     }
+/// }
+/// # fn main() {}
+/// ```
+///
+/// # Serde representation
+///
+/// Human-readable formats, e.g. json, get the [`ExonumJson`] representation of the structure.
+/// Binary formats, e.g. `bincode`, get its raw buffer as bytes, which is checked
+/// the same way as a field of a message when it is read.
 #[macro_export]
 macro_rules! encoding_struct {
     (
+    $(#![domain = $domain:ident])?
     $(#[$attr:meta])*
     struct $name:ident {
         $(
         $(#[$field_attr:meta])*
-        $field_name:ident : $field_type:ty
+        $field_name:ident : $field_type:ty $(where $field_rule:expr)?
         ),*
         $(,)*
-    }) => (
+    }
+    $(where $($struct_rule:expr),+ $(,)*)?
+    ) => (
         #[derive(Clone, PartialEq)]
         $(#[$attr])*
         pub struct $name {
@@ -115,38 +170,19 @@ macro_rules! encoding_struct {
                     __ex_struct_check_field, (latest_segment, vec),
                     $( ($(#[$field_attr])*, $field_name, $field_type) )*
                 );
+                $name::__ex_validate(vec)?;
                 Ok(latest_segment_origin)
             }
 
-            fn field_size() -> $crate::encoding::Offset {
-                // We write `encoding_struct` as regular buffer,
-                // so real `field_size` is 8.
-                // TODO: maybe we should write it as sub structure in place?
-                // We could get benefit from it: we limit indirection
-                // in deserializing sub fields, by only one calculation (ECR-156).
+            // We write `encoding_struct` as regular buffer,
+            // so real `field_size` is 8.
+            // TODO: maybe we should write it as sub structure in place?
+            // We could get benefit from it: we limit indirection
+            // in deserializing sub fields, by only one calculation (ECR-156).
 
-                // $body as $crate::encoding::Offset
+            // $body as $crate::encoding::Offset
 
-                8 as $crate::encoding::Offset
-            }
-        }
-
-        impl $crate::crypto::CryptoHash for $name {
-            fn hash(&self) -> $crate::crypto::Hash {
-                $crate::crypto::hash(self.raw.as_ref())
-            }
-        }
-
-        impl $crate::storage::StorageValue for $name {
-            fn into_bytes(self) -> Vec<u8> {
-                self.raw
-            }
-
-            fn from_bytes(v: ::std::borrow::Cow<[u8]>) -> Self {
-                $name {
-                    raw: v.into_owned()
-                }
-            }
+            const FIELD_SIZE: $crate::encoding::Offset = 8 as $crate::encoding::Offset;
         }
 
         // TODO extract some fields like hash and from_raw into trait (ECR-156)
@@ -172,6 +208,80 @@ macro_rules! encoding_struct {
             fn __ex_header_size() -> $crate::encoding::Offset {
                 __ex_header_size!($($field_type),*)
             }
+
+            // Checks declared validation rules, buffer should be already checked.
+            #[allow(unused_variables)]
+            fn __ex_validate(vec: &[u8]) -> Result<(), $crate::encoding::Error> {
+                __ex_for_each_field!(
+                    __ex_struct_validate_field, (vec),
+                    $( ($(#[$field_attr])*, $field_name, $field_type, [$($field_rule)?]) )*
+                );
+                __ex_struct_validate!(
+                    (vec), $name,
+                    ($( ($(#[$field_attr])*, $field_name, $field_type) )*),
+                    [$($($struct_rule),+)?]
+                );
+                Ok(())
+            }
+        }
+
+        impl $crate::encoding::serialize::json::ExonumJsonDeserialize for $name {
+            #[allow(unused_imports, unused_mut)]
+            fn deserialize(value: &$crate::encoding::serialize::json::reexport::Value)
+                -> Result<Self, $crate::encoding::serialize::json::JsonError> {
+                Self::deserialize_with(value, &Default::default())
+            }
+
+            #[allow(unused_imports, unused_mut, unused_variables)]
+            fn deserialize_with(value: &$crate::encoding::serialize::json::reexport::Value,
+                                config: &$crate::encoding::serialize::text::JsonConfig)
+                -> Result<Self, $crate::encoding::serialize::json::JsonError> {
+                use $crate::encoding::serialize::json::ExonumJson as ExonumJson;
+                use $crate::encoding::serialize::json::JsonError;
+                let mut buf = vec![0; $name::__ex_header_size() as usize];
+                let _obj = value.as_object().ok_or_else(|| JsonError::wrong_type("object"))?;
+                __ex_for_each_field!(
+                    __ex_deserialize_field, (_obj, buf, config),
+                    $( ($(#[$field_attr])*, $field_name, $field_type) )*
+                );
+                $name::__ex_validate(&buf)?;
+                Ok($name { raw: buf })
+            }
+        }
+
+        __ex_struct_impls!($name $(in $domain)?, $($field_name: $field_type),*);
+    )
+}
+
+// Implements traits that depend only on the fields and the raw buffer of a structure:
+// `CryptoHash`, `StorageValue`, `Debug`, `JsonSchema`, `Diff`, `ExonumJson`, `CborValue`
+// and serde traits.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __ex_struct_impls {
+    ($name:ident in $domain:ident, $($field_name:ident : $field_type:ty),*) => (
+        impl $crate::crypto::CryptoHash for $name {
+            fn hash(&self) -> $crate::crypto::Hash {
+                $crate::crypto::Domain::$domain.hash(self.raw.as_ref())
+            }
+        }
+
+        __ex_struct_impls!(@common $name, $($field_name: $field_type),*);
+    );
+    ($name:ident, $($field_name:ident : $field_type:ty),*) => (
+        __ex_struct_impls!($name in Struct, $($field_name: $field_type),*);
+    );
+    (@common $name:ident, $($field_name:ident : $field_type:ty),*) => (
+        impl $crate::storage::StorageValue for $name {
+            fn into_bytes(self) -> Vec<u8> {
+                self.raw
+            }
+
+            fn from_bytes(v: ::std::borrow::Cow<[u8]>) -> Self {
+                $name {
+                    raw: v.into_owned()
+                }
+            }
         }
 
         impl ::std::fmt::Debug for $name {
@@ -183,6 +293,43 @@ macro_rules! encoding_struct {
             }
         }
 
+        impl $crate::encoding::serialize::schema::JsonSchema for $name {
+            fn json_schema() -> $crate::encoding::serialize::json::reexport::Value {
+                $crate::encoding::serialize::schema::object_schema(
+                    stringify!($name),
+                    vec![$(
+                        (stringify!($field_name),
+                         <$field_type as $crate::encoding::serialize::schema::JsonSchema>
+                            ::json_schema()),
+                    )*],
+                )
+            }
+        }
+
+        impl $crate::encoding::diff::Diff for $name {
+            #[allow(unused_variables)]
+            fn diff_at(&self,
+                       other: &Self,
+                       path: &str,
+                       differences: &mut $crate::encoding::diff::Differences) {
+                if self.raw == other.raw {
+                    return;
+                }
+                $(
+                    $crate::encoding::diff::Diff::diff_at(
+                        &self.$field_name(),
+                        &other.$field_name(),
+                        &$crate::encoding::diff::field_path(path, stringify!($field_name)),
+                        differences,
+                    );
+                )*
+            }
+
+            fn diff_value(&self) -> $crate::encoding::serialize::json::reexport::Value {
+                $crate::encoding::diff::json_value(self)
+            }
+        }
+
         impl $crate::encoding::serialize::json::ExonumJson for $name {
             #[allow(unused_variables)]
             fn deserialize_field<B> (value: &$crate::encoding::serialize::json::reexport::Value,
@@ -191,19 +338,37 @@ macro_rules! encoding_struct {
                                         to: $crate::encoding::Offset )
                 -> Result<(), Box<::std::error::Error>>
                 where B: $crate::encoding::serialize::WriteBufferWrapper
+            {
+                Self::deserialize_field_with(value, buffer, from, to, &Default::default())
+            }
+
+            fn serialize_field(&self)
+                -> Result<$crate::encoding::serialize::json::reexport::Value,
+                          Box<::std::error::Error + Send + Sync>>
+            {
+                self.serialize_field_with(&Default::default())
+            }
+
+            #[allow(unused_variables)]
+            fn deserialize_field_with<B> (value: &$crate::encoding::serialize::json::reexport::Value,
+                                        buffer: & mut B,
+                                        from: $crate::encoding::Offset,
+                                        to: $crate::encoding::Offset,
+                                        config: &$crate::encoding::serialize::text::JsonConfig)
+                -> Result<(), Box<::std::error::Error>>
+                where B: $crate::encoding::serialize::WriteBufferWrapper
             {
                 use $crate::encoding::serialize::json::ExonumJsonDeserialize;
                 // deserialize full field
-                let structure = <Self as ExonumJsonDeserialize>::deserialize(value)?;
+                let structure = <Self as ExonumJsonDeserialize>::deserialize_with(value, config)?;
                 // then write it
                 buffer.write(from, to, structure);
 
                 Ok(())
             }
 
-
-            #[allow(unused_mut)]
-            fn serialize_field(&self)
+            #[allow(unused_mut, unused_variables)]
+            fn serialize_field_with(&self, config: &$crate::encoding::serialize::text::JsonConfig)
                 -> Result<$crate::encoding::serialize::json::reexport::Value,
                           Box<::std::error::Error + Send + Sync>>
             {
@@ -211,36 +376,38 @@ macro_rules! encoding_struct {
                 let mut map = $crate::encoding::serialize::json::reexport::Map::new();
                 $(
                     map.insert(stringify!($field_name).to_string(),
-                        self.$field_name().serialize_field()?);
+                        self.$field_name().serialize_field_with(
+                            &config.for_field(stringify!($field_name)))?);
                 )*
                 Ok(Value::Object(map))
             }
         }
-        impl $crate::encoding::serialize::json::ExonumJsonDeserialize for $name {
-            #[allow(unused_imports, unused_mut)]
-            fn deserialize(value: &$crate::encoding::serialize::json::reexport::Value)
-                -> Result<Self, Box<::std::error::Error>> {
-                use $crate::encoding::serialize::json::ExonumJson as ExonumJson;
-                let mut buf = vec![0; $name::__ex_header_size() as usize];
-                let _obj = value.as_object().ok_or("Can't cast json as object.")?;
-                __ex_for_each_field!(
-                    __ex_deserialize_field, (_obj, buf),
-                    $( ($(#[$field_attr])*, $field_name, $field_type) )*
-                );
-                Ok($name { raw: buf })
+
+        impl $crate::encoding::serialize::cbor::CborValue for $name {
+            fn to_cbor(&self) -> $crate::encoding::serialize::cbor::DataItem {
+                $crate::encoding::serialize::cbor::DataItem::Bytes(self.raw.clone())
+            }
+
+            fn from_cbor(item: $crate::encoding::serialize::cbor::DataItem)
+                -> Result<Self, $crate::encoding::serialize::cbor::CborError> {
+                $crate::encoding::serialize::cbor::struct_from_cbor(item, stringify!($name))
             }
         }
 
         // TODO: Rewrite Deserialize and Serialize implementation (ECR-156)
+        // Binary formats, e.g. `bincode`, keep the raw buffer of the structure.
         impl<'de> $crate::encoding::serialize::reexport::Deserialize<'de> for $name {
             fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
                 where D: $crate::encoding::serialize::reexport::Deserializer<'de>
             {
                 use $crate::encoding::serialize::json::reexport::Value;
                 use $crate::encoding::serialize::reexport::{DeError, Deserialize};
+                if !deserializer.is_human_readable() {
+                    return $crate::encoding::serialize::deserialize_raw(deserializer);
+                }
                 let value = <Value as Deserialize>::deserialize(deserializer)?;
                 <Self as $crate::encoding::serialize::json::ExonumJsonDeserialize>::deserialize(
-                    &value).map_err(|_| D::Error::custom("Can not deserialize value."))
+                    &value).map_err(D::Error::custom)
             }
         }
 
@@ -250,6 +417,9 @@ macro_rules! encoding_struct {
             {
                 use $crate::encoding::serialize::reexport::SerError;
                 use $crate::encoding::serialize::json::ExonumJson;
+                if !serializer.is_human_readable() {
+                    return serializer.serialize_bytes(&self.raw);
+                }
                 self.serialize_field()
                     .map_err(|_| S::Error::custom(
                                 concat!("Can not serialize structure: ", stringify!($name))))?
@@ -305,6 +475,398 @@ macro_rules! check_bounds {
     }};
 }
 
+/// `encoding_layout!` macro implements a structure with an explicit layout of the header.
+///
+/// Unlike [`encoding_struct!`], where fields follow each other in the order of declaration,
+/// every field is placed at the given `[from => to]` offsets. Unused bytes of the header
+/// should be declared as `reserved` ranges; they are written as zeros, and `check` fails
+/// with [`IncorrectReservedBytes`] if a buffer contains anything else there.
+///
+/// The layout is validated at compile time: ranges should be adjacent, start from `0`
+/// and end at the declared `size`, and each field range should match the size
+/// of the field type. If `align` is given, `size` should be a multiple of it,
+/// and each field should either start at an aligned offset or fit into a single aligned word.
+/// Reserved ranges can't have attributes, as they don't produce any items.
+///
+/// Besides the layout, the generated type is the same as one created with `encoding_struct!`,
+/// including [validation rules] and the [hash domain].
+///
+/// [`encoding_struct!`]: macro.encoding_struct.html
+/// [`IncorrectReservedBytes`]: ./encoding/enum.Error.html#variant.IncorrectReservedBytes
+/// [validation rules]: macro.encoding_struct.html#validation-rules
+/// [hash domain]: macro.encoding_struct.html#hash-domain
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use] extern crate exonum;
+///
+/// encoding_layout! {
+///     struct MessageHeader [size = 10, align = 2] {
+///         reserved [0 => 1],
+///         version: u8 [1 => 2],
+///         message_type: u16 [2 => 4],
+///         service_id: u16 [4 => 6],
+///         payload_length: u32 [6 => 10],
+///     }
+/// }
+///
+/// # fn main() {
+/// let header = MessageHeader::new(0, 1, 128, 42);
+/// assert_eq!(header.service_id(), 128);
+/// # }
+/// ```
+///
+/// A field range, which doesn't match the size of the field type, fails to compile:
+///
+/// ```compile_fail
+/// #[macro_use] extern crate exonum;
+///
+/// encoding_layout! {
+///     struct WrongSize [size = 8] {
+///         value: u32 [0 => 8],
+///     }
+/// }
+/// # fn main() {}
+/// ```
+///
+/// Rules and the domain are declared in the same way as in `encoding_struct!`:
+///
+/// ```
+/// #[macro_use] extern crate exonum;
+///
+/// encoding_layout! {
+///     #![domain = Block]
+///     struct Range [size = 16] {
+///         from: u64 [0 => 8],
+///         to: u64 [8 => 16] where to < 1_000,
+///     }
+///     where from <= to
+/// }
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! encoding_layout {
+    (
+    $(#![domain = $domain:ident])?
+    $(#[$attr:meta])*
+    struct $name:ident [size = $size:expr] {
+        $($entries:tt)*
+    }
+    $(where $($struct_rule:expr),+ $(,)*)?
+    ) => (
+        encoding_layout! {
+            $(#![domain = $domain])?
+            $(#[$attr])*
+            struct $name [size = $size, align = 1] {
+                $($entries)*
+            }
+            $(where $($struct_rule),+)?
+        }
+    );
+    (
+    $(#![domain = $domain:ident])?
+    $(#[$attr:meta])*
+    struct $name:ident [size = $size:expr, align = $align:expr] {
+        $($entries:tt)*
+    }
+    $(where $($struct_rule:expr),+ $(,)*)?
+    ) => (
+        __ex_layout!(
+            @parse {
+                [$($domain)?] [$(#[$attr])*] $name ($size) ($align) [$($($struct_rule),+)?]
+            }
+            fields [] reserved [] checks [] prev (0);
+            $($entries)*
+        );
+    );
+}
+
+// Munches entries of `encoding_layout!` into the lists of fields, reserved ranges
+// and layout checks, then generates the structure.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __ex_layout {
+    (
+        @parse $ctx:tt fields $fields:tt reserved $reserved:tt checks $checks:tt prev $prev:tt;
+        , $($rest:tt)*
+    ) => (
+        __ex_layout!(
+            @parse $ctx fields $fields reserved $reserved checks $checks prev $prev;
+            $($rest)*
+        );
+    );
+    (
+        @parse $ctx:tt fields $fields:tt reserved $reserved:tt checks $checks:tt prev $prev:tt;
+    ) => (
+        __ex_layout!(@impl $ctx fields $fields reserved $reserved checks $checks prev $prev);
+    );
+    (
+        @parse $ctx:tt fields $fields:tt reserved $reserved:tt checks $checks:tt prev $prev:tt;
+        $(#[$reserved_attr:meta])+
+        reserved [$from:expr => $to:expr]
+        $($rest:tt)*
+    ) => (
+        compile_error!(concat!(
+            "reserved range [", stringify!($from), " => ", stringify!($to),
+            "] can't have attributes"
+        ));
+    );
+    (
+        @parse $ctx:tt fields $fields:tt reserved [$($reserved:tt)*] checks [$($checks:tt)*]
+        prev $prev:tt;
+        reserved [$from:expr => $to:expr]
+        $($rest:tt)*
+    ) => (
+        __ex_layout!(
+            @parse $ctx
+            fields $fields
+            reserved [$($reserved)* (($from) ($to))]
+            checks [$($checks)* [reserved $prev ($from) ($to)]]
+            prev ($to);
+            $($rest)*
+        );
+    );
+    // A rule is an expression, so it is followed by a comma, unless it is the last entry.
+    (
+        @parse $ctx:tt fields $fields:tt reserved $reserved:tt checks $checks:tt prev $prev:tt;
+        $(#[$field_attr:meta])*
+        $field_name:ident : $field_type:ty [$from:expr => $to:expr] where $field_rule:expr
+    ) => (
+        __ex_layout!(
+            @parse $ctx fields $fields reserved $reserved checks $checks prev $prev;
+            $(#[$field_attr])*
+            $field_name : $field_type [$from => $to] where $field_rule,
+        );
+    );
+    (
+        @parse $ctx:tt fields [$($fields:tt)*] reserved $reserved:tt checks [$($checks:tt)*]
+        prev $prev:tt;
+        $(#[$field_attr:meta])*
+        $field_name:ident : $field_type:ty [$from:expr => $to:expr] where $field_rule:expr,
+        $($rest:tt)*
+    ) => (
+        __ex_layout!(
+            @parse $ctx
+            fields [$($fields)* (
+                [$(#[$field_attr])*] $field_name ($field_type) ($from) ($to) [$field_rule]
+            )]
+            reserved $reserved
+            checks [$($checks)* [field $prev ($from) ($to) ($field_type)]]
+            prev ($to);
+            $($rest)*
+        );
+    );
+    (
+        @parse $ctx:tt fields [$($fields:tt)*] reserved $reserved:tt checks [$($checks:tt)*]
+        prev $prev:tt;
+        $(#[$field_attr:meta])*
+        $field_name:ident : $field_type:ty [$from:expr => $to:expr]
+        $($rest:tt)*
+    ) => (
+        __ex_layout!(
+            @parse $ctx
+            fields [$($fields)* ([$(#[$field_attr])*] $field_name ($field_type) ($from) ($to) [])]
+            reserved $reserved
+            checks [$($checks)* [field $prev ($from) ($to) ($field_type)]]
+            prev ($to);
+            $($rest)*
+        );
+    );
+
+    (@check ($align:expr) [reserved ($prev:expr) ($from:expr) ($to:expr)]) => {
+        __ex_layout!(@assert ($prev) == ($from));
+        __ex_layout!(@assert ($from) < ($to));
+    };
+    (@check ($align:expr) [field ($prev:expr) ($from:expr) ($to:expr) ($field_type:ty)]) => {
+        __ex_layout!(@check ($align) [reserved ($prev) ($from) ($to)]);
+        __ex_layout!(@assert
+            (($from) % ($align) == 0) | (($from) / ($align) == (($to) - 1) / ($align)));
+        __ex_layout!(@assert
+            ($to) - ($from) == <$field_type as $crate::encoding::Field>::FIELD_SIZE);
+    };
+    // Fails to compile if the condition is false, because array length underflows.
+    (@assert $($cond:tt)*) => {
+        let _: [(); 0 - !($($cond)*) as usize] = [];
+    };
+
+    // Checks structure-wide rules, every field is available in the rules by its name.
+    (
+        @validate ($vec:ident) $name:ident []
+        $( ($field_name:ident ($field_type:ty) ($from:expr) ($to:expr)) )*
+    ) => {};
+    (
+        @validate ($vec:ident) $name:ident [$($rule:expr),+]
+        $( ($field_name:ident ($field_type:ty) ($from:expr) ($to:expr)) )*
+    ) => {
+        {
+            $(
+                __ex_struct_bind_field!(($vec), , $field_name, $field_type, $from, $to);
+            )*
+            $(
+                if !($rule) {
+                    return Err($crate::encoding::Error::Validation {
+                        field: stringify!($name),
+                        rule: stringify!($rule),
+                    });
+                }
+            )+
+        }
+    };
+
+    (
+        @impl {
+            [$($domain:ident)?] [$(#[$attr:meta])*] $name:ident ($size:expr) ($align:expr)
+            [$($struct_rule:expr),*]
+        }
+        fields [$(
+            (
+                [$(#[$field_attr:meta])*] $field_name:ident ($field_type:ty)
+                ($from:expr) ($to:expr) [$($field_rule:expr)?]
+            )
+        )*]
+        reserved [$( (($reserved_from:expr) ($reserved_to:expr)) )*]
+        checks [$($check:tt)*]
+        prev $prev:tt
+    ) => (
+        #[derive(Clone, PartialEq)]
+        $(#[$attr])*
+        pub struct $name {
+            raw: Vec<u8>
+        }
+
+        #[allow(unsafe_code)]
+        impl<'a> $crate::encoding::Field<'a> for $name {
+            unsafe fn read(buffer: &'a [u8],
+                            from: $crate::encoding::Offset,
+                            to: $crate::encoding::Offset) -> Self {
+                let vec: Vec<u8> = $crate::encoding::Field::read(buffer, from, to);
+                $crate::storage::StorageValue::from_bytes(::std::borrow::Cow::Owned(vec))
+            }
+
+            fn write(&self,
+                            buffer: &mut Vec<u8>,
+                            from: $crate::encoding::Offset,
+                            to: $crate::encoding::Offset) {
+                $crate::encoding::Field::write(&self.raw, buffer, from, to);
+            }
+
+            #[allow(unused_variables)]
+            fn check(buffer: &'a [u8],
+                        from_st_val: $crate::encoding::CheckedOffset,
+                        to_st_val: $crate::encoding::CheckedOffset,
+                        latest_segment: $crate::encoding::CheckedOffset)
+                -> $crate::encoding::Result
+            {
+                let latest_segment_origin = <&[u8] as $crate::encoding::Field>::check(
+                    buffer, from_st_val, to_st_val, latest_segment)?;
+                let vec: &[u8] = unsafe{ $crate::encoding::Field::read(
+                    buffer,
+                    from_st_val.unchecked_offset(),
+                    to_st_val.unchecked_offset())};
+                let latest_segment: $crate::encoding::CheckedOffset =
+                    $name::__ex_header_size().into();
+
+                if vec.len() < $name::__ex_header_size() as usize {
+                    return Err($crate::encoding::Error::UnexpectedlyShortPayload{
+                        actual_size: vec.len() as $crate::encoding::Offset,
+                        minimum_size: $name::__ex_header_size() as $crate::encoding::Offset
+                    })
+                }
+
+                $(
+                    $crate::encoding::check_reserved(vec, $reserved_from, $reserved_to)?;
+                )*
+                $(
+                    __ex_struct_check_field!(
+                        (latest_segment, vec),
+                        $(#[$field_attr])*, $field_name, $field_type, $from, $to
+                    );
+                )*
+                $name::__ex_validate(vec)?;
+                Ok(latest_segment_origin)
+            }
+
+            // Written as a regular buffer, same as `encoding_struct!`.
+            const FIELD_SIZE: $crate::encoding::Offset = 8 as $crate::encoding::Offset;
+        }
+
+        impl $name {
+            #[cfg_attr(feature="cargo-clippy", allow(too_many_arguments))]
+            #[allow(unused_imports, unused_mut)]
+
+            /// Creates a new instance with given parameters.
+            pub fn new($($field_name: $field_type,)*) -> $name {
+                let mut buf = vec![0; $name::__ex_header_size() as usize];
+                $(
+                    __ex_struct_write_field!(
+                        (buf), $(#[$field_attr])*, $field_name, $field_type, $from, $to
+                    );
+                )*
+                $name { raw: buf }
+            }
+
+            $(
+                __ex_struct_mk_field!(
+                    (), $(#[$field_attr])*, $field_name, $field_type, $from, $to
+                );
+            )*
+
+            fn __ex_header_size() -> $crate::encoding::Offset {
+                $(
+                    __ex_layout!(@check ($align) $check);
+                )*
+                __ex_layout!(@assert $prev == ($size));
+                __ex_layout!(@assert ($size) % ($align) == 0);
+                $size
+            }
+
+            // Checks declared validation rules, buffer should be already checked.
+            #[allow(unused_variables)]
+            fn __ex_validate(vec: &[u8]) -> Result<(), $crate::encoding::Error> {
+                $(
+                    __ex_struct_validate_field!(
+                        (vec), $(#[$field_attr])*, $field_name, $field_type, $from, $to,
+                        [$($field_rule)?]
+                    );
+                )*
+                __ex_layout!(
+                    @validate (vec) $name [$($struct_rule),*]
+                    $( ($field_name ($field_type) ($from) ($to)) )*
+                );
+                Ok(())
+            }
+        }
+
+        impl $crate::encoding::serialize::json::ExonumJsonDeserialize for $name {
+            #[allow(unused_imports, unused_mut)]
+            fn deserialize(value: &$crate::encoding::serialize::json::reexport::Value)
+                -> Result<Self, $crate::encoding::serialize::json::JsonError> {
+                Self::deserialize_with(value, &Default::default())
+            }
+
+            #[allow(unused_imports, unused_mut, unused_variables)]
+            fn deserialize_with(value: &$crate::encoding::serialize::json::reexport::Value,
+                                config: &$crate::encoding::serialize::text::JsonConfig)
+                -> Result<Self, $crate::encoding::serialize::json::JsonError> {
+                use $crate::encoding::serialize::json::ExonumJson as ExonumJson;
+                use $crate::encoding::serialize::json::JsonError;
+                let mut buf = vec![0; $name::__ex_header_size() as usize];
+                let _obj = value.as_object().ok_or_else(|| JsonError::wrong_type("object"))?;
+                $(
+                    __ex_deserialize_field!(
+                        (_obj, buf, config), $(#[$field_attr])*, $field_name, $field_type, $from, $to
+                    );
+                )*
+                $name::__ex_validate(&buf)?;
+                Ok($name { raw: buf })
+            }
+        }
+
+        __ex_struct_impls!($name $(in $domain)?, $($field_name: $field_type),*);
+    )
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! __ex_header_size {
@@ -325,6 +887,8 @@ macro_rules! __ex_header_size {
 //         $(#[$field_attr:meta])*, $field_name:ident, $field_type:ty, $from:expr, $to:expr
 //     ) => { ... }
 // }
+// Additional token trees after the field type in the field tuple are passed to $m
+// after `$to`.
 #[doc(hidden)]
 #[macro_export]
 macro_rules! __ex_for_each_field {
@@ -334,7 +898,7 @@ macro_rules! __ex_for_each_field {
 
     (
         @inner $m:ident ($($env:tt)*) ($start_offset:expr);
-        ($(#[$field_attr:meta])*, $field_name:ident, $field_type:ty) $($rest:tt)*
+        ($(#[$field_attr:meta])*, $field_name:ident, $field_type:ty $(, $extra:tt)*) $($rest:tt)*
     ) => {
         $m!(
             ($($env)*),
@@ -343,6 +907,7 @@ macro_rules! __ex_for_each_field {
             $field_type,
             $start_offset,
             $start_offset + <$field_type as $crate::encoding::Field>::field_size()
+            $(, $extra)*
         );
 
         __ex_for_each_field!(
@@ -404,11 +969,75 @@ macro_rules! __ex_struct_mk_field {
 #[macro_export]
 macro_rules! __ex_deserialize_field {
     (
-        ($obj:ident, $writer:ident),
+        ($obj:ident, $writer:ident, $config:ident),
         $(#[$field_attr:meta])*, $field_name:ident, $field_type:ty, $from:expr, $to:expr
     ) => {
         let val = $obj.get(stringify!($field_name))
-                      .ok_or("Can't get object from json.")?;
-        <$field_type as ExonumJson>::deserialize_field(val, &mut $writer, $from, $to)?;
+                      .ok_or_else(|| JsonError::missing_field(stringify!($field_name)))?;
+        <$field_type as ExonumJson>::deserialize_field_with(
+            val, &mut $writer, $from, $to, &$config.for_field(stringify!($field_name))
+        ).map_err(|e| JsonError::from(e).at_field(stringify!($field_name)))?;
     }
 }
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __ex_struct_validate_field {
+    (
+        ($vec:ident),
+        $(#[$field_attr:meta])*, $field_name:ident, $field_type:ty, $from:expr, $to:expr,
+        []
+    ) => {};
+    (
+        ($vec:ident),
+        $(#[$field_attr:meta])*, $field_name:ident, $field_type:ty, $from:expr, $to:expr,
+        [$rule:expr]
+    ) => {
+        #[allow(unsafe_code)]
+        {
+            let $field_name: $field_type = unsafe {
+                $crate::encoding::Field::read($vec, $from, $to)
+            };
+            if !($rule) {
+                return Err($crate::encoding::Error::Validation {
+                    field: stringify!($field_name),
+                    rule: stringify!($rule),
+                });
+            }
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __ex_struct_bind_field {
+    (
+        ($vec:ident),
+        $(#[$field_attr:meta])*, $field_name:ident, $field_type:ty, $from:expr, $to:expr
+    ) => {
+        #[allow(unsafe_code)]
+        let $field_name: $field_type = unsafe {
+            $crate::encoding::Field::read($vec, $from, $to)
+        };
+    }
+}
+
+// Checks structure-wide rules, every field is available in the rules by its name.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __ex_struct_validate {
+    (($vec:ident), $name:ident, ($($fields:tt)*), []) => {};
+    (($vec:ident), $name:ident, ($($fields:tt)*), [$($rule:expr),+]) => {
+        {
+            __ex_for_each_field!(__ex_struct_bind_field, ($vec), $($fields)*);
+            $(
+                if !($rule) {
+                    return Err($crate::encoding::Error::Validation {
+                        field: stringify!($name),
+                        rule: stringify!($rule),
+                    });
+                }
+            )+
+        }
+    };
+}
@@ -22,16 +22,33 @@ use rust_decimal::Decimal;
 
 use std::net::SocketAddr;
 use std::str::FromStr;
-use crypto::{hash, gen_keypair};
-use messages::{Connect, Propose, Prevote, Precommit, Status, BlockResponse,
-               BlockRequest};
-use types::{Height, Round, ValidatorId, user_agent};
-use super::{Field, Offset};
+use crypto::{chain_id, gen_keypair, hash, DEFAULT_NETWORK};
+use old_messages::{Block, BlockRequest, BlockResponse, Connect, Message, Precommit, Prevote,
+                   Propose, RawMessage, Status};
+use types::{Height, Round, ValidatorId};
+use super::{CheckedOffset, Error, Field, Offset};
 
 static VALIDATOR: ValidatorId = ValidatorId(65_123);
 static HEIGHT: Height = Height(123_123_123);
 static ROUND: Round = Round(321_321_312);
 
+// Stand-ins for the parts of the node, which the tests of old messages refer to.
+mod user_agent {
+    pub fn get() -> String {
+        "exonum 0.9.0/rustc 1.26.0".to_owned()
+    }
+}
+
+mod blockchain {
+    pub const SCHEMA_MAJOR_VERSION: u16 = 0;
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct BlockProof {
+    block: Block,
+    precommits: Vec<Precommit>,
+}
+
 #[allow(dead_code)]
 // This structures used to test deserialization,
 // so we should ignore unused `new` method.
@@ -91,6 +108,51 @@ fn test_bitvec() {
     assert_write_check_read(b, 8);
 }
 
+#[test]
+fn test_bitvec_exact_length() {
+    let mut b = BitVec::from_elem(5, false);
+    b.set(0, true);
+    b.set(4, true);
+
+    let mut buffer = vec![0; 8];
+    Field::write(&b, &mut buffer, 0, 8);
+    // Length prefix and a single byte with bits.
+    assert_eq!(&buffer[8..], &[5, 0, 0, 0, 0b1000_1000]);
+    assert_write_check_read(b, 8);
+    assert_write_check_read(BitVec::new(), 8);
+}
+
+#[test]
+fn test_bitvec_incorrect_padding() {
+    let mut buffer = vec![0; 8];
+    Field::write(&BitVec::from_elem(5, true), &mut buffer, 0, 8);
+    <BitVec as Field>::check(&buffer, 0.into(), 8.into(), 8.into()).unwrap();
+
+    buffer[12] |= 1;
+    match <BitVec as Field>::check(&buffer, 0.into(), 8.into(), 8.into()) {
+        Err(Error::IncorrectBitVecPadding { position, value }) => {
+            assert_eq!(position, 12);
+            assert_eq!(value, 0b1111_1001);
+        }
+        other => panic!("Unexpected check result: {:?}", other),
+    }
+
+    // Number of bytes doesn't match the length prefix.
+    let mut buffer = vec![0; 8];
+    Field::write(&BitVec::from_elem(9, true), &mut buffer, 0, 8);
+    buffer[8] = 8;
+    assert!(<BitVec as Field>::check(&buffer, 0.into(), 8.into(), 8.into()).is_err());
+}
+
+#[test]
+#[should_panic(expected = "is too long to be written")]
+fn test_bitvec_too_long() {
+    // Memory of the zeroed vector isn't touched until it is written.
+    let b = BitVec::from_elem(u32::max_value() as usize + 1, false);
+    let mut buffer = vec![0; 8];
+    Field::write(&b, &mut buffer, 0, 8);
+}
+
 #[test]
 fn test_str_segment() {
     let mut buf = vec![0; 8];
@@ -320,10 +382,11 @@ fn test_segments_of_raw_buffers() {
 #[test]
 fn test_segments_of_raw_messages() {
     let (pub_key, sec_key) = gen_keypair();
+    let chain_id = chain_id(DEFAULT_NETWORK);
 
-    let m1 = Status::new(&pub_key, Height(2), &hash(&[]), &sec_key);
-    let m2 = Status::new(&pub_key, Height(4), &hash(&[1]), &sec_key);
-    let m3 = Status::new(&pub_key, Height(5), &hash(&[3]), &sec_key);
+    let m1 = Status::new(&pub_key, Height(2), &hash(&[]), &chain_id, &sec_key).unwrap();
+    let m2 = Status::new(&pub_key, Height(4), &hash(&[1]), &chain_id, &sec_key).unwrap();
+    let m3 = Status::new(&pub_key, Height(5), &hash(&[3]), &chain_id, &sec_key).unwrap();
 
     let dat = vec![m1.raw().clone(), m2.raw().clone(), m3.raw().clone()];
     assert_write_check_read(dat, 8);
@@ -338,10 +401,11 @@ fn test_empty_segments() {
 #[test]
 fn test_segments_of_status_messages() {
     let (pub_key, sec_key) = gen_keypair();
+    let chain_id = chain_id(DEFAULT_NETWORK);
 
-    let m1 = Status::new(&pub_key, Height(2), &hash(&[]), &sec_key);
-    let m2 = Status::new(&pub_key, Height(4), &hash(&[1]), &sec_key);
-    let m3 = Status::new(&pub_key, Height(5), &hash(&[3]), &sec_key);
+    let m1 = Status::new(&pub_key, Height(2), &hash(&[]), &chain_id, &sec_key).unwrap();
+    let m2 = Status::new(&pub_key, Height(4), &hash(&[1]), &chain_id, &sec_key).unwrap();
+    let m3 = Status::new(&pub_key, Height(5), &hash(&[3]), &chain_id, &sec_key).unwrap();
 
     let dat = vec![m1, m2, m3];
     assert_write_check_read(dat, 8);
@@ -353,6 +417,7 @@ fn test_connect(addr: &str) {
     let socket_address = SocketAddr::from_str(addr).unwrap();
     let time = Utc::now();
     let (public_key, secret_key) = gen_keypair();
+    let chain_id = chain_id(DEFAULT_NETWORK);
 
     // write
     let connect = Connect::new(
@@ -360,13 +425,14 @@ fn test_connect(addr: &str) {
         socket_address,
         time,
         &user_agent::get(),
+        &chain_id,
         &secret_key,
-    );
+    ).unwrap();
     // read
     assert_eq!(connect.pub_key(), &public_key);
     assert_eq!(connect.addr(), socket_address);
     assert_eq!(connect.time(), time);
-    assert!(connect.verify_signature(&public_key));
+    assert!(connect.verify_signature(&chain_id, &public_key));
 }
 
 #[test]
@@ -384,9 +450,11 @@ fn test_propose() {
     let prev_hash = hash(&[1, 2, 3]);
     let txs = vec![hash(&[1]), hash(&[2]), hash(&[2])];
     let (public_key, secret_key) = gen_keypair();
+    let chain_id = chain_id(DEFAULT_NETWORK);
 
     // write
-    let propose = Propose::new(VALIDATOR, HEIGHT, ROUND, &prev_hash, &txs, &secret_key);
+    let propose =
+        Propose::new(VALIDATOR, HEIGHT, ROUND, &prev_hash, &txs, &chain_id, &secret_key).unwrap();
     // read
     assert_eq!(propose.validator(), VALIDATOR);
     assert_eq!(propose.height(), HEIGHT);
@@ -396,7 +464,7 @@ fn test_propose() {
     assert_eq!(propose.transactions()[0], txs[0]);
     assert_eq!(propose.transactions()[1], txs[1]);
     assert_eq!(propose.transactions()[2], txs[2]);
-    assert!(propose.verify_signature(&public_key));
+    assert!(propose.verify_signature(&chain_id, &public_key));
 }
 
 #[test]
@@ -404,6 +472,7 @@ fn test_prevote() {
     let propose_hash = hash(&[1, 2, 3]);
     let locked_round = Round(654_345);
     let (public_key, secret_key) = gen_keypair();
+    let chain_id = chain_id(DEFAULT_NETWORK);
 
     // write
     let prevote = Prevote::new(
@@ -412,15 +481,16 @@ fn test_prevote() {
         ROUND,
         &propose_hash,
         locked_round,
+        &chain_id,
         &secret_key,
-    );
+    ).unwrap();
     // read
     assert_eq!(prevote.validator(), VALIDATOR);
     assert_eq!(prevote.height(), HEIGHT);
     assert_eq!(prevote.round(), ROUND);
     assert_eq!(prevote.propose_hash(), &propose_hash);
     assert_eq!(prevote.locked_round(), locked_round);
-    assert!(prevote.verify_signature(&public_key));
+    assert!(prevote.verify_signature(&chain_id, &public_key));
 }
 
 #[test]
@@ -428,6 +498,7 @@ fn test_precommit() {
     let propose_hash = hash(&[1, 2, 3]);
     let block_hash = hash(&[3, 2, 1]);
     let (public_key, secret_key) = gen_keypair();
+    let chain_id = chain_id(DEFAULT_NETWORK);
     let time = Utc::now();
 
     // write
@@ -438,15 +509,16 @@ fn test_precommit() {
         &propose_hash,
         &block_hash,
         time,
+        &chain_id,
         &secret_key,
-    );
+    ).unwrap();
     // read
     assert_eq!(precommit.validator(), VALIDATOR);
     assert_eq!(precommit.height(), HEIGHT);
     assert_eq!(precommit.round(), ROUND);
     assert_eq!(precommit.propose_hash(), &propose_hash);
     assert_eq!(precommit.block_hash(), &block_hash);
-    assert!(precommit.verify_signature(&public_key));
+    assert!(precommit.verify_signature(&chain_id, &public_key));
     assert_eq!(precommit.time(), time);
     let json_str = ::serde_json::to_string(&precommit).unwrap();
     assert!(json_str.len() > 0);
@@ -458,19 +530,21 @@ fn test_precommit() {
 fn test_status() {
     let last_hash = hash(&[3, 2, 1]);
     let (public_key, secret_key) = gen_keypair();
+    let chain_id = chain_id(DEFAULT_NETWORK);
 
     // write
-    let commit = Status::new(&public_key, HEIGHT, &last_hash, &secret_key);
+    let commit = Status::new(&public_key, HEIGHT, &last_hash, &chain_id, &secret_key).unwrap();
     // read
     assert_eq!(commit.from(), &public_key);
     assert_eq!(commit.height(), HEIGHT);
     assert_eq!(commit.last_hash(), &last_hash);
-    assert!(commit.verify_signature(&public_key));
+    assert!(commit.verify_signature(&chain_id, &public_key));
 }
 
 #[test]
 fn test_block() {
     let (pub_key, secret_key) = gen_keypair();
+    let chain_id = chain_id(DEFAULT_NETWORK);
     let ts = Utc::now();
     let txs = [2];
     let tx_count = txs.len() as u32;
@@ -493,8 +567,9 @@ fn test_block() {
             &hash(&[1, 2, 3]),
             &hash(&[3, 2, 1]),
             ts,
+            &chain_id,
             &secret_key,
-        ),
+        ).unwrap(),
         Precommit::new(
             ValidatorId(13),
             Height(25),
@@ -502,8 +577,9 @@ fn test_block() {
             &hash(&[4, 2, 3]),
             &hash(&[3, 3, 1]),
             ts,
+            &chain_id,
             &secret_key,
-        ),
+        ).unwrap(),
         Precommit::new(
             ValidatorId(323),
             Height(15),
@@ -511,17 +587,18 @@ fn test_block() {
             &hash(&[1, 1, 3]),
             &hash(&[5, 2, 1]),
             ts,
+            &chain_id,
             &secret_key,
-        ),
+        ).unwrap(),
     ];
     let transactions = vec![
-        Status::new(&pub_key, Height(2), &hash(&[]), &secret_key)
+        Status::new(&pub_key, Height(2), &hash(&[]), &chain_id, &secret_key).unwrap()
             .raw()
             .clone(),
-        Status::new(&pub_key, Height(4), &hash(&[2]), &secret_key)
+        Status::new(&pub_key, Height(4), &hash(&[2]), &chain_id, &secret_key).unwrap()
             .raw()
             .clone(),
-        Status::new(&pub_key, Height(7), &hash(&[3]), &secret_key)
+        Status::new(&pub_key, Height(7), &hash(&[3]), &chain_id, &secret_key).unwrap()
             .raw()
             .clone(),
     ];
@@ -531,8 +608,9 @@ fn test_block() {
         content.clone(),
         precommits.clone(),
         transactions.clone(),
+        &chain_id,
         &secret_key,
-    );
+    ).unwrap();
 
     assert_eq!(block.from(), &pub_key);
     assert_eq!(block.to(), &pub_key);
@@ -558,6 +636,7 @@ fn test_block() {
 #[test]
 fn test_empty_block() {
     let (pub_key, secret_key) = gen_keypair();
+    let chain_id = chain_id(DEFAULT_NETWORK);
 
     let content = Block::new(
         blockchain::SCHEMA_MAJOR_VERSION,
@@ -577,8 +656,9 @@ fn test_empty_block() {
         content.clone(),
         precommits.clone(),
         transactions.clone(),
+        &chain_id,
         &secret_key,
-    );
+    ).unwrap();
 
     assert_eq!(block.from(), &pub_key);
     assert_eq!(block.to(), &pub_key);
@@ -597,14 +677,16 @@ fn test_empty_block() {
 #[test]
 fn test_request_block() {
     let (public_key, secret_key) = gen_keypair();
+    let chain_id = chain_id(DEFAULT_NETWORK);
 
     // write
-    let request = BlockRequest::new(&public_key, &public_key, Height(1), &secret_key);
+    let request =
+        BlockRequest::new(&public_key, &public_key, Height(1), &chain_id, &secret_key).unwrap();
     // read
     assert_eq!(request.from(), &public_key);
     assert_eq!(request.height(), Height(1));
     assert_eq!(request.to(), &public_key);
-    assert!(request.verify_signature(&public_key));
+    assert!(request.verify_signature(&chain_id, &public_key));
 }
 
 #[test]
@@ -642,3 +724,662 @@ fn test_correct_encoding_struct() {
     }
     drop(ThreeFields::new(0, 0, 0));
 }
+
+#[allow(dead_code)]
+mod layout {
+    use crypto::Hash;
+
+    encoding_layout! {
+        struct Header [size = 10, align = 2] {
+            reserved [0 => 1],
+            version: u8 [1 => 2],
+            message_type: u16 [2 => 4],
+            service_id: u16 [4 => 6],
+            payload_length: u32 [6 => 10],
+        }
+    }
+
+    encoding_layout! {
+        struct WithSegment [size = 48] {
+            height: u64 [0 => 8],
+            hash: &Hash [8 => 40],
+            name: &str [40 => 48]
+        }
+    }
+
+    encoding_layout! {
+        struct Padded [size = 16, align = 8] {
+            flag: bool [0 => 1],
+            reserved [1 => 8],
+            value: u64 [8 => 16],
+        }
+    }
+
+    encoding_layout! {
+        #![domain = Block]
+        struct Range [size = 24, align = 8] {
+            name: &str [0 => 8] where !name.is_empty(),
+            from: u64 [8 => 16],
+            to: u64 [16 => 24] where to < 1_000
+        }
+        where from <= to
+    }
+}
+
+#[test]
+fn test_encoding_layout() {
+    use self::layout::{Header, Padded, WithSegment};
+
+    let header = Header::new(1, 2, 128, 42);
+    assert_eq!(header.version(), 1);
+    assert_eq!(header.message_type(), 2);
+    assert_eq!(header.service_id(), 128);
+    assert_eq!(header.payload_length(), 42);
+    assert_write_check_read(header, 8);
+
+    let with_segment = WithSegment::new(42, &hash(&[1, 2, 3]), "layout");
+    assert_eq!(with_segment.name(), "layout");
+    assert_write_check_read(with_segment, 8);
+
+    let padded = Padded::new(true, u64::max_value());
+    assert_eq!(padded.value(), u64::max_value());
+    assert_write_check_read(padded, 8);
+}
+
+#[test]
+fn test_encoding_layout_rules_and_domain() {
+    use crypto::{CryptoHash, Domain};
+    use encoding::serialize::json::ExonumJsonDeserialize;
+    use storage::StorageValue;
+    use self::layout::Range;
+
+    let range = Range::new("range", 1, 2);
+    assert_eq!(CryptoHash::hash(&range), Domain::Block.hash(&range.clone().into_bytes()));
+    assert_write_check_read(range, 8);
+
+    let expect_validation_error = |value: Range, expected_field: &str| {
+        let mut buffer = vec![0; 8];
+        Field::write(&value, &mut buffer, 0, 8);
+        match <Range as Field>::check(&buffer, 0.into(), 8.into(), 8.into()) {
+            Err(Error::Validation { field, .. }) => assert_eq!(field, expected_field),
+            other => panic!("Unexpected check result: {:?}", other),
+        }
+    };
+    expect_validation_error(Range::new("", 1, 2), "name");
+    expect_validation_error(Range::new("range", 1, 1_000), "to");
+    expect_validation_error(Range::new("range", 3, 2), "Range");
+
+    let value = ::serde_json::from_str(r#"{ "name": "range", "from": "3", "to": "2" }"#).unwrap();
+    assert!(<Range as ExonumJsonDeserialize>::deserialize(&value).is_err());
+}
+
+#[test]
+fn test_encoding_layout_reserved_bytes() {
+    use storage::StorageValue;
+    use self::layout::Padded;
+
+    let mut buffer = vec![0; 8];
+    Field::write(&Padded::new(false, 1), &mut buffer, 0, 8);
+    <Padded as Field>::check(&buffer, 0.into(), 8.into(), 8.into()).unwrap();
+
+    // Header of `Padded` starts right after the segment pointer.
+    buffer[8 + 5] = 7;
+    match <Padded as Field>::check(&buffer, 0.into(), 8.into(), 8.into()) {
+        Err(Error::IncorrectReservedBytes { position, value }) => {
+            assert_eq!(position, 5);
+            assert_eq!(value, 7);
+        }
+        other => panic!("Unexpected check result: {:?}", other),
+    }
+
+    let raw = Padded::new(true, 2).into_bytes();
+    assert_eq!(&raw[1..8], &[0; 7]);
+}
+
+#[test]
+fn test_struct_hash_domain() {
+    use crypto::{CryptoHash, Domain};
+    use storage::StorageValue;
+
+    let child = Child::new(&hash(&[1, 2, 3]));
+    let expected = Domain::Struct.hash(&child.clone().into_bytes());
+    assert_eq!(CryptoHash::hash(&child), expected);
+    assert_ne!(CryptoHash::hash(&child), hash(&child.into_bytes()));
+}
+
+#[allow(dead_code)]
+mod rules {
+    encoding_struct! {
+        struct Range {
+            name: &str where !name.is_empty(),
+            from: u64,
+            to: u64 where to < 1_000,
+        }
+        where from <= to
+    }
+}
+
+#[test]
+fn test_encoding_struct_rules() {
+    use self::rules::Range;
+
+    assert_write_check_read(Range::new("range", 1, 2), 8);
+
+    let expect_validation_error = |value: Range, expected_field: &str| {
+        let mut buffer = vec![0; 8];
+        Field::write(&value, &mut buffer, 0, 8);
+        match <Range as Field>::check(&buffer, 0.into(), 8.into(), 8.into()) {
+            Err(Error::Validation { field, .. }) => assert_eq!(field, expected_field),
+            other => panic!("Unexpected check result: {:?}", other),
+        }
+    };
+    expect_validation_error(Range::new("", 1, 2), "name");
+    expect_validation_error(Range::new("range", 1, 1_000), "to");
+    expect_validation_error(Range::new("range", 3, 2), "Range");
+}
+
+#[test]
+fn test_encoding_struct_rules_json() {
+    use encoding::serialize::json::ExonumJsonDeserialize;
+    use self::rules::Range;
+
+    let value = ::serde_json::from_str(r#"{ "name": "range", "from": "3", "to": "2" }"#).unwrap();
+    assert!(<Range as ExonumJsonDeserialize>::deserialize(&value).is_err());
+    let value = ::serde_json::from_str(r#"{ "name": "range", "from": "2", "to": "3" }"#).unwrap();
+    assert!(<Range as ExonumJsonDeserialize>::deserialize(&value).is_ok());
+}
+
+#[test]
+fn test_prevote_rules() {
+    use messages::Prevote;
+
+    let prevote = Prevote::new(VALIDATOR, HEIGHT, Round(2), &hash(&[]), Round(3));
+    let mut buffer = vec![0; 8];
+    Field::write(&prevote, &mut buffer, 0, 8);
+    match <Prevote as Field>::check(&buffer, 0.into(), 8.into(), 8.into()) {
+        Err(Error::Validation { field, rule }) => {
+            assert_eq!(field, "Prevote");
+            assert_eq!(rule, "locked_round < round");
+        }
+        other => panic!("Unexpected check result: {:?}", other),
+    }
+}
+
+mod binary {
+    use std::collections::BTreeMap;
+
+    use crypto::{gen_keypair, PublicKey};
+
+    encoding_struct! {
+        struct Owner {
+            key: &PublicKey,
+            name: &str,
+        }
+    }
+
+    encoding_struct! {
+        struct Wallet {
+            owner: Owner,
+            balance: u64,
+            frozen: bool,
+            history: Vec<Owner>,
+            tags: &[u8],
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct PlainOwner {
+        pub key: PublicKey,
+        pub name: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct PlainWallet {
+        pub owner: PlainOwner,
+        pub balance: u64,
+        pub frozen: bool,
+        pub history: Vec<PlainOwner>,
+        pub tags: Vec<u8>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub enum Event {
+        Created,
+        Renamed(String),
+        Moved { from: u32, to: u32 },
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct Misc<'a> {
+        pub label: &'a str,
+        pub comment: Option<String>,
+        pub pair: (u8, i64),
+        pub events: Vec<Event>,
+        pub limits: BTreeMap<String, u16>,
+        pub symbol: char,
+    }
+
+    pub fn plain_owner(name: &str) -> PlainOwner {
+        PlainOwner {
+            key: gen_keypair().0,
+            name: name.to_owned(),
+        }
+    }
+
+    pub fn to_owner(owner: &PlainOwner) -> Owner {
+        Owner::new(&owner.key, &owner.name)
+    }
+}
+
+#[test]
+fn test_binary_serializer_matches_encoding_struct() {
+    use crypto::Hash;
+    use messages::{Propose, Status, WithoutEncodingStatus};
+    use storage::StorageValue;
+    use super::serialize::binary::{from_bytes, to_bytes};
+
+    let height = Height(0x0777_7777_7777_7777);
+    let last_hash = hash(&[1, 2, 3]);
+    let status = WithoutEncodingStatus { height, last_hash };
+    let bytes = to_bytes(&status).unwrap();
+    assert_eq!(bytes, Status::new(height, &last_hash).into_bytes());
+    assert_eq!(from_bytes::<WithoutEncodingStatus>(&bytes).unwrap(), status);
+
+    #[derive(Serialize)]
+    struct PlainPropose {
+        validator: ValidatorId,
+        height: Height,
+        round: Round,
+        prev_hash: Hash,
+        transactions: Vec<Hash>,
+    }
+    let transactions = vec![hash(&[1]), hash(&[2])];
+    let propose = PlainPropose {
+        validator: ValidatorId(1),
+        height: Height(2),
+        round: Round(3),
+        prev_hash: hash(&[0]),
+        transactions: transactions.clone(),
+    };
+    let expected = Propose::new(
+        ValidatorId(1),
+        Height(2),
+        Round(3),
+        &hash(&[0]),
+        &transactions,
+    );
+    assert_eq!(to_bytes(&propose).unwrap(), expected.into_bytes());
+}
+
+#[test]
+fn test_binary_serializer_nested_segments() {
+    use storage::StorageValue;
+    use self::binary::{plain_owner, to_owner, PlainWallet, Wallet};
+    use super::serialize::binary::{from_bytes, to_bytes};
+
+    let wallet = PlainWallet {
+        owner: plain_owner("Alice"),
+        balance: 1_000,
+        frozen: true,
+        history: vec![plain_owner("Bob"), plain_owner("Carol")],
+        tags: vec![1, 2, 3],
+    };
+    let expected = Wallet::new(
+        to_owner(&wallet.owner),
+        wallet.balance,
+        wallet.frozen,
+        wallet.history.iter().map(to_owner).collect(),
+        &wallet.tags,
+    );
+
+    let bytes = to_bytes(&wallet).unwrap();
+    assert_eq!(bytes, expected.clone().into_bytes());
+    assert_eq!(from_bytes::<PlainWallet>(&bytes).unwrap(), wallet);
+    assert_eq!(Wallet::from_bytes(bytes.into()), expected);
+}
+
+#[test]
+fn test_binary_serializer_round_trip() {
+    use std::collections::BTreeMap;
+    use self::binary::{Event, Misc};
+    use super::serialize::binary::{from_bytes, to_bytes};
+
+    let mut limits = BTreeMap::new();
+    limits.insert("daily".to_owned(), 10);
+    limits.insert("monthly".to_owned(), 300);
+    let value = Misc {
+        label: "misc",
+        comment: Some("comment".to_owned()),
+        pair: (7, -8),
+        events: vec![
+            Event::Created,
+            Event::Renamed("new".to_owned()),
+            Event::Moved { from: 1, to: 2 },
+        ],
+        limits,
+        symbol: 'λ',
+    };
+    let bytes = to_bytes(&value).unwrap();
+    assert_eq!(from_bytes::<Misc>(&bytes).unwrap(), value);
+
+    let empty = Misc {
+        comment: None,
+        events: Vec::new(),
+        limits: BTreeMap::new(),
+        ..value
+    };
+    let bytes = to_bytes(&empty).unwrap();
+    assert_eq!(from_bytes::<Misc>(&bytes).unwrap(), empty);
+
+    let bytes = to_bytes(&Event::Renamed("x".to_owned())).unwrap();
+    assert_eq!(&bytes[..4], &[1, 0, 0, 0]);
+    assert_eq!(
+        from_bytes::<Event>(&bytes).unwrap(),
+        Event::Renamed("x".to_owned())
+    );
+
+    let bytes = to_bytes(&42_u64).unwrap();
+    assert_eq!(bytes, vec![42, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(from_bytes::<u64>(&bytes).unwrap(), 42);
+}
+
+#[test]
+fn test_binary_serializer_incorrect_buffers() {
+    use messages::WithoutEncodingStatus;
+    use self::binary::{plain_owner, PlainWallet};
+    use super::serialize::binary::{from_bytes, to_bytes};
+
+    let status = WithoutEncodingStatus {
+        height: Height(1),
+        last_hash: hash(&[]),
+    };
+    let bytes = to_bytes(&status).unwrap();
+    match from_bytes::<WithoutEncodingStatus>(&bytes[..39]) {
+        Err(Error::UnexpectedlyShortPayload { .. }) => {}
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    let mut long = bytes.clone();
+    long.push(0);
+    assert!(from_bytes::<WithoutEncodingStatus>(&long).is_err());
+
+    let wallet = PlainWallet {
+        owner: plain_owner("Alice"),
+        balance: 1,
+        frozen: false,
+        history: Vec::new(),
+        tags: Vec::new(),
+    };
+    let mut bytes = to_bytes(&wallet).unwrap();
+    // `frozen` field.
+    bytes[16] = 2;
+    match from_bytes::<PlainWallet>(&bytes) {
+        Err(Error::IncorrectBoolean { position: 16, value: 2 }) => {}
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    // Segment of `owner` is out of the buffer.
+    bytes[16] = 0;
+    bytes[0] = 0xff;
+    match from_bytes::<PlainWallet>(&bytes) {
+        Err(Error::IncorrectSegmentReference { position: 0, .. }) => {}
+        other => panic!("Unexpected result: {:?}", other),
+    }
+
+    let mut bytes = to_bytes(&("a".to_owned(), "b".to_owned())).unwrap();
+    assert_eq!(&bytes[16..], b"ab");
+    // Both segments point to the same byte.
+    bytes[8] = 16;
+    match from_bytes::<(String, String)>(&bytes) {
+        Err(Error::OverlappingSegment { last_end: 17, start: 16 }) => {}
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    // Byte between segments.
+    bytes[8] = 17;
+    bytes[12] = 0;
+    match from_bytes::<(String, String)>(&bytes) {
+        Err(Error::Basic(_)) => {}
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    // Segments in the reverse order of the pointers, which `Field::check` rejects as well.
+    bytes[0] = 17;
+    bytes[8] = 16;
+    bytes[12] = 1;
+    match from_bytes::<(String, String)>(&bytes) {
+        Err(Error::SpaceBetweenSegments { last_end: 16, start: 17 }) => {}
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    match <&str as Field>::check(&bytes, 0.into(), 8.into(), 16.into()) {
+        Err(Error::SpaceBetweenSegments { last_end: 16, start: 17 }) => {}
+        other => panic!("Unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_binary_serializer_fixed_size_names() {
+    use crypto::HASH_SIZE;
+    use super::serialize::binary::{from_bytes, to_bytes};
+
+    // Only the crypto types are written in place.
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Hash(Vec<u8>);
+
+    let value = Hash(vec![1, 2, 3]);
+    let bytes = to_bytes(&value).unwrap();
+    assert_eq!(bytes, to_bytes(&vec![1_u8, 2, 3]).unwrap());
+    assert_eq!(from_bytes::<Hash>(&bytes).unwrap(), value);
+
+    let bytes = to_bytes(&hash(&[1, 2, 3])).unwrap();
+    assert_eq!(bytes.len(), HASH_SIZE);
+    assert_eq!(from_bytes::<::crypto::Hash>(&bytes).unwrap(), hash(&[1, 2, 3]));
+}
+
+#[test]
+fn test_json_error_pointers() {
+    use serde_json::Value;
+    use encoding::serialize::json::{ExonumJsonDeserialize, JsonErrorKind};
+    use self::binary::{Owner, Wallet};
+
+    let (key, _) = gen_keypair();
+    let wallet = Wallet::new(
+        Owner::new(&key, "Alice"),
+        100,
+        false,
+        vec![Owner::new(&key, "Bob"), Owner::new(&key, "Carol")],
+        &[1, 2],
+    );
+    let json = ::serde_json::to_value(&wallet).unwrap();
+    let deserialize = |value: &Value| <Wallet as ExonumJsonDeserialize>::deserialize(value);
+    assert_eq!(deserialize(&json).unwrap(), wallet);
+
+    let mut changed = json.clone();
+    changed.as_object_mut().unwrap().remove("balance");
+    let err = deserialize(&changed).unwrap_err();
+    assert_eq!(err.pointer(), "/balance");
+    assert_eq!(*err.kind(), JsonErrorKind::MissingField);
+
+    let mut changed = json.clone();
+    changed["owner"]["name"] = Value::from(1);
+    let err = deserialize(&changed).unwrap_err();
+    assert_eq!(err.pointer(), "/owner/name");
+    assert_eq!(*err.kind(), JsonErrorKind::WrongType { expected: "string" });
+
+    let mut changed = json.clone();
+    changed["history"][1]["key"] = Value::from("zz");
+    let err = deserialize(&changed).unwrap_err();
+    assert_eq!(err.pointer(), "/history/1/key");
+    match *err.kind() {
+        JsonErrorKind::InvalidHex(_) => {}
+        ref other => panic!("Unexpected error kind: {:?}", other),
+    }
+
+    let mut changed = json.clone();
+    changed["balance"] = Value::from("18446744073709551616");
+    let err = deserialize(&changed).unwrap_err();
+    assert_eq!(err.pointer(), "/balance");
+    assert_eq!(
+        *err.kind(),
+        JsonErrorKind::OutOfRange {
+            value: "18446744073709551616".to_string(),
+            type_name: "u64",
+        }
+    );
+
+    // Errors are surfaced through serde as well.
+    let err = ::serde_json::from_value::<Wallet>(changed).unwrap_err();
+    assert!(err.to_string().contains("/balance"), "{}", err);
+}
+
+#[test]
+fn test_json_config_round_trip() {
+    use encoding::serialize::json::{ExonumJson, ExonumJsonDeserialize};
+    use encoding::serialize::text::{BinaryEncoding, JsonConfig};
+    use self::binary::{Owner, Wallet};
+
+    let (key, _) = gen_keypair();
+    let wallet = Wallet::new(
+        Owner::new(&key, "Alice"),
+        100,
+        false,
+        vec![Owner::new(&key, "Bob")],
+        &[0xfb, 0xff],
+    );
+    let config = JsonConfig::new()
+        .with_bytes(BinaryEncoding::Base64)
+        .with_field("history", BinaryEncoding::Base58);
+
+    let json = wallet.serialize_field_with(&config).unwrap();
+    assert_eq!(json["tags"], "+/8=");
+    assert_eq!(json["owner"]["key"], BinaryEncoding::Hex.encode(key.as_ref()));
+    assert_eq!(
+        json["history"][0]["key"],
+        BinaryEncoding::Base58.encode(key.as_ref())
+    );
+    let result = <Wallet as ExonumJsonDeserialize>::deserialize_with(&json, &config).unwrap();
+    assert_eq!(result, wallet);
+
+    // Default configuration expects hex.
+    let err = <Wallet as ExonumJsonDeserialize>::deserialize(&json).unwrap_err();
+    assert_eq!(err.pointer(), "/history/0/key");
+}
+
+#[test]
+fn test_json_public_key_addresses() {
+    use serde_json::Value;
+    use crypto::{AddressError, PublicKeyAddress};
+    use encoding::serialize::json::{ExonumJson, ExonumJsonDeserialize, JsonErrorKind};
+    use encoding::serialize::text::{BinaryEncoding, JsonConfig};
+    use self::binary::{Owner, Wallet};
+
+    let (key, _) = gen_keypair();
+    let wallet = Wallet::new(
+        Owner::new(&key, "Alice"),
+        100,
+        false,
+        vec![Owner::new(&key, "Bob")],
+        &[1, 2],
+    );
+    let config = JsonConfig::new()
+        .with_addresses("exonum")
+        .with_field("history", BinaryEncoding::Base58);
+
+    let json = wallet.serialize_field_with(&config).unwrap();
+    let address = PublicKeyAddress::new("exonum", key).unwrap();
+    assert_eq!(json["owner"]["key"], Value::from(address.to_string()));
+    assert_eq!(json["history"][0]["key"], BinaryEncoding::Base58.encode(key.as_ref()));
+    let result = <Wallet as ExonumJsonDeserialize>::deserialize_with(&json, &config).unwrap();
+    assert_eq!(result, wallet);
+
+    let testnet = JsonConfig::new().with_addresses("testnet");
+    let err = <Wallet as ExonumJsonDeserialize>::deserialize_with(&json, &testnet).unwrap_err();
+    assert_eq!(err.pointer(), "/owner/key");
+    match *err.kind() {
+        JsonErrorKind::InvalidAddress(AddressError::NetworkMismatch { .. }) => {}
+        ref other => panic!("Unexpected error kind: {:?}", other),
+    }
+
+    // A single mistyped character fails the checksum.
+    let mut changed = json.clone();
+    let mut mistyped = address.to_string();
+    let last = if mistyped.ends_with('q') { "p" } else { "q" };
+    mistyped.pop();
+    mistyped.push_str(last);
+    changed["owner"]["key"] = Value::from(mistyped);
+    let err = <Wallet as ExonumJsonDeserialize>::deserialize_with(&changed, &config).unwrap_err();
+    assert_eq!(*err.kind(), JsonErrorKind::InvalidAddress(AddressError::InvalidChecksum));
+
+    let invalid = JsonConfig::new().with_addresses("Exonum");
+    assert!(wallet.serialize_field_with(&invalid).is_err());
+}
+
+#[test]
+fn test_containers_json_round_trip() {
+    use std::collections::{BTreeMap, HashMap};
+    use serde_json::Value;
+    use encoding::serialize::json::{ExonumJsonDeserialize, ExonumJsonSerialize};
+    use encoding::serialize::text::BinaryEncoding;
+    use self::binary::Owner;
+
+    fn round_trip<T>(value: &T, expected: &str)
+    where
+        T: ExonumJsonSerialize + ExonumJsonDeserialize + PartialEq + ::std::fmt::Debug,
+    {
+        let json = value.serialize_json().unwrap();
+        assert_eq!(json, ::serde_json::from_str::<Value>(expected).unwrap());
+        assert_eq!(&T::deserialize(&json).unwrap(), value);
+    }
+
+    let (key, _) = gen_keypair();
+    let owner = r#"{ "key": "KEY", "name": "Alice" }"#
+        .replace("KEY", &BinaryEncoding::Hex.encode(key.as_ref()));
+    round_trip(&None::<u64>, "null");
+    round_trip(&Some(5_u64), r#""5""#);
+    round_trip(&Some(Owner::new(&key, "Alice")), &owner);
+    round_trip(&(1_u32, -2_i64, vec![1_u8, 2]), r#"[1, "-2", [1, 2]]"#);
+
+    let balances = vec![(2, 200), (1, 100)].into_iter().collect::<BTreeMap<u32, u64>>();
+    round_trip(&balances, r#"{ "1": "100", "2": "200" }"#);
+    let blobs = vec![(7, vec![1, 2]), (3, vec![])]
+        .into_iter()
+        .collect::<HashMap<u64, Vec<u8>>>();
+    round_trip(&blobs, r#"{ "3": [], "7": [1, 2] }"#);
+
+    let err = <BTreeMap<u32, u64> as ExonumJsonDeserialize>::deserialize(
+        &::serde_json::from_str(r#"{ "1": "100", "x": "1" }"#).unwrap(),
+    ).unwrap_err();
+    assert_eq!(err.pointer(), "/x");
+    let err = <(u32, i64) as ExonumJsonDeserialize>::deserialize(&Value::from(vec![1])).unwrap_err();
+    assert_eq!(err.pointer(), "");
+}
+
+#[test]
+fn test_raw_messages_json_round_trip() {
+    use encoding::serialize::json::ExonumJsonDeserialize;
+
+    let (pub_key, sec_key) = gen_keypair();
+    let chain_id = chain_id(DEFAULT_NETWORK);
+    let status = Status::new(&pub_key, Height(2), &hash(&[]), &chain_id, &sec_key).unwrap();
+    let response = BlockResponse::new(
+        &pub_key,
+        &pub_key,
+        Block::new(
+            blockchain::SCHEMA_MAJOR_VERSION,
+            ValidatorId::zero(),
+            Height(2),
+            1,
+            &hash(&[1]),
+            &hash(&[2]),
+            &hash(&[3]),
+        ),
+        vec![],
+        vec![status.raw().clone()],
+        &chain_id,
+        &sec_key,
+    ).unwrap();
+
+    let json = ::serde_json::to_value(&response).unwrap();
+    let transaction = &json["body"]["transactions"][0];
+    assert_eq!(transaction["message_id"], ::serde_json::Value::from(status.raw().message_type()));
+    assert_eq!(transaction["signature"], ::serde_json::Value::from(status.raw().signature().to_hex()));
+    let result = <BlockResponse as ExonumJsonDeserialize>::deserialize(&json).unwrap();
+    assert_eq!(result, response);
+    assert_eq!(result.transactions(), vec![status.raw().clone()]);
+}
@@ -5,6 +5,7 @@ extern crate failure;
 extern crate bit_vec;
 extern crate hex;
 extern crate exonum_sodiumoxide as sodiumoxide;
+extern crate libsodium_sys;
 
 extern crate byteorder;
 extern crate vec_map;
@@ -18,6 +19,8 @@ extern crate uuid;
 extern crate chrono;
 
 extern crate rust_decimal;
+#[cfg(test)]
+extern crate rand;
 
 #[macro_use]
 pub mod encoding;
@@ -45,10 +48,93 @@ fn output_hex<T: serde::Serialize>(val: T) -> String {
         .no_limit()
         .serialize(&val).expect("Could not serialize SignedMessage."))
 }
+// Prints JSON Schema of the message type with the given name, or of all messages.
+fn print_schemas(name: Option<&str>) {
+    let schemas = messages::json_schemas();
+    let output = match name {
+        Some(name) => match schemas.get(name) {
+            Some(schema) => schema.clone(),
+            None => {
+                let known = schemas.keys().cloned().collect::<Vec<_>>().join(", ");
+                eprintln!("Unknown type `{}`, known types: {}", name, known);
+                ::std::process::exit(1);
+            }
+        },
+        None => serde_json::to_value(&schemas).expect("Could not serialize schemas."),
+    };
+    println!("{}", serde_json::to_string_pretty(&output).expect("Could not serialize schema."));
+}
+
+// Generates a random keypair and saves it to the key file encrypted under the passphrase
+// from the `EXONUM_KEY_PASSPHRASE` environment variable.
+fn keygen(path: Option<&str>) {
+    let path = path.unwrap_or_else(|| {
+        eprintln!("Usage: keygen <path>");
+        ::std::process::exit(1);
+    });
+    let passphrase = ::std::env::var("EXONUM_KEY_PASSPHRASE").unwrap_or_else(|_| {
+        eprintln!("The passphrase should be set in the `EXONUM_KEY_PASSPHRASE` variable");
+        ::std::process::exit(1);
+    });
+    let seed = crypto::gen_seed();
+    let (public_key, _) = crypto::gen_keypair_from_seed(&seed);
+    let result = crypto::EncryptedKey::encrypt_with_params(
+        &seed,
+        passphrase.as_bytes(),
+        crypto::KdfParams::sensitive(),
+    ).and_then(|encrypted| encrypted.write(path));
+    match result {
+        Ok(()) => println!("{}", public_key.to_hex()),
+        Err(e) => {
+            eprintln!("{}", e);
+            ::std::process::exit(1);
+        }
+    }
+}
+
+// Selects the hash algorithm given with `--hash-algorithm <name>` before the command,
+// returns the remaining arguments.
+fn select_hash_algorithm(args: Vec<String>) -> Vec<String> {
+    if args.first().map(String::as_str) != Some("--hash-algorithm") {
+        return args;
+    }
+    let algorithm = match args.get(1).map(|name| name.parse::<crypto::HashAlgorithm>()) {
+        Some(Ok(algorithm)) => algorithm,
+        Some(Err(e)) => {
+            eprintln!("{}", e);
+            ::std::process::exit(1);
+        }
+        None => {
+            eprintln!("Usage: --hash-algorithm <sha256|blake2b> [command]");
+            ::std::process::exit(1);
+        }
+    };
+    if let Err(current) = crypto::set_hash_algorithm(algorithm) {
+        eprintln!("Hash algorithm {} is already in use", current);
+        ::std::process::exit(1);
+    }
+    args[2..].to_vec()
+}
+
 fn main() {
-    use crypto::{Seed};
+    let args = select_hash_algorithm(::std::env::args().skip(1).collect());
+    if args.first().map(String::as_str) == Some("schema") {
+        return print_schemas(args.get(1).map(String::as_str));
+    }
+    if args.first().map(String::as_str) == Some("keygen") {
+        return keygen(args.get(1).map(String::as_str));
+    }
+    #[cfg(feature = "protobuf")]
+    {
+        if args.first().map(String::as_str) == Some("proto") {
+            return print!("{}", messages::protobuf::proto_description());
+        }
+    }
+
+    use crypto::{Seed, DEFAULT_NETWORK};
     use messages::{Message, Status, WithoutEncodingStatus};
     let (p, s) = crypto::gen_keypair_from_seed(&Seed::new([210; 32]));
+    let chain_id = crypto::chain_id(DEFAULT_NETWORK);
 
     let message = Message::new(
                         WithoutEncodingStatus {
@@ -56,20 +142,23 @@ fn main() {
                                     last_hash: Hash::zero()
                                 },
                                  p,
-                                 &s);
+                                 &chain_id,
+                                 &s).unwrap();
     println!("pk = {}",::hex::encode(p));
     println!("new status newstruct = {}",  message.to_hex_string());
     let message = Message::new(
         Status::new ( types::Height(0x7777777777777777), &Hash::zero()),
         p,
-        &s);
+        &chain_id,
+        &s).unwrap();
     println!("new status old struct = {}",  message.to_hex_string());
     {
         use old_messages::{Message, Status};
         let message = Status::new(&p,
                                   types::Height(0x7777777777777777),
                                   &Hash::zero(),
-                                  &s);
+                                  &chain_id,
+                                  &s).unwrap();
         println!("old status = {}",  ::hex::encode(message.raw()));
     }
 
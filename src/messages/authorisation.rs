@@ -5,12 +5,12 @@ use byteorder::{ByteOrder, LittleEndian};
 use bincode::Config;
 use serde::{Serialize, Deserialize};
 
-use crypto::{self, hash, CryptoHash, Hash, PublicKey, SecretKey, Signature,
+use crypto::{self, CryptoHash, Domain, Hash, PublicKey, Signature, Signer,
              SIGNATURE_LENGTH, PUBLIC_KEY_LENGTH};
 use messages::Message;
 use storage::StorageValue;
 
-use super::protocol::{Protocol, ProtocolMessage};
+use super::protocol::{FromProtocol, Protocol, ProtocolMessage};
 use super::{PROTOCOL_MAJOR_VERSION, MAX_MESSAGE_SIZE};
 
 use encoding::serialize::encode_hex;
@@ -24,7 +24,7 @@ pub struct AuthorisedMessage {
 }
 
 impl AuthorisedMessage {
-    fn new<T: Into<Protocol>>(value: T, author: PublicKey) -> Result<Self, Error> {
+    pub(crate) fn new<T: Into<Protocol>>(value: T, author: PublicKey) -> Result<Self, Error> {
         Ok(AuthorisedMessage {
             reserved: 0,
             version: PROTOCOL_MAJOR_VERSION,
@@ -32,6 +32,15 @@ impl AuthorisedMessage {
             protocol: value.into(),
         })
     }
+
+    /// Returns the domain of the message hash and signature, which separates transactions
+    /// from the other messages.
+    pub(crate) fn domain(&self) -> Domain {
+        match self.protocol {
+            Protocol::Transaction(_) => Domain::Transaction,
+            _ => Domain::Message,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
@@ -41,12 +50,16 @@ pub struct SignedMessage {
 }
 
 impl SignedMessage {
-    pub fn new<T: Into<Protocol>>(value: T,
+    /// Signs the message in the network with the given chain id.
+    pub fn new<T: Into<Protocol>, S: Signer + ?Sized>(value: T,
                                        author: PublicKey,
-                                       secret_key: &SecretKey)
+                                       chain_id: &Hash,
+                                       signer: &S)
                                        -> Result<SignedMessage, Error> {
+        Self::check_author(&author, signer)?;
         let authorised_message = AuthorisedMessage::new(value, author)?;
-        let signature = Self::sign(&authorised_message, secret_key)?;
+        let domain = authorised_message.domain();
+        let signature = Self::sign(&authorised_message, domain, chain_id, signer)?;
 
         Ok(SignedMessage {
             authorised_message,
@@ -54,19 +67,103 @@ impl SignedMessage {
         })
     }
 
-    pub fn verify_buffer<T: AsRef<[u8]>>(buffer: T) -> Result<SignedMessage, Error> {
+    /// Deserializes the message and verifies its signature in the network with the given
+    /// chain id.
+    pub fn verify_buffer<T: AsRef<[u8]>>(buffer: T,
+                                         chain_id: &Hash)
+                                         -> Result<SignedMessage, Error> {
+        let message = Self::deserialize_unverified(buffer)?;
+        message.verify(chain_id)?;
+        Ok(message)
+    }
+
+    /// Verifies the signature of the message in the network with the given chain id.
+    pub fn verify(&self, chain_id: &Hash) -> Result<(), Error> {
         // TODO: external serialization library shadows any knowledge about internal
         // binary representation.
         // Sodium verify/sign api allows to work only with raw buffer.
         // This two factors lead to additional `serialize` inside verify
-        let buffer = buffer.as_ref();
-        let message: SignedMessage = ::bincode::config().no_limit().deserialize(&buffer)?;
-        Self::verify(&message.authorised_message,
-                     &message.signature,
-                    &message.authorised_message.author)?;
+        let full_buffer = ::bincode::config().no_limit().serialize(&self.authorised_message)?;
+        if !self.hash_domain().verify(chain_id,
+                                      &self.signature,
+                                      &full_buffer,
+                                      &self.authorised_message.author) {
+            bail!("Can't verify message.");
+        }
+        Ok(())
+    }
+
+    /// Deserializes messages from the buffers and verifies their signatures in a batch.
+    /// Returns indices of the buffers which don't contain correctly signed messages.
+    pub fn verify_buffers<T: AsRef<[u8]>>(buffers: &[T], chain_id: &Hash)
+                                          -> Result<Vec<SignedMessage>, Vec<usize>> {
+        let mut messages = Vec::with_capacity(buffers.len());
+        let mut failed = Vec::new();
+        for (i, buffer) in buffers.iter().enumerate() {
+            match Self::deserialize_unverified(buffer) {
+                Ok(message) => messages.push((i, message)),
+                Err(_) => failed.push(i),
+            }
+        }
+
+        let (indices, messages): (Vec<_>, Vec<_>) = messages.into_iter().unzip();
+        if let Err(invalid) = Self::verify_batch(&messages, chain_id) {
+            failed.extend(invalid.into_iter().map(|i| indices[i]));
+            failed.sort();
+        }
+        if failed.is_empty() {
+            Ok(messages)
+        } else {
+            Err(failed)
+        }
+    }
+
+    /// Verifies signatures of the messages in a batch.
+    /// Returns indices of the messages with incorrect signatures.
+    pub fn verify_batch(messages: &[SignedMessage], chain_id: &Hash) -> Result<(), Vec<usize>> {
+        let buffers = messages.iter()
+            .map(|message| {
+                let buffer = ::bincode::config()
+                    .no_limit()
+                    .serialize(&message.authorised_message)
+                    .expect("Could not serialize AuthorisedMessage.");
+                message.hash_domain().prefixed(chain_id, &buffer)
+            })
+            .collect::<Vec<_>>();
+        let items = messages.iter()
+            .zip(&buffers)
+            .map(|(message, buffer)| {
+                (buffer.as_ref(), &message.signature, &message.authorised_message.author)
+            })
+            .collect::<Vec<_>>();
+        crypto::verify_batch(&items)
+    }
+
+    /// Deserializes the message without verifying its signature.
+    pub(crate) fn deserialize_unverified<T: AsRef<[u8]>>(buffer: T)
+                                                     -> Result<SignedMessage, Error> {
+        let message = ::bincode::config().no_limit().deserialize(buffer.as_ref())?;
         Ok(message)
     }
 
+    /// Assembles the message without verifying its signature.
+    pub(crate) fn from_parts(version: u8,
+                             author: PublicKey,
+                             protocol: Protocol,
+                             signature: Signature)
+                             -> SignedMessage {
+        let authorised_message = AuthorisedMessage {
+            reserved: 0,
+            version,
+            author,
+            protocol,
+        };
+        SignedMessage {
+            authorised_message,
+            signature,
+        }
+    }
+
     pub fn to_vec(&self) -> Vec<u8> {
         ::bincode::config().no_limit().serialize(&self).expect("Could not serialize SignedMessage.")
     }
@@ -82,29 +179,38 @@ impl SignedMessage {
         }
     }
 
-    fn sign<T: Serialize>(val: &T, secret_key: &SecretKey) -> Result<Signature, Error> {
+    // Messages of an author could be signed only with the key of the author.
+    pub(crate) fn check_author<S: Signer + ?Sized>(author: &PublicKey,
+                                                   signer: &S)
+                                                   -> Result<(), Error> {
+        if *author != signer.public_key() {
+            bail!("Author {:?} doesn't match the public key of the signer", author);
+        }
+        Ok(())
+    }
+
+    fn sign<T: Serialize, S: Signer + ?Sized>(val: &T,
+                                              domain: Domain,
+                                              chain_id: &Hash,
+                                              signer: &S)
+                                              -> Result<Signature, Error> {
         let full_buffer = ::bincode::config().no_limit().serialize(&val)?;
-        let signature = crypto::sign(&full_buffer, secret_key);
-        Ok(signature)
+        domain.sign(chain_id, &full_buffer, signer)
+    }
+
+    /// Returns the domain of the message hash and signature, which separates transactions
+    /// from the other messages.
+    fn hash_domain(&self) -> Domain {
+        self.authorised_message.domain()
     }
 
     fn hash(&self) -> Hash {
-        hash(&::bincode::config()
+        self.hash_domain().hash(&::bincode::config()
                 .no_limit()
                 .serialize(self)
                 .expect("Expected serialize to work"))
     }
 
-    fn verify<T: Serialize>(val: &T, signature: &Signature, public_key: &PublicKey) -> Result<(), Error> {
-        let full_buffer = ::bincode::config().no_limit().serialize(&val)?;
-        if !crypto::verify(signature,
-                           &full_buffer,
-                           &public_key) {
-            bail!("Can't verify message.");
-        }
-        Ok(())
-    }
-
 }
 
 
@@ -113,8 +219,9 @@ impl StorageValue for SignedMessage {
         self.to_vec()
     }
 
+    // Messages are verified before they are stored.
     fn from_bytes(value: Cow<[u8]>) -> Self {
-        SignedMessage::verify_buffer(&value).unwrap()
+        SignedMessage::deserialize_unverified(&value).unwrap()
     }
 }
 
@@ -124,18 +231,242 @@ impl CryptoHash for SignedMessage {
     }
 }
 
-impl<T: ProtocolMessage> StorageValue for Message<T> {
+impl<T: ProtocolMessage + FromProtocol> StorageValue for Message<T> {
     fn into_bytes(self) -> Vec<u8> {
-        unimplemented!()
+        self.message.into_bytes()
     }
 
     fn from_bytes(value: Cow<[u8]>) -> Self {
-        unimplemented!()
+        let message = SignedMessage::from_bytes(value);
+        let payload = T::from_protocol(message.authorised_message.protocol.clone())
+            .expect("Stored message has another type");
+        Message { payload, message }
     }
 }
 
 impl<T: ProtocolMessage> CryptoHash for Message<T> {
     fn hash(&self) -> Hash {
-        unimplemented!()
+        self.message.hash()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use byteorder::{ByteOrder, LittleEndian};
+    use chrono::{TimeZone, Utc};
+
+    use crypto::{chain_id, gen_keypair_from_seed, hash, CryptoHash, Domain, Hash, InMemorySigner,
+                 PublicKey, Seed, Signature, Signer, DEFAULT_NETWORK};
+    use encoding::{Error, Field, SegmentField};
+    use messages::{Block, BlockResponse, Message, Precommit, Protocol, RawTransaction, Status,
+                   TransactionsResponse, WithoutEncodingStatus};
+    use storage::StorageValue;
+    use types::{Height, Round, ValidatorId};
+
+    use super::SignedMessage;
+
+    fn messages(count: u8) -> Vec<SignedMessage> {
+        (0..count)
+            .map(|i| {
+                let (public_key, secret_key) = gen_keypair_from_seed(&Seed::new([i; 32]));
+                let status = WithoutEncodingStatus {
+                    height: Height(u64::from(i)),
+                    last_hash: Hash::zero(),
+                };
+                SignedMessage::new(status, public_key, &chain_id(DEFAULT_NETWORK), &secret_key)
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    fn forge(message: &SignedMessage) -> SignedMessage {
+        SignedMessage {
+            signature: Signature::zero(),
+            ..message.clone()
+        }
+    }
+
+    #[test]
+    fn test_verify_buffers() {
+        let chain_id = chain_id(DEFAULT_NETWORK);
+        let messages = messages(4);
+        let mut buffers = messages.iter().map(SignedMessage::to_vec).collect::<Vec<_>>();
+        assert_eq!(SignedMessage::verify_buffers(&buffers, &chain_id), Ok(messages.clone()));
+        assert_eq!(SignedMessage::verify_batch(&messages, &chain_id), Ok(()));
+
+        // Signatures of one network are not valid in another.
+        let other_chain_id = ::crypto::chain_id("testnet");
+        assert_eq!(SignedMessage::verify_batch(&messages, &other_chain_id), Err(vec![0, 1, 2, 3]));
+        assert!(SignedMessage::verify_buffer(&buffers[0], &other_chain_id).is_err());
+
+        buffers[1] = forge(&messages[1]).to_vec();
+        buffers[3].truncate(10);
+        assert_eq!(SignedMessage::verify_buffers(&buffers, &chain_id), Err(vec![1, 3]));
+    }
+
+    #[test]
+    fn test_verify_signed_messages_of_segments_in_batch() {
+        let mut messages = messages(5);
+        messages[1] = forge(&messages[1]);
+        messages[4] = forge(&messages[4]);
+
+        // Segments are checked without the signatures, which depend on the network.
+        let response = TransactionsResponse::new(&PublicKey::zero(), messages);
+        let mut buffer = vec![0; 8];
+        response.clone().write(&mut buffer, 0, 8);
+        assert!(<TransactionsResponse as Field>::check(&buffer, 0.into(), 8.into(), 8.into())
+            .is_ok());
+
+        let response = unsafe { <TransactionsResponse as Field>::read(&buffer, 0, 8) };
+        let chain_id = chain_id(DEFAULT_NETWORK);
+        assert_eq!(response.verify_transactions(&chain_id), Err(vec![1, 4]));
+        let response = TransactionsResponse::new(&PublicKey::zero(), self::messages(3));
+        assert_eq!(response.verify_transactions(&chain_id), Ok(()));
+    }
+
+    #[test]
+    fn test_verify_precommits_of_block_response() {
+        let chain_id = chain_id(DEFAULT_NETWORK);
+        let precommits = (0..3)
+            .map(|i| {
+                let (public_key, secret_key) = gen_keypair_from_seed(&Seed::new([i; 32]));
+                let precommit = Precommit::new(
+                    ValidatorId(u16::from(i)),
+                    Height(2),
+                    Round(1),
+                    &hash(&[1]),
+                    &hash(&[2]),
+                    Utc.timestamp(1_500_000_000, 0),
+                );
+                SignedMessage::new(precommit, public_key, &chain_id, &secret_key).unwrap()
+            })
+            .collect::<Vec<_>>();
+        let block = Block::new(0, ValidatorId(0), Height(2), 0, &hash(&[]), &hash(&[]),
+                               &hash(&[]));
+        let response = BlockResponse::new(&PublicKey::zero(), block.clone(), precommits.clone(),
+                                          &[]);
+        assert_eq!(response.verify_precommits(&chain_id), Ok(()));
+        assert_eq!(response.verify_precommits(&::crypto::chain_id("testnet")),
+                   Err(vec![0, 1, 2]));
+
+        let mut tampered = precommits;
+        tampered[2] = forge(&tampered[2]);
+        let response = BlockResponse::new(&PublicKey::zero(), block, tampered, &[]);
+        assert_eq!(response.verify_precommits(&chain_id), Err(vec![2]));
+    }
+
+    #[test]
+    fn test_transaction_signature_does_not_verify_as_message() {
+        let chain_id = chain_id(DEFAULT_NETWORK);
+        let (public_key, secret_key) = gen_keypair_from_seed(&Seed::new([1; 32]));
+        let transaction = RawTransaction {
+            service_id: 1,
+            payload: vec![1, 2, 3],
+        };
+        let signed = SignedMessage::new(transaction, public_key, &chain_id, &secret_key).unwrap();
+        assert!(signed.verify(&chain_id).is_ok());
+        let buffer = ::bincode::config().no_limit().serialize(&signed.authorised_message).unwrap();
+        assert!(Domain::Transaction.verify(&chain_id, &signed.signature, &buffer, &public_key));
+        assert!(!Domain::Message.verify(&chain_id, &signed.signature, &buffer, &public_key));
+
+        // A signature of the same bytes in the message domain isn't accepted.
+        let forged = SignedMessage {
+            signature: Domain::Message.sign(&chain_id, &buffer, &secret_key).unwrap(),
+            ..signed.clone()
+        };
+        assert!(forged.verify(&chain_id).is_err());
+        assert_eq!(SignedMessage::verify_batch(&[signed, forged], &chain_id), Err(vec![1]));
+    }
+
+    #[test]
+    fn test_check_signed_message_segment_size() {
+        let mut buffer = messages(1)[0].to_vec();
+        let size = buffer.len() as u32;
+        assert!(<SignedMessage as SegmentField>::check_data(
+            &buffer, 0.into(), size.into(), size.into()).is_ok());
+
+        // Trailing bytes of the segment aren't consumed by the message.
+        buffer.push(0);
+        match <SignedMessage as SegmentField>::check_data(
+            &buffer, 0.into(), (size + 1).into(), (size + 1).into()) {
+            Err(Error::IncorrectSegmentSize { value, .. }) => assert_eq!(value, size + 1),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        let mut segments = vec![0; 8];
+        LittleEndian::write_u32(&mut segments[0..4], 8);
+        LittleEndian::write_u32(&mut segments[4..8], size + 1);
+        segments.extend_from_slice(&buffer);
+        match <SignedMessage as SegmentField>::check_segments(
+            &segments, 0.into(), 1.into(), 8.into()) {
+            Err(Error::IncorrectSegmentSize { position, .. }) => assert_eq!(position, 8),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_encoding_struct_payload_in_binary_form() {
+        let (public_key, secret_key) = gen_keypair_from_seed(&Seed::new([7; 32]));
+        let status = Status::new(Height(7), &Hash::zero());
+        let chain_id = chain_id(DEFAULT_NETWORK);
+        let signed =
+            SignedMessage::new(status.clone(), public_key, &chain_id, &secret_key).unwrap();
+        let buffer = signed.to_vec();
+        let raw = status.into_bytes();
+        assert_eq!(SignedMessage::verify_buffer(&buffer, &chain_id).unwrap(), signed);
+
+        // Raw buffer of the structure is checked, e.g. for its length.
+        let mut truncated = buffer.clone();
+        let position = buffer.windows(raw.len()).position(|w| w == &raw[..]).unwrap();
+        // Length of the bytes precedes them.
+        truncated[position - 8] -= 1;
+        truncated.remove(position);
+        assert!(SignedMessage::deserialize_unverified(&truncated).is_err());
+    }
+
+    #[test]
+    fn test_sign_with_signer() {
+        let (public_key, secret_key) = gen_keypair_from_seed(&Seed::new([9; 32]));
+        let signer: Box<Signer> = Box::new(InMemorySigner::new(public_key, secret_key.clone()));
+        let status = WithoutEncodingStatus {
+            height: Height(9),
+            last_hash: Hash::zero(),
+        };
+
+        let chain_id = chain_id(DEFAULT_NETWORK);
+
+        let signed =
+            SignedMessage::new(status.clone(), signer.public_key(), &chain_id, &signer).unwrap();
+        let expected = SignedMessage::new(status.clone(), public_key, &chain_id, &secret_key)
+            .unwrap();
+        assert_eq!(signed, expected);
+        assert_eq!(SignedMessage::verify_buffer(signed.to_vec(), &chain_id).unwrap(), expected);
+
+        let message = Message::new(status.clone(), public_key, &chain_id, &*signer).unwrap();
+        assert_eq!(message.to_hex_string(), expected.into_message().to_hex_string());
+
+        let (other_key, _) = gen_keypair_from_seed(&Seed::new([10; 32]));
+        assert!(SignedMessage::new(status.clone(), other_key, &chain_id, &signer).is_err());
+        assert!(Message::new(status, other_key, &chain_id, &*signer).is_err());
+    }
+
+    #[test]
+    fn test_message_storage_value_and_hash() {
+        let (public_key, secret_key) = gen_keypair_from_seed(&Seed::new([11; 32]));
+        let status = WithoutEncodingStatus {
+            height: Height(11),
+            last_hash: Hash::zero(),
+        };
+        let message =
+            Message::new(status, public_key, &chain_id(DEFAULT_NETWORK), &secret_key).unwrap();
+        let signed: SignedMessage = message.clone().into();
+        assert_eq!(CryptoHash::hash(&message), CryptoHash::hash(&signed));
+
+        let bytes = message.clone().into_bytes();
+        assert_eq!(bytes, signed.clone().into_bytes());
+        let restored = Message::<WithoutEncodingStatus>::from_bytes(bytes.clone().into());
+        assert_eq!(restored, message);
+        let restored = Message::<Protocol>::from_bytes(bytes.into());
+        assert_eq!(restored, signed.into_message());
     }
 }
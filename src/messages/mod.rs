@@ -13,12 +13,15 @@
 // limitations under the License.
 
 //! Consensus and other messages and related utilities.
+use std::collections::BTreeMap;
 use std::fmt::{self, Debug};
 use std::ops::Deref;
 
 use failure::Error;
+use serde_json::Value;
 
-use ::crypto::{PublicKey, SecretKey};
+use ::crypto::{Hash, PublicKey, Signer};
+use encoding::serialize::schema::{json_schema_document, object_schema, JsonSchema};
 
 pub use self::authorisation::SignedMessage;
 pub use self::protocol::*;
@@ -29,6 +32,8 @@ mod raw;
 mod protocol;
 mod authorisation;
 mod helpers;
+#[cfg(feature = "protobuf")]
+pub mod protobuf;
 
 /// Version of the protocol. Different versions are incompatible.
 pub const PROTOCOL_MAJOR_VERSION: u8 = 1;
@@ -45,6 +50,19 @@ impl RawTransaction {
     pub fn service_id(&self) -> u16 {
         self.service_id
     }
+
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+}
+
+impl JsonSchema for RawTransaction {
+    fn json_schema() -> Value {
+        object_schema("RawTransaction", vec![
+            ("service_id", <u16 as JsonSchema>::json_schema()),
+            ("payload", <Vec<u8> as JsonSchema>::json_schema()),
+        ])
+    }
 }
 
 impl fmt::Debug for RawTransaction {
@@ -67,15 +85,20 @@ where T: ProtocolMessage
 }
 
 impl<T: ProtocolMessage> Message<T> {
-    pub fn new(payload: T, author: PublicKey, secret_key: &SecretKey) -> Message<T> {
+    /// Signs the message in the network with the given chain id.
+    pub fn new<S: Signer + ?Sized>(payload: T,
+                                   author: PublicKey,
+                                   chain_id: &Hash,
+                                   signer: &S)
+                                   -> Result<Message<T>, Error> {
         let message = SignedMessage::new(payload.clone(),
                                          author,
-                                         secret_key)
-            .expect("Serialization error");
-        Message {
+                                         chain_id,
+                                         signer)?;
+        Ok(Message {
             payload,
             message
-        }
+        })
     }
 
     pub fn map<U, F>(self, func: F) -> Result<Message<U>, Error>
@@ -142,3 +165,24 @@ impl<T: ProtocolMessage> Deref for Message<T> {
         &self.payload
     }
 }
+
+/// Returns JSON Schema documents for the messages of the protocol, keyed by the type name.
+pub fn json_schemas() -> BTreeMap<&'static str, Value> {
+    let mut schemas = BTreeMap::new();
+    schemas.insert("Block", json_schema_document::<Block>());
+    schemas.insert("BlockRequest", json_schema_document::<BlockRequest>());
+    schemas.insert("BlockResponse", json_schema_document::<BlockResponse>());
+    schemas.insert("Connect", json_schema_document::<Connect>());
+    schemas.insert("PeersRequest", json_schema_document::<PeersRequest>());
+    schemas.insert("Precommit", json_schema_document::<Precommit>());
+    schemas.insert("Prevote", json_schema_document::<Prevote>());
+    schemas.insert("PrevotesRequest", json_schema_document::<PrevotesRequest>());
+    schemas.insert("Propose", json_schema_document::<Propose>());
+    schemas.insert("ProposeRequest", json_schema_document::<ProposeRequest>());
+    schemas.insert("RawTransaction", json_schema_document::<RawTransaction>());
+    schemas.insert("SignedMessage", json_schema_document::<SignedMessage>());
+    schemas.insert("Status", json_schema_document::<Status>());
+    schemas.insert("TransactionsRequest", json_schema_document::<TransactionsRequest>());
+    schemas.insert("TransactionsResponse", json_schema_document::<TransactionsResponse>());
+    schemas
+}
@@ -0,0 +1,1421 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Protobuf (proto3 wire format) representation of the protocol messages.
+//!
+//! The codec is hidden behind the `protobuf` feature gate. Description of the messages
+//! in the `.proto` language is generated by [`proto_description`], so clients in other
+//! languages could use the usual protobuf tooling.
+//!
+//! The encoder is deterministic: fields are written in the order of their numbers,
+//! singular scalar fields with the default value are omitted, and repeated scalars are packed.
+//! The decoder accepts any valid proto3 encoding: unknown fields are skipped,
+//! the last occurrence of a singular field wins and both packed and unpacked repeated
+//! scalars are supported.
+//!
+//! Messages of external clients are `ProtobufSignedMessage`s, which are signed over
+//! the protobuf bytes of the authorised message, see [`proto_description`] for the exact
+//! signed byte string. The bytes are kept as they were read, so the signature is verified
+//! whatever encoder the author used.
+//!
+//! `SignedMessage` is signed over the Exonum binary form instead and keeps its signature
+//! in protobuf as `BinarySignedMessage`. This is how the nested messages of
+//! `BlockResponse` and `TransactionsResponse`, signed by their own authors, are written.
+//!
+//! Signatures depend on the network, so the decoder doesn't verify them; use `verify`
+//! with the chain id of the network.
+//!
+//! [`proto_description`]: fn.proto_description.html
+
+use bit_vec::BitVec;
+use chrono::{DateTime, TimeZone, Utc};
+use failure::Error;
+
+use std::borrow::Cow;
+use std::net::SocketAddr;
+
+use crypto::{Hash, PublicKey, Signature, Signer};
+use types::{Height, Round, ValidatorId};
+use super::authorisation::AuthorisedMessage;
+use super::{Block, BlockRequest, BlockResponse, Connect, ConsensusMessage, PeersRequest,
+            Precommit, Prevote, PrevotesRequest, Propose, ProposeRequest, Protocol,
+            RawTransaction, RequestMessage, SignedMessage, Status, TransactionsRequest,
+            TransactionsResponse, WithoutEncodingStatus};
+
+/// Package of the generated `.proto` description.
+pub const PROTO_PACKAGE: &str = "exonum";
+
+const WIRE_VARINT: u8 = 0;
+const WIRE_FIXED64: u8 = 1;
+const WIRE_LENGTH_DELIMITED: u8 = 2;
+const WIRE_FIXED32: u8 = 5;
+
+// Varint takes at most 10 bytes for 64 bit values.
+const MAX_VARINT_LENGTH: usize = 10;
+
+/// Value of a field in the protobuf wire format.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WireValue<'a> {
+    /// Variable length integer.
+    Varint(u64),
+    /// Fixed size 64 bit value.
+    Fixed64(u64),
+    /// Length-delimited value: bytes, strings, embedded messages and packed repeated fields.
+    LengthDelimited(&'a [u8]),
+    /// Fixed size 32 bit value.
+    Fixed32(u32),
+}
+
+/// Writer of the protobuf wire format.
+#[derive(Debug, Default)]
+pub struct ProtobufWriter {
+    buffer: Vec<u8>,
+}
+
+impl ProtobufWriter {
+    /// Creates an empty writer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns written bytes.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buffer
+    }
+
+    /// Writes varint field.
+    pub fn write_varint(&mut self, number: u32, value: u64) {
+        self.write_tag(number, WIRE_VARINT);
+        write_varint(&mut self.buffer, value);
+    }
+
+    /// Writes length-delimited field.
+    pub fn write_bytes(&mut self, number: u32, value: &[u8]) {
+        self.write_tag(number, WIRE_LENGTH_DELIMITED);
+        write_varint(&mut self.buffer, value.len() as u64);
+        self.buffer.extend_from_slice(value);
+    }
+
+    /// Writes embedded message field.
+    pub fn write_message<T: ProtobufMessage>(&mut self, number: u32, value: &T) {
+        self.write_bytes(number, &value.to_protobuf());
+    }
+
+    fn write_tag(&mut self, number: u32, wire_type: u8) {
+        write_varint(&mut self.buffer, u64::from(number) << 3 | u64::from(wire_type));
+    }
+}
+
+fn write_varint(buffer: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buffer.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+fn read_varint(buffer: &[u8], pos: &mut usize) -> Result<u64, Error> {
+    let mut value = 0u64;
+    for i in 0..MAX_VARINT_LENGTH {
+        let byte = match buffer.get(*pos) {
+            Some(byte) => *byte,
+            None => bail!("Unexpected end of the buffer in varint"),
+        };
+        *pos += 1;
+        if i == MAX_VARINT_LENGTH - 1 && byte > 1 {
+            bail!("Varint overflows 64 bits");
+        }
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    bail!("Varint overflows 64 bits")
+}
+
+fn read_slice<'a>(buffer: &'a [u8], pos: &mut usize, len: usize) -> Result<&'a [u8], Error> {
+    if buffer.len() - *pos < len {
+        bail!("Unexpected end of the buffer: expected {} more bytes", len);
+    }
+    let slice = &buffer[*pos..*pos + len];
+    *pos += len;
+    Ok(slice)
+}
+
+/// Fields of a message in the protobuf wire format.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtobufFields<'a> {
+    fields: Vec<(u32, WireValue<'a>)>,
+}
+
+impl<'a> ProtobufFields<'a> {
+    /// Splits buffer into fields.
+    pub fn parse(buffer: &'a [u8]) -> Result<Self, Error> {
+        let mut fields = Vec::new();
+        let mut pos = 0;
+        while pos < buffer.len() {
+            let tag = read_varint(buffer, &mut pos)?;
+            let number = tag >> 3;
+            if number == 0 || number > u64::from(u32::max_value() >> 3) {
+                bail!("Incorrect field number {}", number);
+            }
+            let value = match tag as u8 & 0x07 {
+                WIRE_VARINT => WireValue::Varint(read_varint(buffer, &mut pos)?),
+                WIRE_FIXED64 => {
+                    let bytes = read_slice(buffer, &mut pos, 8)?;
+                    WireValue::Fixed64(
+                        bytes.iter().rev().fold(0, |acc, byte| acc << 8 | u64::from(*byte)),
+                    )
+                }
+                WIRE_LENGTH_DELIMITED => {
+                    let len = read_varint(buffer, &mut pos)?;
+                    if len > (buffer.len() - pos) as u64 {
+                        bail!("Length-delimited field {} is out of the buffer", number);
+                    }
+                    WireValue::LengthDelimited(read_slice(buffer, &mut pos, len as usize)?)
+                }
+                WIRE_FIXED32 => {
+                    let bytes = read_slice(buffer, &mut pos, 4)?;
+                    WireValue::Fixed32(
+                        bytes.iter().rev().fold(0, |acc, byte| acc << 8 | u32::from(*byte)),
+                    )
+                }
+                wire_type => bail!("Unsupported wire type {} of field {}", wire_type, number),
+            };
+            fields.push((number as u32, value));
+        }
+        Ok(ProtobufFields { fields })
+    }
+
+    /// Returns the last occurrence of the field.
+    pub fn last(&self, number: u32) -> Option<WireValue<'a>> {
+        self.fields
+            .iter()
+            .rev()
+            .find(|&&(n, _)| n == number)
+            .map(|&(_, value)| value)
+    }
+
+    /// Returns all occurrences of the field.
+    pub fn all<'b>(&'b self, number: u32) -> impl Iterator<Item = WireValue<'a>> + 'b {
+        self.fields
+            .iter()
+            .filter(move |&&(n, _)| n == number)
+            .map(|&(_, value)| value)
+    }
+
+    /// Returns the number of the last field from the given set, if any.
+    pub fn last_of(&self, numbers: &[u32]) -> Option<u32> {
+        self.fields
+            .iter()
+            .rev()
+            .map(|&(n, _)| n)
+            .find(|n| numbers.contains(n))
+    }
+}
+
+/// Field of a message, that could be written in the protobuf wire format.
+///
+/// Implemented for the borrowed types, returned by the getters of messages.
+pub trait ProtobufEncode {
+    /// Writes the field with the given number.
+    fn encode_field(&self, number: u32, writer: &mut ProtobufWriter);
+}
+
+/// Field of a message, that could be read from the protobuf wire format.
+pub trait ProtobufDecode: Sized {
+    /// Type of the field in the `.proto` description.
+    fn proto_type() -> Cow<'static, str>;
+
+    /// Reads a value of the field, `None` means that the field is absent.
+    fn decode_value(value: Option<WireValue>) -> Result<Self, Error>;
+
+    /// Reads the field with the given number.
+    fn decode_field(number: u32, fields: &ProtobufFields) -> Result<Self, Error> {
+        Self::decode_value(fields.last(number))
+    }
+}
+
+/// Message with protobuf representation.
+pub trait ProtobufMessage: Sized {
+    /// Returns description of the message.
+    fn descriptor() -> MessageDescriptor;
+
+    /// Writes fields of the message.
+    fn write_fields(&self, writer: &mut ProtobufWriter);
+
+    /// Reads message from the fields.
+    fn read_fields(fields: &ProtobufFields) -> Result<Self, Error>;
+
+    /// Returns protobuf representation of the message.
+    fn to_protobuf(&self) -> Vec<u8> {
+        let mut writer = ProtobufWriter::new();
+        self.write_fields(&mut writer);
+        writer.into_bytes()
+    }
+
+    /// Reads message from its protobuf representation.
+    fn from_protobuf(buffer: &[u8]) -> Result<Self, Error> {
+        Self::read_fields(&ProtobufFields::parse(buffer)?)
+    }
+}
+
+/// Description of a field in the `.proto` language.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDescriptor {
+    /// Number of the field.
+    pub number: u32,
+    /// Name of the field.
+    pub name: &'static str,
+    /// Type of the field, including the `repeated` label.
+    pub proto_type: Cow<'static, str>,
+}
+
+/// Description of a message in the `.proto` language.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageDescriptor {
+    /// Name of the message.
+    pub name: &'static str,
+    /// Name of the `oneof` wrapping all the fields, if any.
+    pub oneof: Option<&'static str>,
+    /// Fields of the message.
+    pub fields: Vec<FieldDescriptor>,
+}
+
+impl MessageDescriptor {
+    /// Returns the message in the `.proto` language.
+    pub fn to_proto(&self) -> String {
+        let indent = if self.oneof.is_some() { "    " } else { "  " };
+        let mut out = format!("message {} {{\n", self.name);
+        if let Some(oneof) = self.oneof {
+            out.push_str(&format!("  oneof {} {{\n", oneof));
+        }
+        for field in &self.fields {
+            out.push_str(&format!(
+                "{}{} {} = {};\n",
+                indent, field.proto_type, field.name, field.number
+            ));
+        }
+        if self.oneof.is_some() {
+            out.push_str("  }\n");
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Returns description of all the protocol messages in the `.proto` language.
+pub fn proto_description() -> String {
+    let descriptors = vec![
+        AuthorisedMessage::descriptor(),
+        SignedMessage::descriptor(),
+        Block::descriptor(),
+        BlockRequest::descriptor(),
+        BlockResponse::descriptor(),
+        Connect::descriptor(),
+        PeersRequest::descriptor(),
+        Precommit::descriptor(),
+        Prevote::descriptor(),
+        PrevotesRequest::descriptor(),
+        Propose::descriptor(),
+        ProposeRequest::descriptor(),
+        Protocol::descriptor(),
+        RawTransaction::descriptor(),
+        ProtobufSignedMessage::descriptor(),
+        Status::descriptor(),
+        TransactionsRequest::descriptor(),
+        TransactionsResponse::descriptor(),
+        WithoutEncodingStatus::descriptor(),
+    ];
+    let mut out = String::from(
+        "// Generated by `exonum::messages::protobuf::proto_description`, do not edit.\n\
+         //\n\
+         // `SignedMessage.signature` is the ed25519 signature of the byte string\n\
+         //\n\
+         //     domain || chain_id || authorised_message\n\
+         //\n\
+         // where `domain` is the tag 0x11 if the protocol is `transaction` and 0x10\n\
+         // for the other messages, `chain_id` is the 32 byte identifier of the network,\n\
+         // i.e. `hash(0x13 || network name)`, and `authorised_message` is the protobuf\n\
+         // encoding of `SignedMessage.authorised_message` exactly as it appears\n\
+         // on the wire.\n\
+         //\n\
+         // `BinarySignedMessage` is a message signed by its author in the Exonum binary\n\
+         // form, e.g. a pre-commit nested into `BlockResponse`. Its signature covers\n\
+         // `domain || chain_id || <Exonum binary form of authorised_message>`.\n\n",
+    );
+    out.push_str("syntax = \"proto3\";\n\n");
+    out.push_str(&format!("package {};\n\n", PROTO_PACKAGE));
+    out.push_str("import \"google/protobuf/timestamp.proto\";\n");
+    for descriptor in descriptors {
+        out.push('\n');
+        out.push_str(&descriptor.to_proto());
+    }
+    out
+}
+
+impl<'b, T: ProtobufEncode + ?Sized> ProtobufEncode for &'b T {
+    fn encode_field(&self, number: u32, writer: &mut ProtobufWriter) {
+        (**self).encode_field(number, writer)
+    }
+}
+
+macro_rules! impl_protobuf_uint {
+    ($($typename:ty => $proto_type:expr, $max:expr);*) => {$(
+        impl ProtobufEncode for $typename {
+            fn encode_field(&self, number: u32, writer: &mut ProtobufWriter) {
+                if *self != 0 {
+                    writer.write_varint(number, u64::from(*self));
+                }
+            }
+        }
+
+        impl ProtobufDecode for $typename {
+            fn proto_type() -> Cow<'static, str> {
+                $proto_type.into()
+            }
+
+            fn decode_value(value: Option<WireValue>) -> Result<Self, Error> {
+                match value {
+                    None => Ok(0),
+                    Some(WireValue::Varint(value)) if value <= $max as u64 => {
+                        Ok(value as $typename)
+                    }
+                    Some(WireValue::Varint(value)) => {
+                        bail!("Value {} is out of range for {}", value, stringify!($typename))
+                    }
+                    Some(value) => bail!("Expected varint, found {:?}", value),
+                }
+            }
+        }
+    )*};
+}
+
+impl_protobuf_uint! {
+    u16 => "uint32", u16::max_value();
+    u32 => "uint32", u32::max_value();
+    u64 => "uint64", u64::max_value()
+}
+
+macro_rules! impl_protobuf_newtype {
+    ($($typename:ident($inner:ty));*) => {$(
+        impl ProtobufEncode for $typename {
+            fn encode_field(&self, number: u32, writer: &mut ProtobufWriter) {
+                self.0.encode_field(number, writer)
+            }
+        }
+
+        impl ProtobufDecode for $typename {
+            fn proto_type() -> Cow<'static, str> {
+                <$inner as ProtobufDecode>::proto_type()
+            }
+
+            fn decode_value(value: Option<WireValue>) -> Result<Self, Error> {
+                <$inner as ProtobufDecode>::decode_value(value).map($typename)
+            }
+        }
+    )*};
+}
+
+impl_protobuf_newtype! {
+    Height(u64);
+    Round(u32);
+    ValidatorId(u16)
+}
+
+fn bytes_from_wire<'a>(value: Option<WireValue<'a>>) -> Result<&'a [u8], Error> {
+    match value {
+        None => Ok(&[]),
+        Some(WireValue::LengthDelimited(bytes)) => Ok(bytes),
+        Some(value) => bail!("Expected length-delimited value, found {:?}", value),
+    }
+}
+
+macro_rules! impl_protobuf_crypto {
+    ($($typename:ident),*) => {$(
+        impl ProtobufEncode for $typename {
+            fn encode_field(&self, number: u32, writer: &mut ProtobufWriter) {
+                writer.write_bytes(number, self.as_ref())
+            }
+        }
+
+        impl ProtobufDecode for $typename {
+            fn proto_type() -> Cow<'static, str> {
+                "bytes".into()
+            }
+
+            fn decode_value(value: Option<WireValue>) -> Result<Self, Error> {
+                let bytes = bytes_from_wire(value)?;
+                match $typename::from_slice(bytes) {
+                    Some(value) => Ok(value),
+                    None => bail!(
+                        "Incorrect length {} of {}", bytes.len(), stringify!($typename)
+                    ),
+                }
+            }
+        }
+    )*};
+}
+
+impl_protobuf_crypto!(Hash, PublicKey, Signature);
+
+impl ProtobufEncode for [u8] {
+    fn encode_field(&self, number: u32, writer: &mut ProtobufWriter) {
+        if !self.is_empty() {
+            writer.write_bytes(number, self);
+        }
+    }
+}
+
+impl ProtobufDecode for Vec<u8> {
+    fn proto_type() -> Cow<'static, str> {
+        "bytes".into()
+    }
+
+    fn decode_value(value: Option<WireValue>) -> Result<Self, Error> {
+        Ok(bytes_from_wire(value)?.to_vec())
+    }
+}
+
+impl ProtobufEncode for str {
+    fn encode_field(&self, number: u32, writer: &mut ProtobufWriter) {
+        self.as_bytes().encode_field(number, writer)
+    }
+}
+
+impl ProtobufEncode for String {
+    fn encode_field(&self, number: u32, writer: &mut ProtobufWriter) {
+        self.as_str().encode_field(number, writer)
+    }
+}
+
+impl ProtobufDecode for String {
+    fn proto_type() -> Cow<'static, str> {
+        "string".into()
+    }
+
+    fn decode_value(value: Option<WireValue>) -> Result<Self, Error> {
+        Ok(String::from_utf8(bytes_from_wire(value)?.to_vec())?)
+    }
+}
+
+impl ProtobufEncode for SocketAddr {
+    fn encode_field(&self, number: u32, writer: &mut ProtobufWriter) {
+        self.to_string().encode_field(number, writer)
+    }
+}
+
+impl ProtobufDecode for SocketAddr {
+    fn proto_type() -> Cow<'static, str> {
+        String::proto_type()
+    }
+
+    fn decode_value(value: Option<WireValue>) -> Result<Self, Error> {
+        Ok(String::decode_value(value)?.parse()?)
+    }
+}
+
+/// Timestamp is written as the well known `google.protobuf.Timestamp` message.
+impl ProtobufEncode for DateTime<Utc> {
+    fn encode_field(&self, number: u32, writer: &mut ProtobufWriter) {
+        let mut timestamp = ProtobufWriter::new();
+        if self.timestamp() != 0 {
+            timestamp.write_varint(1, self.timestamp() as u64);
+        }
+        if self.timestamp_subsec_nanos() != 0 {
+            timestamp.write_varint(2, u64::from(self.timestamp_subsec_nanos()));
+        }
+        writer.write_bytes(number, &timestamp.into_bytes());
+    }
+}
+
+impl ProtobufDecode for DateTime<Utc> {
+    fn proto_type() -> Cow<'static, str> {
+        "google.protobuf.Timestamp".into()
+    }
+
+    fn decode_value(value: Option<WireValue>) -> Result<Self, Error> {
+        let fields = ProtobufFields::parse(bytes_from_wire(value)?)?;
+        let secs = u64::decode_field(1, &fields)? as i64;
+        let nanos = u64::decode_field(2, &fields)? as i64;
+        if nanos < 0 || nanos > i64::from(u32::max_value()) {
+            bail!("Timestamp nanos {} is out of range", nanos);
+        }
+        match Utc.timestamp_opt(secs, nanos as u32).single() {
+            Some(time) => Ok(time),
+            None => bail!("Timestamp {}.{} is out of range", secs, nanos),
+        }
+    }
+}
+
+/// Bit vector is written as a packed `repeated bool`.
+impl ProtobufEncode for BitVec {
+    fn encode_field(&self, number: u32, writer: &mut ProtobufWriter) {
+        if !self.is_empty() {
+            let bits = self.iter().map(u8::from).collect::<Vec<_>>();
+            writer.write_bytes(number, &bits);
+        }
+    }
+}
+
+impl ProtobufDecode for BitVec {
+    fn proto_type() -> Cow<'static, str> {
+        "repeated bool".into()
+    }
+
+    fn decode_value(value: Option<WireValue>) -> Result<Self, Error> {
+        let mut bits = BitVec::new();
+        match value {
+            None => {}
+            Some(WireValue::Varint(value)) => bits.push(value != 0),
+            Some(WireValue::LengthDelimited(packed)) => {
+                let mut pos = 0;
+                while pos < packed.len() {
+                    bits.push(read_varint(packed, &mut pos)? != 0);
+                }
+            }
+            Some(value) => bail!("Expected bool, found {:?}", value),
+        }
+        Ok(bits)
+    }
+
+    fn decode_field(number: u32, fields: &ProtobufFields) -> Result<Self, Error> {
+        let mut bits = BitVec::new();
+        for value in fields.all(number) {
+            bits.extend(Self::decode_value(Some(value))?.iter());
+        }
+        Ok(bits)
+    }
+}
+
+/// Slices are written as `repeated` fields of the length-delimited items.
+impl<T: ProtobufEncode> ProtobufEncode for [T] {
+    fn encode_field(&self, number: u32, writer: &mut ProtobufWriter) {
+        for item in self {
+            item.encode_field(number, writer);
+        }
+    }
+}
+
+impl<T: ProtobufEncode> ProtobufEncode for Vec<T> {
+    fn encode_field(&self, number: u32, writer: &mut ProtobufWriter) {
+        self.as_slice().encode_field(number, writer)
+    }
+}
+
+impl<T: ProtobufDecode> ProtobufDecode for Vec<T> {
+    fn proto_type() -> Cow<'static, str> {
+        format!("repeated {}", T::proto_type()).into()
+    }
+
+    fn decode_value(value: Option<WireValue>) -> Result<Self, Error> {
+        value.into_iter().map(|value| T::decode_value(Some(value))).collect()
+    }
+
+    fn decode_field(number: u32, fields: &ProtobufFields) -> Result<Self, Error> {
+        fields.all(number).map(|value| T::decode_value(Some(value))).collect()
+    }
+}
+
+macro_rules! impl_protobuf_embedded {
+    ($($typename:ident),*) => {$(
+        impl ProtobufEncode for $typename {
+            fn encode_field(&self, number: u32, writer: &mut ProtobufWriter) {
+                writer.write_message(number, self)
+            }
+        }
+
+        impl ProtobufDecode for $typename {
+            fn proto_type() -> Cow<'static, str> {
+                Self::descriptor().name.into()
+            }
+
+            fn decode_value(value: Option<WireValue>) -> Result<Self, Error> {
+                Self::from_protobuf(bytes_from_wire(value)?)
+            }
+        }
+    )*};
+}
+
+impl_protobuf_embedded!(
+    AuthorisedMessage,
+    Block,
+    BlockRequest,
+    BlockResponse,
+    Connect,
+    PeersRequest,
+    Precommit,
+    Prevote,
+    PrevotesRequest,
+    Propose,
+    ProposeRequest,
+    Protocol,
+    RawTransaction,
+    SignedMessage,
+    Status,
+    TransactionsRequest,
+    TransactionsResponse,
+    WithoutEncodingStatus
+);
+
+/// Implements `ProtobufMessage` for a structure with getters named after the fields.
+///
+/// Fields are listed with their numbers and owned types, the constructor receives
+/// references to the decoded fields.
+macro_rules! impl_protobuf_message {
+    (
+        $name:ident {
+            $($number:expr => $field:ident: $field_type:ty),* $(,)*
+        }
+        |$($arg:ident),*| $constructor:expr
+    ) => {
+        impl ProtobufMessage for $name {
+            fn descriptor() -> MessageDescriptor {
+                MessageDescriptor {
+                    name: stringify!($name),
+                    oneof: None,
+                    fields: vec![$(
+                        FieldDescriptor {
+                            number: $number,
+                            name: stringify!($field),
+                            proto_type: <$field_type as ProtobufDecode>::proto_type(),
+                        }
+                    ),*],
+                }
+            }
+
+            fn write_fields(&self, writer: &mut ProtobufWriter) {
+                $(self.$field().encode_field($number, writer);)*
+            }
+
+            fn read_fields(fields: &ProtobufFields) -> Result<Self, Error> {
+                $(
+                    let $arg = &<$field_type as ProtobufDecode>::decode_field($number, fields)
+                        .map_err(|e| format_err!("{}.{}: {}", stringify!($name), stringify!($field), e))?;
+                )*
+                Ok($constructor)
+            }
+        }
+    };
+}
+
+impl_protobuf_message! {
+    Block {
+        1 => schema_version: u16,
+        2 => proposer_id: ValidatorId,
+        3 => height: Height,
+        4 => tx_count: u32,
+        5 => prev_hash: Hash,
+        6 => tx_hash: Hash,
+        7 => state_hash: Hash,
+    }
+    |schema_version, proposer_id, height, tx_count, prev_hash, tx_hash, state_hash| Block::new(
+        *schema_version, *proposer_id, *height, *tx_count, prev_hash, tx_hash, state_hash
+    )
+}
+
+impl_protobuf_message! {
+    Connect {
+        1 => addr: SocketAddr,
+        2 => time: DateTime<Utc>,
+        3 => user_agent: String,
+    }
+    |addr, time, user_agent| Connect::new(*addr, *time, user_agent)
+}
+
+impl_protobuf_message! {
+    Status {
+        1 => height: Height,
+        2 => last_hash: Hash,
+    }
+    |height, last_hash| Status::new(*height, last_hash)
+}
+
+impl_protobuf_message! {
+    Propose {
+        1 => validator: ValidatorId,
+        2 => height: Height,
+        3 => round: Round,
+        4 => prev_hash: Hash,
+        5 => transactions: Vec<Hash>,
+    }
+    |validator, height, round, prev_hash, transactions| Propose::new(
+        *validator, *height, *round, prev_hash, transactions
+    )
+}
+
+impl_protobuf_message! {
+    Prevote {
+        1 => validator: ValidatorId,
+        2 => height: Height,
+        3 => round: Round,
+        4 => propose_hash: Hash,
+        5 => locked_round: Round,
+    }
+    |validator, height, round, propose_hash, locked_round| Prevote::new(
+        *validator, *height, *round, propose_hash, *locked_round
+    )
+}
+
+impl_protobuf_message! {
+    Precommit {
+        1 => validator: ValidatorId,
+        2 => height: Height,
+        3 => round: Round,
+        4 => propose_hash: Hash,
+        5 => block_hash: Hash,
+        6 => time: DateTime<Utc>,
+    }
+    |validator, height, round, propose_hash, block_hash, time| Precommit::new(
+        *validator, *height, *round, propose_hash, block_hash, *time
+    )
+}
+
+impl_protobuf_message! {
+    BlockResponse {
+        1 => to: PublicKey,
+        2 => block: Block,
+        3 => precommits: Vec<SignedMessage>,
+        4 => transactions: Vec<Hash>,
+    }
+    |to, block, precommits, transactions| BlockResponse::new(
+        to, block.clone(), precommits.clone(), transactions
+    )
+}
+
+impl_protobuf_message! {
+    TransactionsResponse {
+        1 => to: PublicKey,
+        2 => transactions: Vec<SignedMessage>,
+    }
+    |to, transactions| TransactionsResponse::new(to, transactions.clone())
+}
+
+impl_protobuf_message! {
+    ProposeRequest {
+        1 => to: PublicKey,
+        2 => height: Height,
+        3 => propose_hash: Hash,
+    }
+    |to, height, propose_hash| ProposeRequest::new(to, *height, propose_hash)
+}
+
+impl_protobuf_message! {
+    TransactionsRequest {
+        1 => to: PublicKey,
+        2 => txs: Vec<Hash>,
+    }
+    |to, txs| TransactionsRequest::new(to, txs)
+}
+
+impl_protobuf_message! {
+    PrevotesRequest {
+        1 => to: PublicKey,
+        2 => height: Height,
+        3 => round: Round,
+        4 => propose_hash: Hash,
+        5 => validators: BitVec,
+    }
+    |to, height, round, propose_hash, validators| PrevotesRequest::new(
+        to, *height, *round, propose_hash, validators.clone()
+    )
+}
+
+impl_protobuf_message! {
+    PeersRequest {
+        1 => to: PublicKey,
+    }
+    |to| PeersRequest::new(to)
+}
+
+impl_protobuf_message! {
+    BlockRequest {
+        1 => to: PublicKey,
+        2 => height: Height,
+    }
+    |to, height| BlockRequest::new(to, *height)
+}
+
+impl_protobuf_message! {
+    RawTransaction {
+        1 => service_id: u16,
+        2 => payload: Vec<u8>,
+    }
+    |service_id, payload| RawTransaction {
+        service_id: *service_id,
+        payload: payload.clone(),
+    }
+}
+
+impl ProtobufMessage for WithoutEncodingStatus {
+    fn descriptor() -> MessageDescriptor {
+        MessageDescriptor {
+            name: "WithoutEncodingStatus",
+            ..Status::descriptor()
+        }
+    }
+
+    fn write_fields(&self, writer: &mut ProtobufWriter) {
+        self.height.encode_field(1, writer);
+        self.last_hash.encode_field(2, writer);
+    }
+
+    fn read_fields(fields: &ProtobufFields) -> Result<Self, Error> {
+        Ok(WithoutEncodingStatus {
+            height: Height::decode_field(1, fields)?,
+            last_hash: Hash::decode_field(2, fields)?,
+        })
+    }
+}
+
+/// Implements `ProtobufMessage` for `Protocol` as a message with a single `oneof`,
+/// nested enums are flattened.
+macro_rules! impl_protobuf_protocol {
+    ($($number:tt => $field:ident: $field_type:ident = $variant:pat => $value:expr;)*) => {
+        impl ProtobufMessage for Protocol {
+            fn descriptor() -> MessageDescriptor {
+                MessageDescriptor {
+                    name: "Protocol",
+                    oneof: Some("kind"),
+                    fields: vec![$(
+                        FieldDescriptor {
+                            number: $number,
+                            name: stringify!($field),
+                            proto_type: stringify!($field_type).into(),
+                        }
+                    ),*],
+                }
+            }
+
+            fn write_fields(&self, writer: &mut ProtobufWriter) {
+                match *self {
+                    $($variant => writer.write_message($number, $field),)*
+                }
+            }
+
+            fn read_fields(fields: &ProtobufFields) -> Result<Self, Error> {
+                match fields.last_of(&[$($number),*]) {
+                    $(Some($number) => {
+                        let $field = $field_type::decode_field($number, fields)?;
+                        Ok($value)
+                    })*
+                    _ => bail!("Protocol message doesn't contain any known message"),
+                }
+            }
+        }
+    };
+}
+
+impl_protobuf_protocol! {
+    1 => transaction: RawTransaction =
+        Protocol::Transaction(ref transaction) => Protocol::Transaction(transaction);
+    2 => connect: Connect =
+        Protocol::Connect(ref connect) => Protocol::Connect(connect);
+    3 => status: Status =
+        Protocol::Status(ref status) => Protocol::Status(status);
+    4 => without_encoding_status: WithoutEncodingStatus =
+        Protocol::WithoutEncodingStatus(ref without_encoding_status) =>
+        Protocol::WithoutEncodingStatus(without_encoding_status);
+    5 => block_response: BlockResponse =
+        Protocol::Block(ref block_response) => Protocol::Block(block_response);
+    6 => propose: Propose =
+        Protocol::Consensus(ConsensusMessage::Propose(ref propose)) =>
+        Protocol::Consensus(ConsensusMessage::Propose(propose));
+    7 => prevote: Prevote =
+        Protocol::Consensus(ConsensusMessage::Prevote(ref prevote)) =>
+        Protocol::Consensus(ConsensusMessage::Prevote(prevote));
+    8 => precommit: Precommit =
+        Protocol::Consensus(ConsensusMessage::Precommit(ref precommit)) =>
+        Protocol::Consensus(ConsensusMessage::Precommit(precommit));
+    9 => propose_request: ProposeRequest =
+        Protocol::Request(RequestMessage::Propose(ref propose_request)) =>
+        Protocol::Request(RequestMessage::Propose(propose_request));
+    10 => transactions_request: TransactionsRequest =
+        Protocol::Request(RequestMessage::Transactions(ref transactions_request)) =>
+        Protocol::Request(RequestMessage::Transactions(transactions_request));
+    11 => prevotes_request: PrevotesRequest =
+        Protocol::Request(RequestMessage::Prevotes(ref prevotes_request)) =>
+        Protocol::Request(RequestMessage::Prevotes(prevotes_request));
+    12 => peers_request: PeersRequest =
+        Protocol::Request(RequestMessage::Peers(ref peers_request)) =>
+        Protocol::Request(RequestMessage::Peers(peers_request));
+    13 => block_request: BlockRequest =
+        Protocol::Request(RequestMessage::Block(ref block_request)) =>
+        Protocol::Request(RequestMessage::Block(block_request));
+    14 => transactions_batch: TransactionsResponse =
+        Protocol::TransactionsBatch(ref transactions_batch) =>
+        Protocol::TransactionsBatch(transactions_batch);
+}
+
+impl ProtobufMessage for AuthorisedMessage {
+    fn descriptor() -> MessageDescriptor {
+        MessageDescriptor {
+            name: "AuthorisedMessage",
+            oneof: None,
+            fields: vec![
+                FieldDescriptor {
+                    number: 1,
+                    name: "version",
+                    proto_type: u32::proto_type(),
+                },
+                FieldDescriptor {
+                    number: 2,
+                    name: "author",
+                    proto_type: PublicKey::proto_type(),
+                },
+                FieldDescriptor {
+                    number: 3,
+                    name: "protocol",
+                    proto_type: Protocol::proto_type(),
+                },
+            ],
+        }
+    }
+
+    fn write_fields(&self, writer: &mut ProtobufWriter) {
+        u32::from(self.version).encode_field(1, writer);
+        self.author.encode_field(2, writer);
+        self.protocol.encode_field(3, writer);
+    }
+
+    fn read_fields(fields: &ProtobufFields) -> Result<Self, Error> {
+        let version = u32::decode_field(1, fields)?;
+        if version > u32::from(u8::max_value()) {
+            bail!("Protocol version {} is out of range", version);
+        }
+        Ok(AuthorisedMessage {
+            reserved: 0,
+            version: version as u8,
+            author: PublicKey::decode_field(2, fields)?,
+            protocol: Protocol::decode_field(3, fields)?,
+        })
+    }
+}
+
+/// Message signed over its protobuf form.
+///
+/// The signature covers the protobuf bytes of the authorised message prefixed with
+/// the domain tag and the chain id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtobufSignedMessage {
+    authorised_message: AuthorisedMessage,
+    // Protobuf bytes of the authorised message covered by the signature.
+    authorised_bytes: Vec<u8>,
+    signature: Signature,
+}
+
+impl ProtobufSignedMessage {
+    /// Creates a message signed over its protobuf form in the network with the given chain id.
+    pub fn new<T: Into<Protocol>, S: Signer + ?Sized>(
+        value: T,
+        author: PublicKey,
+        chain_id: &Hash,
+        signer: &S,
+    ) -> Result<Self, Error> {
+        SignedMessage::check_author(&author, signer)?;
+        let authorised_message = AuthorisedMessage::new(value, author)?;
+        let authorised_bytes = authorised_message.to_protobuf();
+        let domain = authorised_message.domain();
+        let signature = domain.sign(chain_id, &authorised_bytes, signer)?;
+        Ok(ProtobufSignedMessage {
+            authorised_message,
+            authorised_bytes,
+            signature,
+        })
+    }
+
+    /// Verifies the signature of the message in the network with the given chain id.
+    pub fn verify(&self, chain_id: &Hash) -> Result<(), Error> {
+        if !self.authorised_message.domain().verify(
+            chain_id,
+            &self.signature,
+            &self.authorised_bytes,
+            &self.authorised_message.author,
+        ) {
+            bail!("Can't verify message.");
+        }
+        Ok(())
+    }
+
+    /// Returns the author of the message.
+    pub fn author(&self) -> PublicKey {
+        self.authorised_message.author
+    }
+
+    /// Returns the payload of the message.
+    pub fn protocol(&self) -> &Protocol {
+        &self.authorised_message.protocol
+    }
+
+    /// Returns the signature of the message.
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+}
+
+impl ProtobufMessage for ProtobufSignedMessage {
+    fn descriptor() -> MessageDescriptor {
+        MessageDescriptor {
+            name: "SignedMessage",
+            oneof: None,
+            fields: vec![
+                FieldDescriptor {
+                    number: 1,
+                    name: "authorised_message",
+                    proto_type: AuthorisedMessage::proto_type(),
+                },
+                FieldDescriptor {
+                    number: 2,
+                    name: "signature",
+                    proto_type: Signature::proto_type(),
+                },
+            ],
+        }
+    }
+
+    fn write_fields(&self, writer: &mut ProtobufWriter) {
+        writer.write_bytes(1, &self.authorised_bytes);
+        self.signature.encode_field(2, writer);
+    }
+
+    fn read_fields(fields: &ProtobufFields) -> Result<Self, Error> {
+        let authorised_bytes = bytes_from_wire(fields.last(1))?.to_vec();
+        Ok(ProtobufSignedMessage {
+            authorised_message: AuthorisedMessage::from_protobuf(&authorised_bytes)?,
+            authorised_bytes,
+            signature: Signature::decode_field(2, fields)?,
+        })
+    }
+}
+
+/// The signature is defined over the Exonum binary form of the authorised message,
+/// so the message is written as `BinarySignedMessage`.
+impl ProtobufMessage for SignedMessage {
+    fn descriptor() -> MessageDescriptor {
+        MessageDescriptor {
+            name: "BinarySignedMessage",
+            oneof: None,
+            fields: vec![
+                FieldDescriptor {
+                    number: 1,
+                    name: "authorised_message",
+                    proto_type: AuthorisedMessage::proto_type(),
+                },
+                FieldDescriptor {
+                    number: 2,
+                    name: "signature",
+                    proto_type: Signature::proto_type(),
+                },
+            ],
+        }
+    }
+
+    fn write_fields(&self, writer: &mut ProtobufWriter) {
+        self.authorised_message.encode_field(1, writer);
+        self.signature.encode_field(2, writer);
+    }
+
+    fn read_fields(fields: &ProtobufFields) -> Result<Self, Error> {
+        let authorised_message = AuthorisedMessage::decode_field(1, fields)?;
+        let signature = Signature::decode_field(2, fields)?;
+        Ok(SignedMessage::from_parts(
+            authorised_message.version,
+            authorised_message.author,
+            authorised_message.protocol,
+            signature,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bit_vec::BitVec;
+    use chrono::{TimeZone, Utc};
+
+    use crypto::{chain_id, gen_keypair, hash, DEFAULT_NETWORK};
+    use super::*;
+
+    fn round_trip<T: ProtobufMessage + Into<Protocol> + Clone>(value: T) {
+        let (public_key, secret_key) = gen_keypair();
+        let protocol = value.clone().into();
+        let chain_id = chain_id(DEFAULT_NETWORK);
+        let signed =
+            SignedMessage::new(value.clone(), public_key, &chain_id, &secret_key).unwrap();
+        let bytes = signed.to_protobuf();
+        let decoded = SignedMessage::from_protobuf(&bytes).unwrap();
+        assert_eq!(decoded, signed);
+        assert!(decoded.verify(&chain_id).is_ok());
+        assert_eq!(decoded.authorised_message.protocol, protocol);
+        assert_eq!(decoded.to_protobuf(), bytes);
+
+        let signed = ProtobufSignedMessage::new(value, public_key, &chain_id, &secret_key).unwrap();
+        let bytes = signed.to_protobuf();
+        let decoded = ProtobufSignedMessage::from_protobuf(&bytes).unwrap();
+        assert_eq!(decoded, signed);
+        assert!(decoded.verify(&chain_id).is_ok());
+        assert_eq!(decoded.protocol(), &protocol);
+        assert_eq!(decoded.to_protobuf(), bytes);
+    }
+
+    fn precommit(round: u32) -> Precommit {
+        Precommit::new(
+            ValidatorId(1),
+            Height(2),
+            Round(round),
+            &hash(&[1]),
+            &hash(&[2]),
+            Utc.timestamp(1_500_000_000, 1),
+        )
+    }
+
+    #[test]
+    fn test_varint() {
+        for &value in &[0, 1, 127, 128, 300, u64::from(u32::max_value()), u64::max_value()] {
+            let mut buffer = Vec::new();
+            write_varint(&mut buffer, value);
+            let mut pos = 0;
+            assert_eq!(read_varint(&buffer, &mut pos).unwrap(), value);
+            assert_eq!(pos, buffer.len());
+        }
+        let mut buffer = Vec::new();
+        write_varint(&mut buffer, 300);
+        assert_eq!(buffer, vec![0xac, 0x02]);
+
+        assert!(read_varint(&[0x80], &mut 0).is_err());
+        assert!(read_varint(&[0xff; 10], &mut 0).is_err());
+        assert!(read_varint(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02], &mut 0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_status_wire_format() {
+        let status = Status::new(Height(150), &Hash::zero());
+        let mut expected = vec![0x08, 0x96, 0x01, 0x12, 0x20];
+        expected.extend_from_slice(&[0; 32]);
+        assert_eq!(status.to_protobuf(), expected);
+        assert_eq!(Status::from_protobuf(&expected).unwrap(), status);
+
+        // Unknown fields are skipped and the last occurrence of the field wins.
+        let mut bytes = vec![0x08, 0x01, 0x18, 0x05, 0x25, 0, 0, 0, 0];
+        bytes.extend_from_slice(&expected);
+        assert_eq!(Status::from_protobuf(&bytes).unwrap(), status);
+    }
+
+    #[test]
+    fn test_protocol_round_trip() {
+        let (public_key, _) = gen_keypair();
+        let hashes = vec![hash(&[1]), hash(&[2]), hash(&[3])];
+        let block = Block::new(
+            0,
+            ValidatorId(3),
+            Height(4),
+            2,
+            &hash(&[4]),
+            &hash(&[5]),
+            &hash(&[6]),
+        );
+        let mut validators = BitVec::from_elem(11, false);
+        validators.set(0, true);
+        validators.set(10, true);
+
+        round_trip(RawTransaction {
+            service_id: 7,
+            payload: vec![1, 2, 3],
+        });
+        round_trip(Connect::new(
+            "127.0.0.1:8000".parse().unwrap(),
+            Utc.timestamp(1_500_000_000, 999_999_999),
+            "exonum 0.8",
+        ));
+        round_trip(Connect::new(
+            "[::1]:8000".parse().unwrap(),
+            Utc.timestamp(-1, 0),
+            "exonum",
+        ));
+        round_trip(Status::new(Height(0), &hash(&[])));
+        round_trip(WithoutEncodingStatus {
+            height: Height(u64::max_value()),
+            last_hash: hash(&[]),
+        });
+        round_trip(BlockResponse::new(&public_key, block, Vec::new(), &hashes));
+        round_trip(Propose::new(ValidatorId(0), Height(1), Round(1), &hash(&[]), &hashes));
+        round_trip(Propose::new(ValidatorId(0), Height(1), Round(1), &hash(&[]), &[]));
+        round_trip(Prevote::new(ValidatorId(1), Height(2), Round(3), &hash(&[]), Round(0)));
+        round_trip(precommit(1));
+        round_trip(ProposeRequest::new(&public_key, Height(5), &hash(&[])));
+        round_trip(TransactionsRequest::new(&public_key, &hashes));
+        round_trip(PrevotesRequest::new(&public_key, Height(1), Round(2), &hash(&[]), validators));
+        round_trip(PrevotesRequest::new(
+            &public_key,
+            Height(1),
+            Round(2),
+            &hash(&[]),
+            BitVec::new(),
+        ));
+        round_trip(PeersRequest::new(&public_key));
+        round_trip(BlockRequest::new(&public_key, Height(9)));
+        round_trip(TransactionsResponse::new(&public_key, Vec::new()));
+    }
+
+    #[test]
+    fn test_repeated_signed_messages() {
+        let (public_key, secret_key) = gen_keypair();
+        let chain_id = chain_id(DEFAULT_NETWORK);
+        let precommits = vec![
+            SignedMessage::new(precommit(1), public_key, &chain_id, &secret_key).unwrap(),
+            SignedMessage::new(precommit(2), public_key, &chain_id, &secret_key).unwrap(),
+        ];
+        let mut writer = ProtobufWriter::new();
+        precommits.encode_field(3, &mut writer);
+        let bytes = writer.into_bytes();
+        let fields = ProtobufFields::parse(&bytes).unwrap();
+        assert_eq!(fields.all(3).count(), 2);
+        assert_eq!(Vec::<SignedMessage>::decode_field(3, &fields).unwrap(), precommits);
+        assert_eq!(Vec::<SignedMessage>::decode_field(4, &fields).unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn test_nested_signed_messages() {
+        let (public_key, secret_key) = gen_keypair();
+        let block = Block::new(
+            0,
+            ValidatorId(1),
+            Height(2),
+            1,
+            &hash(&[1]),
+            &hash(&[2]),
+            &hash(&[3]),
+        );
+        let chain_id = chain_id(DEFAULT_NETWORK);
+        let precommit =
+            SignedMessage::new(precommit(1), public_key, &chain_id, &secret_key).unwrap();
+        let response = BlockResponse::new(
+            &public_key,
+            block.clone(),
+            vec![precommit.clone()],
+            &[hash(&[4])],
+        );
+        let bytes = response.to_protobuf();
+        let fields = ProtobufFields::parse(&bytes).unwrap();
+        let nested = precommit.to_protobuf();
+        assert_eq!(fields.last(3), Some(WireValue::LengthDelimited(&nested)));
+        let decoded = BlockResponse::from_protobuf(&bytes).unwrap();
+        assert_eq!(decoded.precommits(), vec![precommit.clone()]);
+        assert_eq!(decoded, response);
+        round_trip(response);
+
+        let transaction = RawTransaction {
+            service_id: 1,
+            payload: vec![1, 2, 3],
+        };
+        let transaction =
+            SignedMessage::new(transaction, public_key, &chain_id, &secret_key).unwrap();
+        round_trip(TransactionsResponse::new(&public_key, vec![transaction]));
+
+        // Signatures of the nested messages are kept as is.
+        let mut writer = ProtobufWriter::new();
+        public_key.encode_field(1, &mut writer);
+        block.encode_field(2, &mut writer);
+        let mut corrupted = nested.clone();
+        let len = corrupted.len();
+        corrupted[len - 1] ^= 1;
+        writer.write_bytes(3, &corrupted);
+        let decoded = BlockResponse::from_protobuf(&writer.into_bytes()).unwrap();
+        assert!(decoded.precommits()[0].verify(&chain_id).is_err());
+    }
+
+    #[test]
+    fn test_unpacked_bit_vec() {
+        let (public_key, _) = gen_keypair();
+        let request = PrevotesRequest::new(
+            &public_key,
+            Height(1),
+            Round(2),
+            &hash(&[]),
+            BitVec::from_bytes(&[0b1010_0000]),
+        );
+        let mut writer = ProtobufWriter::new();
+        public_key.encode_field(1, &mut writer);
+        Height(1).encode_field(2, &mut writer);
+        Round(2).encode_field(3, &mut writer);
+        hash(&[]).encode_field(4, &mut writer);
+        writer.write_bytes(5, &[1, 0]);
+        for &bit in &[1, 0, 0, 0, 0, 0] {
+            writer.write_varint(5, bit);
+        }
+        assert_eq!(
+            PrevotesRequest::from_protobuf(&writer.into_bytes()).unwrap(),
+            request
+        );
+    }
+
+    #[test]
+    fn test_signature_is_over_protobuf_form() {
+        let (public_key, secret_key) = gen_keypair();
+        let chain_id = chain_id(DEFAULT_NETWORK);
+        let signed =
+            ProtobufSignedMessage::new(precommit(1), public_key, &chain_id, &secret_key).unwrap();
+        let authorised = signed.authorised_message.to_protobuf();
+        let signed_bytes = [&[0x10][..], chain_id.as_ref(), &authorised].concat();
+        assert!(::crypto::verify(signed.signature(), &signed_bytes, &public_key));
+        assert!(signed.verify(&::crypto::chain_id("testnet")).is_err());
+
+        // The signature covers the bytes as they were written by the author,
+        // e.g. with an unknown field.
+        let mut authorised = authorised;
+        authorised.extend_from_slice(&[0x20, 0x01]);
+        let signed_bytes = [&[0x10][..], chain_id.as_ref(), &authorised].concat();
+        let signature = ::crypto::sign(&signed_bytes, &secret_key);
+        let mut writer = ProtobufWriter::new();
+        writer.write_bytes(1, &authorised);
+        signature.encode_field(2, &mut writer);
+        let bytes = writer.into_bytes();
+        let decoded = ProtobufSignedMessage::from_protobuf(&bytes).unwrap();
+        assert!(decoded.verify(&chain_id).is_ok());
+        assert_eq!(decoded.protocol(), signed.protocol());
+        assert_eq!(decoded.to_protobuf(), bytes);
+
+        // The binary form signature doesn't verify as the protobuf form one.
+        let binary = SignedMessage::new(precommit(1), public_key, &chain_id, &secret_key).unwrap();
+        let mut writer = ProtobufWriter::new();
+        writer.write_bytes(1, &signed.authorised_message.to_protobuf());
+        binary.signature.encode_field(2, &mut writer);
+        let decoded = ProtobufSignedMessage::from_protobuf(&writer.into_bytes()).unwrap();
+        assert!(decoded.verify(&chain_id).is_err());
+
+        // A change of the signature is detected by the verification.
+        let mut bytes = signed.to_protobuf();
+        let len = bytes.len();
+        bytes[len - 1] ^= 1;
+        let decoded = ProtobufSignedMessage::from_protobuf(&bytes).unwrap();
+        assert!(decoded.verify(&chain_id).is_err());
+    }
+
+    #[test]
+    fn test_malformed_messages() {
+        assert!(Status::from_protobuf(&[0x12, 0x05, 0x00]).is_err());
+        assert!(Status::from_protobuf(&[0x12, 0x01, 0x00]).is_err());
+        assert!(Status::from_protobuf(&[0x08, 0x96]).is_err());
+        assert!(Status::from_protobuf(&[0x0b]).is_err());
+        assert!(Status::from_protobuf(&[0x00, 0x00]).is_err());
+        assert!(Protocol::from_protobuf(&[]).is_err());
+        assert!(Block::from_protobuf(&[0x08, 0x80, 0x80, 0x04]).is_err());
+        assert!(Connect::from_protobuf(&[0x0a, 0x03, b'a', b':', b'1']).is_err());
+    }
+
+    #[test]
+    fn test_proto_description_is_up_to_date() {
+        assert_eq!(proto_description(), include_str!("protocol.proto"));
+    }
+}
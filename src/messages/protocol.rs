@@ -36,7 +36,11 @@ use crypto::{Hash, PublicKey};
 use types::{Height, Round, ValidatorId};
 use super::{SignedMessage, RawTransaction};
 
+/// Maximum number of transactions in a single `Propose`.
+pub const MAX_PROPOSE_TRANSACTIONS: usize = 10_000;
+
 encoding_struct!(
+    #![domain = Block]
     /// Exonum block header data structure.
     ///
     /// Block is essentially a list of transactions, which is
@@ -194,7 +198,7 @@ encoding_struct! {
         /// Time when the message was created.
         time: DateTime<Utc>,
         /// String containing information about this node including Exonum, Rust and OS versions.
-        user_agent: &str,
+        user_agent: &str where !user_agent.is_empty(),
     }
 
 }
@@ -251,11 +255,11 @@ encoding_struct! {
         /// The height to which the message is related.
         height: Height,
         /// The round to which the message is related.
-        round: Round,
+        round: Round where round >= Round::first(),
         /// Hash of the previous block.
         prev_hash: &Hash,
         /// The list of transactions to include in the next block.
-        transactions: &[Hash],
+        transactions: &[Hash] where transactions.len() <= MAX_PROPOSE_TRANSACTIONS,
     }
 }
 encoding_struct! {
@@ -283,12 +287,13 @@ encoding_struct! {
         /// The height to which the message is related.
         height: Height,
         /// The round to which the message is related.
-        round: Round,
+        round: Round where round >= Round::first(),
         /// Hash of the corresponding `Propose`.
         propose_hash: &Hash,
         /// Locked round.
         locked_round: Round,
     }
+    where locked_round < round
 }
 encoding_struct! {
     /// Pre-commit for a proposal.
@@ -315,7 +320,7 @@ encoding_struct! {
         /// The height to which the message is related.
         height: Height,
         /// The round to which the message is related.
-        round: Round,
+        round: Round where round >= Round::first(),
         /// Hash of the corresponding `Propose`.
         propose_hash: &Hash,
         /// Hash of the new block.
@@ -370,6 +375,22 @@ encoding_struct! {
     }
 
 }
+
+impl BlockResponse {
+    /// Verifies the signatures of the pre-commits in a batch, returns the indices of
+    /// the invalid pre-commits on failure.
+    pub fn verify_precommits(&self, chain_id: &Hash) -> Result<(), Vec<usize>> {
+        SignedMessage::verify_batch(&self.precommits(), chain_id)
+    }
+}
+
+impl TransactionsResponse {
+    /// Verifies the signatures of the transactions in a batch, returns the indices of
+    /// the invalid transactions on failure.
+    pub fn verify_transactions(&self, chain_id: &Hash) -> Result<(), Vec<usize>> {
+        SignedMessage::verify_batch(&self.transactions(), chain_id)
+    }
+}
 encoding_struct! {
     /// Request for the `Propose`.
     ///
@@ -426,7 +447,7 @@ encoding_struct! {
         /// The height to which the message is related.
         height: Height,
         /// The round to which the message is related.
-        round: Round,
+        round: Round where round >= Round::first(),
         /// Hash of the `Propose`.
         propose_hash: &Hash,
         /// The list of validators that send pre-votes.
@@ -474,6 +495,18 @@ encoding_struct! {
 
 pub trait ProtocolMessage: Debug + Into<Protocol> + PartialEq<Protocol> + Clone{}
 impl<T: Debug + Into<Protocol> + PartialEq<Protocol> + Clone> ProtocolMessage for T {}
+
+/// Extraction of the message of a specific type from the `Protocol`.
+pub trait FromProtocol: Sized {
+    /// Returns the message, if the protocol contains the message of this type.
+    fn from_protocol(protocol: Protocol) -> Option<Self>;
+}
+
+impl FromProtocol for Protocol {
+    fn from_protocol(protocol: Protocol) -> Option<Self> {
+        Some(protocol)
+    }
+}
 /*
 pub enum Protocol {
     /// `Connect` message.
@@ -535,6 +568,14 @@ macro_rules! impl_protocol {
             $($ma)*
         }
     }
+    impl FromProtocol for $val {
+        fn from_protocol(protocol: Protocol) -> Option<Self> {
+            match protocol {
+                $($ma)* => Some($v),
+                _ => None,
+            }
+        }
+    }
 
     };
 }
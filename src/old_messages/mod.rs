@@ -22,7 +22,7 @@ use bit_vec::BitVec;
 
 use std::fmt;
 
-use crypto::PublicKey;
+use crypto::{Hash, PublicKey};
 use encoding::Error;
 use types::{Height, Round, ValidatorId};
 
@@ -120,15 +120,16 @@ impl RequestMessage {
         }
     }
 
-    /// Verifies the message signature with given public key.
+    /// Verifies the message signature in the network with the given chain id
+    /// with given public key.
     #[cfg_attr(feature = "flame_profile", flame)]
-    pub fn verify(&self, public_key: &PublicKey) -> bool {
+    pub fn verify(&self, chain_id: &Hash, public_key: &PublicKey) -> bool {
         match *self {
-            RequestMessage::Propose(ref msg) => msg.verify_signature(public_key),
-            RequestMessage::Transactions(ref msg) => msg.verify_signature(public_key),
-            RequestMessage::Prevotes(ref msg) => msg.verify_signature(public_key),
-            RequestMessage::Peers(ref msg) => msg.verify_signature(public_key),
-            RequestMessage::Block(ref msg) => msg.verify_signature(public_key),
+            RequestMessage::Propose(ref msg) => msg.verify_signature(chain_id, public_key),
+            RequestMessage::Transactions(ref msg) => msg.verify_signature(chain_id, public_key),
+            RequestMessage::Prevotes(ref msg) => msg.verify_signature(chain_id, public_key),
+            RequestMessage::Peers(ref msg) => msg.verify_signature(chain_id, public_key),
+            RequestMessage::Block(ref msg) => msg.verify_signature(chain_id, public_key),
         }
     }
 
@@ -193,12 +194,13 @@ impl ConsensusMessage {
         }
     }
 
-    /// Verifies the message signature with given public key.
-    pub fn verify(&self, public_key: &PublicKey) -> bool {
+    /// Verifies the message signature in the network with the given chain id
+    /// with given public key.
+    pub fn verify(&self, chain_id: &Hash, public_key: &PublicKey) -> bool {
         match *self {
-            ConsensusMessage::Propose(ref msg) => msg.verify_signature(public_key),
-            ConsensusMessage::Prevote(ref msg) => msg.verify_signature(public_key),
-            ConsensusMessage::Precommit(ref msg) => msg.verify_signature(public_key),
+            ConsensusMessage::Propose(ref msg) => msg.verify_signature(chain_id, public_key),
+            ConsensusMessage::Prevote(ref msg) => msg.verify_signature(chain_id, public_key),
+            ConsensusMessage::Precommit(ref msg) => msg.verify_signature(chain_id, public_key),
         }
     }
 }
@@ -15,12 +15,13 @@
 #![allow(unsafe_code)]
 
 use byteorder::{ByteOrder, LittleEndian};
+use failure::Error;
 
 use std::{convert, fmt::Debug, ops::Deref, sync};
 
-use crypto::{hash, sign, verify, CryptoHash, Hash, PublicKey, SecretKey, Signature,
-             SIGNATURE_LENGTH};
+use crypto::{CryptoHash, Domain, Hash, PublicKey, Signature, Signer, SIGNATURE_LENGTH};
 use encoding::{self, CheckedOffset, Field, Offset, Result as StreamStructResult};
+use super::CONSENSUS;
 
 /// Length of the message header.
 pub const HEADER_LENGTH: usize = 10;
@@ -44,7 +45,7 @@ impl RawMessage {
 
     /// Returns hash of the `RawMessage`.
     pub fn hash(&self) -> Hash {
-        hash(self.as_ref())
+        self.domain().hash(self.as_ref())
     }
 }
 
@@ -130,6 +131,12 @@ impl MessageBuffer {
         LittleEndian::read_u16(&self.raw[4..6])
     }
 
+    /// Returns the domain of the message hash and signature, which separates
+    /// transactions of the services from the consensus messages.
+    pub fn domain(&self) -> Domain {
+        domain_of_service(self.service_id())
+    }
+
     /// Returns type of the message.
     pub fn message_type(&self) -> u16 {
         LittleEndian::read_u16(&self.raw[2..4])
@@ -231,13 +238,24 @@ impl MessageWriter {
         );
     }
 
-    /// Signs the message with the given secret key.
-    pub fn sign(mut self, secret_key: &SecretKey) -> MessageBuffer {
+    /// Copies the given bytes into the payload, which should have the same length.
+    pub fn write_payload(&mut self, payload: &[u8]) {
+        self.raw[HEADER_LENGTH..].copy_from_slice(payload);
+    }
+
+    /// Signs the message in the network with the given chain id with the given signer,
+    /// for example, a secret key.
+    pub fn sign<S: Signer + ?Sized>(
+        mut self,
+        chain_id: &Hash,
+        signer: &S,
+    ) -> Result<MessageBuffer, Error> {
         let payload_length = self.raw.len() + SIGNATURE_LENGTH;
         self.set_payload_length(payload_length);
-        let signature = sign(&self.raw, secret_key);
+        let service_id = LittleEndian::read_u16(&self.raw[4..6]);
+        let signature = domain_of_service(service_id).sign(chain_id, &self.raw, signer)?;
         self.raw.extend_from_slice(signature.as_ref());
-        MessageBuffer { raw: self.raw }
+        Ok(MessageBuffer { raw: self.raw })
     }
 
     /// Appends the given signature to the message.
@@ -278,15 +296,15 @@ pub trait Message: CryptoHash + Debug + Send + Sync {
     /// Returns raw message.
     fn raw(&self) -> &RawMessage;
 
-    /// Verifies the message using given public key.
-    fn verify_signature(&self, pub_key: &PublicKey) -> bool {
-        self.raw().verify_signature(pub_key)
+    /// Verifies the message in the network with the given chain id using given public key.
+    fn verify_signature(&self, chain_id: &Hash, pub_key: &PublicKey) -> bool {
+        self.raw().verify_signature(chain_id, pub_key)
     }
 }
 
 impl<T: Message> CryptoHash for T {
     fn hash(&self) -> Hash {
-        hash(self.raw().as_ref())
+        self.raw().hash()
     }
 }
 
@@ -299,7 +317,15 @@ impl Message for RawMessage {
         self
     }
 
-    fn verify_signature(&self, pub_key: &PublicKey) -> bool {
-        verify(self.signature(), self.body(), pub_key)
+    fn verify_signature(&self, chain_id: &Hash, pub_key: &PublicKey) -> bool {
+        self.domain().verify(chain_id, self.signature(), self.body(), pub_key)
+    }
+}
+
+fn domain_of_service(service_id: u16) -> Domain {
+    if service_id == CONSENSUS {
+        Domain::Message
+    } else {
+        Domain::Transaction
     }
 }
@@ -175,10 +175,14 @@ macro_rules! __ex_message {
 
         impl $name {
             #[cfg_attr(feature="cargo-clippy", allow(too_many_arguments))]
-            /// Creates message and signs it.
+            /// Creates message and signs it in the network with the given chain id,
+            /// fails if the signer fails.
             #[allow(unused_mut)]
-            pub fn new($($field_name: $field_type,)*
-                       secret_key: &$crate::crypto::SecretKey) -> $name {
+            pub fn new<S>($($field_name: $field_type,)*
+                          chain_id: &$crate::crypto::Hash,
+                          signer: &S) -> Result<$name, $crate::failure::Error>
+                where S: $crate::crypto::Signer + ?Sized
+            {
                 use $crate::old_messages::{RawMessage, MessageWriter};
                 let mut writer = MessageWriter::new(
                     $crate::old_messages::PROTOCOL_MAJOR_VERSION,
@@ -190,7 +194,7 @@ macro_rules! __ex_message {
                     __ex_message_write_field, (writer),
                     $( ($(#[$field_attr])*, $field_name, $field_type) )*
                 );
-                $name { raw: RawMessage::new(writer.sign(secret_key)) }
+                Ok($name { raw: RawMessage::new(writer.sign(chain_id, signer)?) })
             }
 
             /// Creates message and appends existing signature.
@@ -333,33 +337,40 @@ macro_rules! __ex_message {
         impl $crate::encoding::serialize::json::ExonumJsonDeserialize for $name {
             #[allow(unused_imports, unused_variables, unused_mut)]
             fn deserialize(value: &$crate::encoding::serialize::json::reexport::Value)
-                -> ::std::result::Result<Self, Box<::std::error::Error>>
+                -> ::std::result::Result<Self, $crate::encoding::serialize::json::JsonError>
             {
-                use $crate::encoding::serialize::json::ExonumJson;
-                use $crate::encoding::serialize::json::reexport::from_value;
+                use $crate::encoding::serialize::json::{ExonumJson, ExonumJsonDeserialize,
+                                                        JsonError};
+                use $crate::encoding::serialize::json::reexport::{from_value, Value};
                 use $crate::old_messages::{RawMessage, MessageWriter};
 
                 // if we could deserialize values, try append signature
-                let obj = value.as_object().ok_or("Can't cast json as object.")?;
+                let obj = value.as_object().ok_or_else(|| JsonError::wrong_type("object"))?;
+                let field = |name| -> ::std::result::Result<&Value, JsonError> {
+                    obj.get(name).ok_or_else(|| JsonError::missing_field(name))
+                };
 
-                let body = obj.get("body").ok_or("Can't get body from json.")?;
+                let body = field("body")?;
 
-                let signature = from_value(obj.get("signature")
-                                    .ok_or("Can't get signature from json")?.clone())?;
-                let message_id = from_value(obj.get("message_id")
-                                    .ok_or("Can't get message_id from json")?.clone())?;
-                let service_id = from_value(obj.get("service_id")
-                                    .ok_or("Can't get service_id from json")?.clone())?;
+                let signature = <$crate::crypto::Signature as ExonumJsonDeserialize>::deserialize(
+                    field("signature")?,
+                ).map_err(|e| e.at_field("signature"))?;
+                let message_id = from_value(field("message_id")?.clone())
+                    .map_err(|e| JsonError::from(e).at_field("message_id"))?;
+                let service_id = from_value(field("service_id")?.clone())
+                    .map_err(|e| JsonError::from(e).at_field("service_id"))?;
 
-                let protocol_version = from_value(obj.get("protocol_version")
-                                        .ok_or("Can't get protocol_version from json")?.clone())?;
+                let protocol_version = from_value(field("protocol_version")?.clone())
+                    .map_err(|e| JsonError::from(e).at_field("protocol_version"))?;
 
                 if service_id != <Self as $crate::old_messages::ServiceMessage>::SERVICE_ID {
-                    return Err("service_id didn't equal real service_id.".into())
+                    return Err(JsonError::invalid_value("service_id didn't equal real service_id.")
+                        .at_field("service_id"))
                 }
 
                 if message_id != <Self as $crate::old_messages::ServiceMessage>::MESSAGE_ID {
-                    return Err("message_id didn't equal real message_id.".into())
+                    return Err(JsonError::invalid_value("message_id didn't equal real message_id.")
+                        .at_field("message_id"))
                 }
 
                 let mut writer = MessageWriter::new(
@@ -368,11 +379,18 @@ macro_rules! __ex_message {
                     message_id,
                     $name::__ex_header_size() as usize,
                 );
-                let obj = body.as_object().ok_or("Can't cast body as object.")?;
-                __ex_for_each_field!(
-                    __ex_deserialize_field, (obj, writer),
-                    $( ($(#[$field_attr])*, $field_name, $field_type) )*
-                );
+                {
+                    let mut write_body = || -> ::std::result::Result<(), JsonError> {
+                        let obj = body.as_object().ok_or_else(|| JsonError::wrong_type("object"))?;
+                        let config = $crate::encoding::serialize::text::JsonConfig::default();
+                        __ex_for_each_field!(
+                            __ex_deserialize_field, (obj, writer, config),
+                            $( ($(#[$field_attr])*, $field_name, $field_type) )*
+                        );
+                        Ok(())
+                    };
+                    write_body().map_err(|e| e.at_field("body"))?;
+                }
                 Ok($name { raw: RawMessage::new(writer.append_signature(&signature)) })
             }
         }
@@ -389,7 +407,7 @@ macro_rules! __ex_message {
                 let value = <Value as Deserialize>::deserialize(deserializer)?;
                 <Self as $crate::encoding::serialize::json::ExonumJsonDeserialize>::deserialize(
                     &value).map_err(|e| D::Error::custom(
-                            format!("Can't deserialize a value: {}", e.description())))
+                            format!("Can't deserialize a value: {}", e)))
             }
         }
 
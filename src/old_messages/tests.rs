@@ -12,9 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crypto::{gen_keypair, PublicKey, SecretKey, Signature};
+use crypto::{chain_id, gen_keypair, Domain, PublicKey, SecretKey, Signature, DEFAULT_NETWORK};
 use encoding::serialize::FromHex;
-use messages::{raw::MessageBuffer, Message, RawMessage, RawTransaction};
+use old_messages::{Message, MessageBuffer, RawMessage, RawTransaction};
 
 messages! {
     const SERVICE_ID = 0;
@@ -28,7 +28,7 @@ messages! {
 #[test]
 fn test_debug_transaction() {
     let (p_key, s_key) = gen_keypair();
-    let tx = TxSimple::new(&p_key, "Hello, World!", &s_key);
+    let tx = TxSimple::new(&p_key, "Hello, World!", &chain_id(DEFAULT_NETWORK), &s_key).unwrap();
     let vec = tx.raw().as_ref().to_vec();
     let transaction: RawTransaction = RawTransaction::from_vec(vec);
 
@@ -53,7 +53,7 @@ fn test_message_without_fields() {
         struct NoFields {
         }
     }
-    drop(NoFields::new(&SecretKey::new([1; 64])));
+    drop(NoFields::new(&chain_id(DEFAULT_NETWORK), &SecretKey::new([1; 64])));
 }
 
 #[test]
@@ -84,7 +84,8 @@ fn test_message_with_small_size() {
 #[test]
 fn test_hex_valid_into_message() {
     let keypair = gen_keypair();
-    let msg = TxSimple::new(&keypair.0, "I am a simple!", &keypair.1);
+    let chain_id = chain_id(DEFAULT_NETWORK);
+    let msg = TxSimple::new(&keypair.0, "I am a simple!", &chain_id, &keypair.1).unwrap();
     let hex = msg.to_hex();
     let msg2 = TxSimple::from_hex(hex).expect("Unable to decode hex into `TxFirst`");
     assert_eq!(msg2, msg);
@@ -101,7 +102,8 @@ fn test_hex_wrong_length_into_message() {
         }
     }
     let keypair = gen_keypair();
-    let msg = TxSimple::new(&keypair.0, "I am a simple!", &keypair.1);
+    let chain_id = chain_id(DEFAULT_NETWORK);
+    let msg = TxSimple::new(&keypair.0, "I am a simple!", &chain_id, &keypair.1).unwrap();
     let hex = msg.to_hex();
     let _msg = TxOtherSize::from_hex(hex).unwrap();
 }
@@ -140,7 +142,8 @@ fn test_hex_wrong_id_into_message() {
         }
     }
     let keypair = gen_keypair();
-    let msg = TxSimple::new(&keypair.0, "I am a simple!", &keypair.1);
+    let chain_id = chain_id(DEFAULT_NETWORK);
+    let msg = TxSimple::new(&keypair.0, "I am a simple!", &chain_id, &keypair.1).unwrap();
     let hex = msg.to_hex();
     let _msg = TxOtherId::from_hex(hex).unwrap();
 }
@@ -157,7 +160,34 @@ fn test_hex_wrong_type_into_message() {
         }
     }
     let keypair = gen_keypair();
-    let msg = TxSimple::new(&keypair.0, "I am a simple!", &keypair.1);
+    let chain_id = chain_id(DEFAULT_NETWORK);
+    let msg = TxSimple::new(&keypair.0, "I am a simple!", &chain_id, &keypair.1).unwrap();
     let hex = msg.to_hex();
     let _msg = TxOtherType::from_hex(hex).unwrap();
 }
+
+#[test]
+#[allow(dead_code)]
+fn test_transaction_signature_does_not_verify_as_message() {
+    messages! {
+        const SERVICE_ID = 1;
+        struct TxService {
+            public_key: &PublicKey,
+            msg: &str,
+        }
+    }
+    let (public_key, secret_key) = gen_keypair();
+    let chain_id = chain_id(DEFAULT_NETWORK);
+    let tx = TxService::new(&public_key, "Hello", &chain_id, &secret_key).unwrap();
+    let raw = tx.raw();
+    assert_eq!(raw.domain(), Domain::Transaction);
+    assert!(tx.verify_signature(&chain_id, &public_key));
+    assert!(!Domain::Message.verify(&chain_id, raw.signature(), raw.body(), &public_key));
+    assert_eq!(raw.hash(), Domain::Transaction.hash(raw.as_ref()));
+
+    // Consensus messages are signed in the message domain.
+    let msg = TxSimple::new(&public_key, "Hello", &chain_id, &secret_key).unwrap();
+    let raw = msg.raw();
+    assert_eq!(raw.domain(), Domain::Message);
+    assert!(Domain::Message.verify(&chain_id, raw.signature(), raw.body(), &public_key));
+}
@@ -0,0 +1,116 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Domain separated hashing of the Merkle tree nodes.
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crypto::{Domain, Hash, HashStream};
+
+/// Prefix of the hashed data, which separates leaves of the Merkle trees from their
+/// inner nodes, so a leaf could not be presented as a node and vice versa.
+///
+/// The tags of leaves and list nodes coincide with the `MerkleLeaf` and `MerkleNode`
+/// [`Domain`](../crypto/enum.Domain.html)s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum HashTag {
+    /// Leaf of a tree.
+    Blob = Domain::MerkleLeaf as u8,
+    /// Inner node of a list tree.
+    ListBranchNode = Domain::MerkleNode as u8,
+    /// Root of a list tree, combined with the length of the list.
+    ListNode = 2,
+    /// Root of a map tree.
+    MapNode = 3,
+    /// Inner node of a map tree.
+    MapBranchNode = 4,
+}
+
+impl HashTag {
+    /// Returns a hash of the tree leaf with the given data.
+    pub fn hash_leaf(data: &[u8]) -> Hash {
+        Domain::MerkleLeaf.hash(data)
+    }
+
+    /// Returns a hash of the list node with two children.
+    pub fn hash_node(left: &Hash, right: &Hash) -> Hash {
+        Domain::MerkleNode
+            .hash_stream()
+            .update(left.as_ref())
+            .update(right.as_ref())
+            .hash()
+    }
+
+    /// Returns a hash of the list node with the left child only, which is the last node
+    /// of a level with an odd number of nodes.
+    pub fn hash_single_node(left: &Hash) -> Hash {
+        Domain::MerkleNode
+            .hash_stream()
+            .update(left.as_ref())
+            .hash()
+    }
+
+    /// Returns a hash of the list with the given length and the root of its tree.
+    pub fn hash_list_node(length: u64, root: &Hash) -> Hash {
+        let mut length_bytes = [0; 8];
+        LittleEndian::write_u64(&mut length_bytes, length);
+        HashStream::new()
+            .update(&[HashTag::ListNode as u8])
+            .update(&length_bytes)
+            .update(root.as_ref())
+            .hash()
+    }
+
+    /// Returns a hash of the empty list.
+    pub fn empty_list_hash() -> Hash {
+        HashTag::hash_list_node(0, &Hash::zero())
+    }
+
+    /// Returns a hash of the map node with two children, identified by their paths.
+    pub fn hash_map_branch(
+        left: &Hash,
+        right: &Hash,
+        left_path: &[u8],
+        right_path: &[u8],
+    ) -> Hash {
+        HashStream::new()
+            .update(&[HashTag::MapBranchNode as u8])
+            .update(left.as_ref())
+            .update(right.as_ref())
+            .update(left_path)
+            .update(right_path)
+            .hash()
+    }
+
+    /// Returns a hash of the map with the given root node, identified by its path.
+    /// The root is the leaf of the only entry, if the map contains a single entry.
+    ///
+    /// The path is committed, so the root can't be moved to another subtree in a proof.
+    pub fn hash_map_node(root_path: &[u8], root: &Hash) -> Hash {
+        HashStream::new()
+            .update(&[HashTag::MapNode as u8])
+            .update(root_path)
+            .update(root.as_ref())
+            .hash()
+    }
+
+    /// Returns a hash of the empty map.
+    pub fn empty_map_hash() -> Hash {
+        HashStream::new()
+            .update(&[HashTag::MapNode as u8])
+            .update(Hash::zero().as_ref())
+            .hash()
+    }
+}
@@ -12,7 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! A definition of `StorageValue` trait and implementations for common types.
+//! A definition of `StorageValue` trait and implementations for common types,
+//! and Merkle proofs for the values.
 
 use byteorder::{ByteOrder, LittleEndian};
 use chrono::{DateTime, Duration, NaiveDateTime, Utc};
@@ -24,9 +25,20 @@ use std::borrow::Cow;
 
 use crypto::{Hash, CryptoHash, PublicKey};
 use encoding::{Field, Offset};
+#[cfg(feature = "float_serialize")]
+use encoding::{F32, F64};
 use messages::SignedMessage;
 use types::Round;
 
+pub use self::hash::HashTag;
+pub use self::proof_list::{root_hash, ListProof, ListProofError, MerkleTree, ProofEntry};
+pub use self::proof_map::{MapIter, MapProof, MapProofEntry, MapProofError, MapProofNode,
+                          MerklePatriciaMap};
+
+mod hash;
+mod proof_list;
+mod proof_map;
+
 /// A common trait for the ability to compute a unique hash. Unlike `CryptoHash`, the hash value
 /// returned by the `UniqueHash::hash()` method isn't always irreversible.
 pub trait UniqueHash {
@@ -313,6 +325,42 @@ impl StorageValue for Duration {
     }
 }
 
+/// Uses little-endian encoding.
+///
+/// # Panics
+///
+/// Reading panics if the value is not allowed by `F32`.
+#[cfg(feature = "float_serialize")]
+impl StorageValue for F32 {
+    fn into_bytes(self) -> Vec<u8> {
+        let mut v = vec![0; 4];
+        LittleEndian::write_f32(&mut v, self.get());
+        v
+    }
+
+    fn from_bytes(value: Cow<[u8]>) -> Self {
+        F32::new(LittleEndian::read_f32(value.as_ref()))
+    }
+}
+
+/// Uses little-endian encoding.
+///
+/// # Panics
+///
+/// Reading panics if the value is not allowed by `F64`.
+#[cfg(feature = "float_serialize")]
+impl StorageValue for F64 {
+    fn into_bytes(self) -> Vec<u8> {
+        let mut v = vec![0; 8];
+        LittleEndian::write_f64(&mut v, self.get());
+        v
+    }
+
+    fn from_bytes(value: Cow<[u8]>) -> Self {
+        F64::new(LittleEndian::read_f64(value.as_ref()))
+    }
+}
+
 impl StorageValue for Round {
     fn into_bytes(self) -> Vec<u8> {
         self.0.into_bytes()
@@ -0,0 +1,513 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Binary Merkle tree over a list of hashes and proofs of inclusion of the list items.
+//!
+//! Leaves of the tree are the hashes of the items, tagged with `HashTag::Blob`. Every inner
+//! node is the hash of its children; the last node of a level with an odd number of nodes
+//! has no right child. The hash of the list is the root of the tree combined with
+//! the length of the list, so lists with the different lengths never have the same hash.
+//!
+//! Levels of the tree are numbered from the leaves, which have height 0.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+
+use crypto::{CryptoHash, Hash};
+use super::HashTag;
+
+encoding_struct! {
+    /// Hash of a subtree which doesn't contain the proven items.
+    struct ProofEntry {
+        /// Height of the subtree root.
+        height: u8,
+        /// Index of the subtree root on its level.
+        index: u64,
+        /// Hash of the subtree root.
+        hash: &Hash,
+    }
+}
+
+encoding_struct! {
+    /// Proof of inclusion of the consecutive items into a list.
+    ///
+    /// The proof of the empty list has no items, otherwise at least one item is proven.
+    struct ListProof {
+        /// Length of the list.
+        length: u64,
+        /// Index of the first proven item.
+        first: u64,
+        /// Proven items.
+        items: &[Hash],
+        /// Hashes of the subtrees adjacent to the proven items, ordered by height and index.
+        proof: Vec<ProofEntry>,
+    }
+    where
+        items.is_empty() == (length == 0),
+        first.checked_add(items.len() as u64).map_or(false, |end| end <= length),
+}
+
+/// Error of the `ListProof` verification.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ListProofError {
+    /// Proven items are outside of the list.
+    InvalidRange,
+    /// Proof doesn't contain a hash required to compute the root.
+    MissingHash {
+        /// Height of the node.
+        height: u8,
+        /// Index of the node.
+        index: u64,
+    },
+    /// Proof contains a hash which is not required to compute the root.
+    RedundantHash {
+        /// Height of the node.
+        height: u8,
+        /// Index of the node.
+        index: u64,
+    },
+    /// Hash of the list computed from the proof doesn't match the expected one.
+    UnmatchedRootHash,
+}
+
+impl fmt::Display for ListProofError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ListProofError::InvalidRange => write!(f, "Proven items are outside of the list"),
+            ListProofError::MissingHash { height, index } => {
+                write!(f, "Missing hash of the node {} at height {}", index, height)
+            }
+            ListProofError::RedundantHash { height, index } => {
+                write!(f, "Redundant hash of the node {} at height {}", index, height)
+            }
+            ListProofError::UnmatchedRootHash => write!(f, "Root hash doesn't match"),
+        }
+    }
+}
+
+impl Error for ListProofError {
+    fn description(&self) -> &str {
+        match *self {
+            ListProofError::InvalidRange => "Proven items are outside of the list",
+            ListProofError::MissingHash { .. } => "Missing hash of the node",
+            ListProofError::RedundantHash { .. } => "Redundant hash of the node",
+            ListProofError::UnmatchedRootHash => "Root hash doesn't match",
+        }
+    }
+}
+
+impl ListProof {
+    /// Returns indices and hashes of the proven items.
+    pub fn entries(&self) -> Vec<(u64, Hash)> {
+        let first = self.first();
+        self.items()
+            .iter()
+            .enumerate()
+            .map(|(i, hash)| (first + i as u64, *hash))
+            .collect()
+    }
+
+    /// Computes the hash of the list from the proof.
+    pub fn list_hash(&self) -> Result<Hash, ListProofError> {
+        let length = self.length();
+        let items = self.items();
+        let mut proof = BTreeMap::new();
+        for entry in self.proof() {
+            let key = (entry.height(), entry.index());
+            if proof.insert(key, *entry.hash()).is_some() {
+                return Err(ListProofError::RedundantHash {
+                    height: key.0,
+                    index: key.1,
+                });
+            }
+        }
+
+        let first = self.first();
+        let end = first.checked_add(items.len() as u64);
+        if items.is_empty() != (length == 0) || end.map_or(true, |end| end > length) {
+            return Err(ListProofError::InvalidRange);
+        }
+        if length == 0 {
+            return match proof.keys().next() {
+                Some(&(height, index)) => Err(ListProofError::RedundantHash { height, index }),
+                None => Ok(HashTag::empty_list_hash()),
+            };
+        }
+
+        let mut nodes: Vec<Hash> = items
+            .iter()
+            .map(|hash| HashTag::hash_leaf(hash.as_ref()))
+            .collect();
+        let mut start = first;
+        let mut level_len = length;
+        let mut height = 0;
+        while level_len > 1 {
+            let mut take = |index| {
+                proof
+                    .remove(&(height, index))
+                    .ok_or(ListProofError::MissingHash { height, index })
+            };
+            if start % 2 == 1 {
+                start -= 1;
+                nodes.insert(0, take(start)?);
+            }
+            let end = start + nodes.len() as u64;
+            if end % 2 == 1 && end < level_len {
+                nodes.push(take(end)?);
+            }
+
+            nodes = parent_level(&nodes);
+            start /= 2;
+            level_len = (level_len + 1) / 2;
+            height += 1;
+        }
+
+        if let Some(&(height, index)) = proof.keys().next() {
+            return Err(ListProofError::RedundantHash { height, index });
+        }
+        Ok(HashTag::hash_list_node(length, &nodes[0]))
+    }
+
+    /// Checks the proof against the expected hash of the list and returns
+    /// indices and hashes of the proven items.
+    pub fn validate(&self, list_hash: &Hash) -> Result<Vec<(u64, Hash)>, ListProofError> {
+        if self.list_hash()? != *list_hash {
+            return Err(ListProofError::UnmatchedRootHash);
+        }
+        Ok(self.entries())
+    }
+}
+
+/// Merkle tree over a list of hashes, which builds proofs for the list items.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate exonum;
+/// use exonum::crypto::hash;
+/// use exonum::storage::MerkleTree;
+///
+/// # fn main() {
+/// let hashes = vec![hash(&[1]), hash(&[2]), hash(&[3])];
+/// let tree = MerkleTree::new(&hashes);
+///
+/// let proof = tree.get_proof(1).unwrap();
+/// assert_eq!(proof.validate(&tree.root_hash()).unwrap(), vec![(1, hashes[1])]);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct MerkleTree {
+    hashes: Vec<Hash>,
+    // Levels from the leaves to the root, the empty list has no levels.
+    levels: Vec<Vec<Hash>>,
+}
+
+impl MerkleTree {
+    /// Builds a tree over the hashes of the list items.
+    pub fn new(hashes: &[Hash]) -> Self {
+        let mut levels = Vec::new();
+        if !hashes.is_empty() {
+            levels.push(
+                hashes
+                    .iter()
+                    .map(|hash| HashTag::hash_leaf(hash.as_ref()))
+                    .collect::<Vec<_>>(),
+            );
+        }
+        while levels.last().map_or(false, |level| level.len() > 1) {
+            let next = parent_level(&levels[levels.len() - 1]);
+            levels.push(next);
+        }
+        MerkleTree {
+            hashes: hashes.to_vec(),
+            levels,
+        }
+    }
+
+    /// Builds a tree over the list items, items are represented by their `CryptoHash`.
+    pub fn from_items<T: CryptoHash>(items: &[T]) -> Self {
+        let hashes = items.iter().map(CryptoHash::hash).collect::<Vec<_>>();
+        MerkleTree::new(&hashes)
+    }
+
+    /// Returns the length of the list.
+    pub fn len(&self) -> u64 {
+        self.hashes.len() as u64
+    }
+
+    /// Returns `true` if the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    /// Returns the hash of the list.
+    pub fn root_hash(&self) -> Hash {
+        match self.levels.last() {
+            Some(root) => HashTag::hash_list_node(self.len(), &root[0]),
+            None => HashTag::empty_list_hash(),
+        }
+    }
+
+    /// Returns the proof of inclusion of the item with the given index,
+    /// or `None` if the index is out of bounds.
+    pub fn get_proof(&self, index: u64) -> Option<ListProof> {
+        let end = index.checked_add(1)?;
+        self.get_range_proof(index..end)
+    }
+
+    /// Returns the proof of inclusion of the items in the given range, or `None` if the range
+    /// is out of bounds or empty. The empty range could be proven only for the empty list.
+    pub fn get_range_proof(&self, range: Range<u64>) -> Option<ListProof> {
+        let length = self.len();
+        if range.start > range.end || range.end > length {
+            return None;
+        }
+        if length == 0 {
+            return Some(ListProof::new(0, 0, &[], Vec::new()));
+        }
+        if range.start == range.end {
+            return None;
+        }
+
+        let items = &self.hashes[range.start as usize..range.end as usize];
+        let mut proof = Vec::new();
+        let (mut start, mut end) = (range.start, range.end);
+        for (height, level) in self.levels.iter().enumerate() {
+            let level_len = level.len() as u64;
+            if level_len == 1 {
+                break;
+            }
+            if start % 2 == 1 {
+                start -= 1;
+                proof.push(ProofEntry::new(height as u8, start, &level[start as usize]));
+            }
+            if end % 2 == 1 && end < level_len {
+                proof.push(ProofEntry::new(height as u8, end, &level[end as usize]));
+                end += 1;
+            }
+            start /= 2;
+            end = (end + 1) / 2;
+        }
+        Some(ListProof::new(length, range.start, items, proof))
+    }
+
+    /// Returns the hashes of the list items.
+    pub fn hashes(&self) -> &[Hash] {
+        &self.hashes
+    }
+}
+
+/// Returns the hash of the list with the given item hashes, e.g. the `tx_hash` of a block.
+pub fn root_hash(hashes: &[Hash]) -> Hash {
+    MerkleTree::new(hashes).root_hash()
+}
+
+fn parent_level(nodes: &[Hash]) -> Vec<Hash> {
+    nodes
+        .chunks(2)
+        .map(|pair| match *pair {
+            [ref left, ref right] => HashTag::hash_node(left, right),
+            [ref left] => HashTag::hash_single_node(left),
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+
+    use crypto::{hash, CryptoHash, Hash};
+    use encoding::serialize::json::ExonumJson;
+    use encoding::Field;
+    use messages::Block;
+    use storage::{HashTag, StorageValue};
+    use types::{Height, ValidatorId};
+
+    use super::*;
+
+    fn hashes(len: usize) -> Vec<Hash> {
+        (0..len).map(|i| hash(&[i as u8])).collect()
+    }
+
+    #[test]
+    fn test_root_hash() {
+        assert_eq!(root_hash(&[]), HashTag::hash_list_node(0, &Hash::zero()));
+
+        let items = hashes(3);
+        let leaves = items
+            .iter()
+            .map(|hash| HashTag::hash_leaf(hash.as_ref()))
+            .collect::<Vec<_>>();
+        let root = HashTag::hash_node(
+            &HashTag::hash_node(&leaves[0], &leaves[1]),
+            &HashTag::hash_single_node(&leaves[2]),
+        );
+        assert_eq!(root_hash(&items), HashTag::hash_list_node(3, &root));
+        assert_eq!(
+            root_hash(&items[..1]),
+            HashTag::hash_list_node(1, &leaves[0])
+        );
+
+        // Leaves and nodes are never confused.
+        assert_ne!(leaves[0], items[0]);
+        assert_ne!(root_hash(&items[..2]), root_hash(&[root_hash(&items[..2])]));
+    }
+
+    #[test]
+    fn test_proofs_of_every_range() {
+        for len in 0..10 {
+            let items = hashes(len);
+            let tree = MerkleTree::new(&items);
+            assert_eq!(tree.len(), len as u64);
+            assert_eq!(tree.root_hash(), root_hash(&items));
+
+            for start in 0..len {
+                for end in start + 1..len + 1 {
+                    let proof = tree.get_range_proof(start as u64..end as u64).unwrap();
+                    let expected = (start..end)
+                        .map(|i| (i as u64, items[i]))
+                        .collect::<Vec<_>>();
+                    assert_eq!(proof.validate(&tree.root_hash()), Ok(expected));
+                }
+            }
+            assert!(tree.get_proof(len as u64).is_none());
+        }
+
+        let tree = MerkleTree::new(&[]);
+        let proof = tree.get_range_proof(0..0).unwrap();
+        assert_eq!(proof.validate(&tree.root_hash()), Ok(vec![]));
+        assert!(MerkleTree::new(&hashes(2)).get_range_proof(1..1).is_none());
+    }
+
+    #[test]
+    fn test_proof_of_max_index() {
+        for len in 0..3 {
+            let tree = MerkleTree::new(&hashes(len));
+            assert!(tree.get_proof(u64::max_value()).is_none());
+            assert!(tree.get_range_proof(0..u64::max_value()).is_none());
+        }
+    }
+
+    #[test]
+    fn test_proof_size_is_logarithmic() {
+        let tree = MerkleTree::new(&hashes(1000));
+        for index in 0..1000 {
+            assert!(tree.get_proof(index).unwrap().proof().len() <= 10);
+        }
+    }
+
+    #[test]
+    fn test_invalid_proofs() {
+        let items = hashes(5);
+        let tree = MerkleTree::new(&items);
+        let root = tree.root_hash();
+        let proof = tree.get_proof(2).unwrap();
+
+        let other_item = ListProof::new(5, 2, &[hash(&[10])], proof.proof());
+        assert_eq!(
+            other_item.validate(&root),
+            Err(ListProofError::UnmatchedRootHash)
+        );
+        let other_index = ListProof::new(5, 3, proof.items(), proof.proof());
+        assert_eq!(
+            other_index.validate(&root),
+            Err(ListProofError::MissingHash {
+                height: 0,
+                index: 2,
+            })
+        );
+        let other_length = ListProof::new(6, 2, proof.items(), proof.proof());
+        assert_eq!(
+            other_length.validate(&root),
+            Err(ListProofError::UnmatchedRootHash)
+        );
+        let out_of_range = ListProof::new(2, 2, proof.items(), proof.proof());
+        assert_eq!(out_of_range.validate(&root), Err(ListProofError::InvalidRange));
+
+        let mut entries = proof.proof();
+        let missing = entries.remove(0);
+        assert_eq!(
+            ListProof::new(5, 2, proof.items(), entries.clone()).validate(&root),
+            Err(ListProofError::MissingHash {
+                height: missing.height(),
+                index: missing.index(),
+            })
+        );
+        entries.insert(0, missing.clone());
+        entries.push(ProofEntry::new(5, 0, &hash(&[])));
+        assert_eq!(
+            ListProof::new(5, 2, proof.items(), entries).validate(&root),
+            Err(ListProofError::RedundantHash {
+                height: 5,
+                index: 0,
+            })
+        );
+        let mut entries = proof.proof();
+        entries.push(missing);
+        assert_eq!(
+            ListProof::new(5, 2, proof.items(), entries).validate(&root),
+            Err(ListProofError::RedundantHash {
+                height: 0,
+                index: 3,
+            })
+        );
+    }
+
+    #[test]
+    fn test_proof_json_and_binary() {
+        let tree = MerkleTree::new(&hashes(7));
+        let proof = tree.get_range_proof(2..5).unwrap();
+
+        let json = serde_json::to_string(&proof).unwrap();
+        let parsed: ListProof = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, proof);
+        assert_eq!(parsed.serialize_field().unwrap(), proof.serialize_field().unwrap());
+
+        let bytes = proof.clone().into_bytes();
+        assert_eq!(ListProof::from_bytes(bytes.into()), proof);
+
+        // Proven items should be inside the list.
+        let mut json = proof.serialize_field().unwrap();
+        json["first"] = serde_json::to_value("5").unwrap();
+        assert!(serde_json::from_value::<ListProof>(json).is_err());
+
+        let mut buffer = vec![0; 8];
+        ListProof::new(0, 1, &[], vec![]).write(&mut buffer, 0, 8);
+        assert!(<ListProof as Field>::check(&buffer, 0.into(), 8.into(), 8.into()).is_err());
+    }
+
+    #[test]
+    fn test_block_tx_hash() {
+        let transactions = (0..4).map(|i| vec![i; 10]).collect::<Vec<Vec<u8>>>();
+        let tree = MerkleTree::from_items(&transactions);
+        let block = Block::new(
+            0,
+            ValidatorId(0),
+            Height(1),
+            transactions.len() as u32,
+            &hash(&[]),
+            &tree.root_hash(),
+            &hash(&[]),
+        );
+
+        let proof = tree.get_proof(3).unwrap();
+        assert_eq!(
+            proof.validate(block.tx_hash()).unwrap(),
+            vec![(3, transactions[3].hash())]
+        );
+    }
+}
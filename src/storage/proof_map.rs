@@ -0,0 +1,701 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Merkelized map based on a binary Patricia trie, and proofs of presence and absence
+//! of the map keys.
+//!
+//! The trie is keyed by the 256-bit hash of the key, bits of the hash are taken from
+//! the most significant bit of the first byte. Leaves of the trie are the hashes of
+//! the values, tagged with `HashTag::Blob`. Every branch node splits the keys at the first
+//! bit in which they differ, and its hash covers the hashes and the paths of both children.
+//! The path of a child is the common prefix of its keys; a path is encoded as its length
+//! in bits (`u16`, little-endian) followed by the bytes of the prefix. The hash of the map
+//! covers the path and the hash of the root node, tagged with `HashTag::MapNode`.
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use std::cmp;
+use std::collections::btree_map::{self, BTreeMap};
+use std::error::Error;
+use std::fmt;
+
+use crypto::{CryptoHash, Hash, HASH_SIZE};
+use super::{HashTag, StorageValue};
+
+const KEY_BITS: u16 = 256;
+
+encoding_struct! {
+    /// Proven entry of the map.
+    struct MapProofEntry {
+        /// Hash of the key.
+        key: &Hash,
+        /// Serialized value.
+        value: &[u8],
+    }
+}
+
+encoding_struct! {
+    /// Hash of a subtree which doesn't contain the proven keys.
+    struct MapProofNode {
+        /// Common prefix of the subtree keys, the bits after the prefix are zero.
+        path: &Hash,
+        /// Length of the prefix in bits.
+        path_len: u16,
+        /// Hash of the subtree root.
+        hash: &Hash,
+    }
+    where ProofPath::from_parts(path, path_len).is_some()
+}
+
+encoding_struct! {
+    /// Proof of presence and absence of the keys in a map.
+    struct MapProof {
+        /// Entries of the present keys.
+        entries: Vec<MapProofEntry>,
+        /// Hashes of the absent keys.
+        missing_keys: &[Hash],
+        /// Hashes of the subtrees adjacent to the proven keys, ordered by their paths.
+        proof: Vec<MapProofNode>,
+    }
+}
+
+/// Error of the `MapProof` verification.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MapProofError {
+    /// Path of a node is longer than a key or has non-zero bits after its length.
+    InvalidPath,
+    /// Several entries or nodes have the same path.
+    DuplicatePath,
+    /// Path of a node is a prefix of the path of another node or entry.
+    EmbeddedPaths,
+    /// Key declared absent is covered by an entry or a node of the proof.
+    NonAbsence {
+        /// Hash of the key.
+        key: Hash,
+    },
+    /// Key is neither present nor absent in the proof.
+    UnprovenKey {
+        /// Hash of the key.
+        key: Hash,
+    },
+    /// Hash of the map computed from the proof doesn't match the expected one.
+    UnmatchedRootHash,
+}
+
+impl fmt::Display for MapProofError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MapProofError::InvalidPath => write!(f, "Invalid path of a proof node"),
+            MapProofError::DuplicatePath => write!(f, "Duplicate path in the proof"),
+            MapProofError::EmbeddedPaths => write!(f, "Embedded paths in the proof"),
+            MapProofError::NonAbsence { ref key } => {
+                write!(f, "Key {:?} is declared absent, but it is covered by the proof", key)
+            }
+            MapProofError::UnprovenKey { ref key } => {
+                write!(f, "Key {:?} is not proven", key)
+            }
+            MapProofError::UnmatchedRootHash => write!(f, "Root hash doesn't match"),
+        }
+    }
+}
+
+impl Error for MapProofError {
+    fn description(&self) -> &str {
+        match *self {
+            MapProofError::InvalidPath => "Invalid path of a proof node",
+            MapProofError::DuplicatePath => "Duplicate path in the proof",
+            MapProofError::EmbeddedPaths => "Embedded paths in the proof",
+            MapProofError::NonAbsence { .. } => "Absent key is covered by the proof",
+            MapProofError::UnprovenKey { .. } => "Key is not proven",
+            MapProofError::UnmatchedRootHash => "Root hash doesn't match",
+        }
+    }
+}
+
+// Prefix of a key path, the bits after the prefix are zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct ProofPath {
+    bytes: [u8; HASH_SIZE],
+    len: u16,
+}
+
+impl ProofPath {
+    fn new(key: &Hash) -> Self {
+        let mut bytes = [0; HASH_SIZE];
+        bytes.copy_from_slice(key.as_ref());
+        ProofPath {
+            bytes,
+            len: KEY_BITS,
+        }
+    }
+
+    fn from_parts(path: &Hash, len: u16) -> Option<Self> {
+        if len > KEY_BITS {
+            return None;
+        }
+        let path = ProofPath {
+            len,
+            ..ProofPath::new(path)
+        };
+        if path.prefix(len) == path {
+            Some(path)
+        } else {
+            None
+        }
+    }
+
+    fn bit(&self, index: u16) -> u8 {
+        (self.bytes[index as usize / 8] >> (7 - index % 8)) & 1
+    }
+
+    fn common_prefix_len(&self, other: &ProofPath) -> u16 {
+        let len = cmp::min(self.len, other.len);
+        (0..len)
+            .find(|&i| self.bit(i) != other.bit(i))
+            .unwrap_or(len)
+    }
+
+    fn starts_with(&self, prefix: &ProofPath) -> bool {
+        prefix.len <= self.len && self.common_prefix_len(prefix) == prefix.len
+    }
+
+    fn prefix(&self, len: u16) -> Self {
+        let mut bytes = [0; HASH_SIZE];
+        let full_bytes = len as usize / 8;
+        bytes[..full_bytes].copy_from_slice(&self.bytes[..full_bytes]);
+        if len % 8 != 0 {
+            bytes[full_bytes] = self.bytes[full_bytes] & !(0xff >> (len % 8));
+        }
+        ProofPath { bytes, len }
+    }
+
+    fn hash(&self) -> Hash {
+        Hash::new(self.bytes)
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut buffer = vec![0; 2];
+        LittleEndian::write_u16(&mut buffer, self.len);
+        buffer.extend_from_slice(&self.bytes[..(self.len as usize + 7) / 8]);
+        buffer
+    }
+}
+
+// Computes the root of the subtree with the given disjoint nodes, ordered by their paths.
+fn subtree_root(nodes: &[(ProofPath, Hash)]) -> (ProofPath, Hash) {
+    if nodes.len() == 1 {
+        return nodes[0];
+    }
+    let branch_len = nodes[0].0.common_prefix_len(&nodes[nodes.len() - 1].0);
+    let split = nodes
+        .iter()
+        .position(|&(ref path, _)| path.bit(branch_len) == 1)
+        .expect("Nodes should differ in the branch bit");
+    let (left_path, left_hash) = subtree_root(&nodes[..split]);
+    let (right_path, right_hash) = subtree_root(&nodes[split..]);
+    let hash = HashTag::hash_map_branch(
+        &left_hash,
+        &right_hash,
+        &left_path.encode(),
+        &right_path.encode(),
+    );
+    (nodes[0].0.prefix(branch_len), hash)
+}
+
+fn map_root(nodes: &[(ProofPath, Hash)]) -> Hash {
+    if nodes.is_empty() {
+        return HashTag::empty_map_hash();
+    }
+    let (path, hash) = subtree_root(nodes);
+    HashTag::hash_map_node(&path.encode(), &hash)
+}
+
+// Adds nodes of the subtree which don't contain the keys to the proof.
+fn collect_proof(
+    leaves: &[(ProofPath, Hash)],
+    keys: &[ProofPath],
+    proof: &mut Vec<MapProofNode>,
+) {
+    let path = if leaves.len() == 1 {
+        leaves[0].0
+    } else {
+        leaves[0].0.prefix(leaves[0].0.common_prefix_len(&leaves[leaves.len() - 1].0))
+    };
+    let keys = keys.iter()
+        .filter(|key| key.starts_with(&path))
+        .cloned()
+        .collect::<Vec<_>>();
+    if keys.is_empty() {
+        let (_, hash) = subtree_root(leaves);
+        proof.push(MapProofNode::new(&path.hash(), path.len, &hash));
+    } else if leaves.len() > 1 {
+        let split = leaves
+            .iter()
+            .position(|&(ref leaf, _)| leaf.bit(path.len) == 1)
+            .expect("Leaves should differ in the branch bit");
+        collect_proof(&leaves[..split], &keys, proof);
+        collect_proof(&leaves[split..], &keys, proof);
+    }
+}
+
+impl MapProof {
+    /// Computes the hash of the map from the proof.
+    pub fn map_hash(&self) -> Result<Hash, MapProofError> {
+        let mut nodes = Vec::new();
+        for entry in self.entries() {
+            nodes.push((ProofPath::new(entry.key()), HashTag::hash_leaf(entry.value())));
+        }
+        for node in self.proof() {
+            let path = ProofPath::from_parts(node.path(), node.path_len())
+                .ok_or(MapProofError::InvalidPath)?;
+            nodes.push((path, *node.hash()));
+        }
+        nodes.sort_by(|a, b| a.0.cmp(&b.0));
+
+        for pair in nodes.windows(2) {
+            if pair[0].0 == pair[1].0 {
+                return Err(MapProofError::DuplicatePath);
+            } else if pair[1].0.starts_with(&pair[0].0) {
+                return Err(MapProofError::EmbeddedPaths);
+            }
+        }
+        for key in self.missing_keys() {
+            let path = ProofPath::new(key);
+            if nodes.iter().any(|&(ref node, _)| path.starts_with(node)) {
+                return Err(MapProofError::NonAbsence { key: *key });
+            }
+        }
+        Ok(map_root(&nodes))
+    }
+
+    /// Checks the proof against the expected hash of the map and returns the hashes
+    /// of the proven keys with their serialized values, `None` for absent keys.
+    pub fn validate(
+        &self,
+        map_hash: &Hash,
+    ) -> Result<Vec<(Hash, Option<Vec<u8>>)>, MapProofError> {
+        if self.map_hash()? != *map_hash {
+            return Err(MapProofError::UnmatchedRootHash);
+        }
+        let mut result = self.entries()
+            .iter()
+            .map(|entry| (*entry.key(), Some(entry.value().to_vec())))
+            .collect::<Vec<_>>();
+        result.extend(self.missing_keys().iter().map(|key| (*key, None)));
+        Ok(result)
+    }
+
+    /// Checks the proof against the expected hash of the map and returns the value
+    /// of the key, or `None` if the key is proven to be absent.
+    pub fn get<K, V>(&self, map_hash: &Hash, key: &K) -> Result<Option<V>, MapProofError>
+    where
+        K: CryptoHash,
+        V: StorageValue,
+    {
+        let key = key.hash();
+        self.validate(map_hash)?
+            .into_iter()
+            .find(|&(ref proven, _)| *proven == key)
+            .map(|(_, value)| value.map(|value| V::from_bytes(value.into())))
+            .ok_or(MapProofError::UnprovenKey { key })
+    }
+}
+
+/// Map which computes the Merkle root of its entries and builds proofs for its keys.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate exonum;
+/// use exonum::crypto::gen_keypair;
+/// use exonum::storage::MerklePatriciaMap;
+///
+/// # fn main() {
+/// let (alice, _) = gen_keypair();
+/// let (bob, _) = gen_keypair();
+/// let mut balances = MerklePatriciaMap::new();
+/// balances.insert(alice, 100_u64);
+///
+/// let proof = balances.get_multiproof(&[alice, bob]);
+/// let root_hash = balances.root_hash();
+/// assert_eq!(proof.get(&root_hash, &alice), Ok(Some(100_u64)));
+/// assert_eq!(proof.get::<_, u64>(&root_hash, &bob), Ok(None));
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct MerklePatriciaMap<K, V> {
+    // Entries by the hash of the key, with the hash of the serialized value.
+    entries: BTreeMap<Hash, (K, V, Hash)>,
+}
+
+impl<K, V> Default for MerklePatriciaMap<K, V> {
+    fn default() -> Self {
+        MerklePatriciaMap {
+            entries: BTreeMap::new(),
+        }
+    }
+}
+
+impl<K, V> MerklePatriciaMap<K, V>
+where
+    K: CryptoHash,
+    V: StorageValue + Clone,
+{
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the value of the key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.entries.get(&key.hash()).map(|&(_, ref value, _)| value)
+    }
+
+    /// Returns `true` if the map contains the key.
+    pub fn contains(&self, key: &K) -> bool {
+        self.entries.contains_key(&key.hash())
+    }
+
+    /// Inserts the value and returns the previous value of the key.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let value_hash = HashTag::hash_leaf(&value.clone().into_bytes());
+        self.entries
+            .insert(key.hash(), (key, value, value_hash))
+            .map(|(_, value, _)| value)
+    }
+
+    /// Removes the key and returns its value.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.entries.remove(&key.hash()).map(|(_, value, _)| value)
+    }
+
+    /// Returns an iterator over the entries, ordered by the hashes of the keys.
+    pub fn iter(&self) -> MapIter<K, V> {
+        MapIter {
+            inner: self.entries.values(),
+        }
+    }
+
+    /// Returns the hash of the map, e.g. the `state_hash` of a block.
+    pub fn root_hash(&self) -> Hash {
+        map_root(&self.leaves())
+    }
+
+    /// Returns the proof of presence or absence of the key.
+    pub fn get_proof(&self, key: &K) -> MapProof {
+        self.get_multiproof(::std::slice::from_ref(key))
+    }
+
+    /// Returns the proof of presence or absence of the keys.
+    pub fn get_multiproof(&self, keys: &[K]) -> MapProof {
+        let mut paths = keys.iter()
+            .map(|key| ProofPath::new(&key.hash()))
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths.dedup();
+
+        let mut entries = Vec::new();
+        let mut missing_keys = Vec::new();
+        for path in &paths {
+            let key = path.hash();
+            match self.entries.get(&key) {
+                Some(&(_, ref value, _)) => {
+                    entries.push(MapProofEntry::new(&key, &value.clone().into_bytes()))
+                }
+                None => missing_keys.push(key),
+            }
+        }
+
+        let mut proof = Vec::new();
+        let leaves = self.leaves();
+        if !leaves.is_empty() {
+            collect_proof(&leaves, &paths, &mut proof);
+        }
+        MapProof::new(entries, &missing_keys, proof)
+    }
+
+    fn leaves(&self) -> Vec<(ProofPath, Hash)> {
+        self.entries
+            .iter()
+            .map(|(key, &(_, _, ref value_hash))| (ProofPath::new(key), *value_hash))
+            .collect()
+    }
+}
+
+/// Iterator over the entries of a `MerklePatriciaMap`.
+#[derive(Debug)]
+pub struct MapIter<'a, K: 'a, V: 'a> {
+    inner: btree_map::Values<'a, Hash, (K, V, Hash)>,
+}
+
+impl<'a, K, V> Iterator for MapIter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|&(ref key, ref value, _)| (key, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+
+    use crypto::{gen_keypair_from_seed, hash, CryptoHash, Hash, PublicKey, Seed};
+    use encoding::serialize::json::ExonumJson;
+    use messages::Block;
+    use storage::{HashTag, StorageValue};
+    use types::{Height, ValidatorId};
+
+    use super::*;
+
+    fn keys(count: u8) -> Vec<PublicKey> {
+        (0..count)
+            .map(|i| gen_keypair_from_seed(&Seed::new([i; 32])).0)
+            .collect()
+    }
+
+    fn balances(count: u8) -> MerklePatriciaMap<PublicKey, u64> {
+        let mut map = MerklePatriciaMap::new();
+        for (i, key) in keys(count).into_iter().enumerate() {
+            map.insert(key, i as u64 * 10);
+        }
+        map
+    }
+
+    #[test]
+    fn test_root_hash() {
+        let mut map = MerklePatriciaMap::new();
+        assert_eq!(map.root_hash(), HashTag::empty_map_hash());
+
+        let keys = keys(2);
+        map.insert(keys[0], 1_u64);
+        let path = ProofPath::new(&keys[0].hash());
+        assert_eq!(
+            map.root_hash(),
+            HashTag::hash_map_node(&path.encode(), &HashTag::hash_leaf(&1_u64.into_bytes()))
+        );
+
+        map.insert(keys[1], 2_u64);
+        let mut paths = vec![
+            (ProofPath::new(&keys[0].hash()), HashTag::hash_leaf(&1_u64.into_bytes())),
+            (ProofPath::new(&keys[1].hash()), HashTag::hash_leaf(&2_u64.into_bytes())),
+        ];
+        paths.sort();
+        let branch = HashTag::hash_map_branch(
+            &paths[0].1,
+            &paths[1].1,
+            &paths[0].0.encode(),
+            &paths[1].0.encode(),
+        );
+        let root_path = paths[0].0.prefix(paths[0].0.common_prefix_len(&paths[1].0));
+        assert_eq!(map.root_hash(), HashTag::hash_map_node(&root_path.encode(), &branch));
+    }
+
+    #[test]
+    fn test_relocated_root_is_rejected() {
+        // Keys of the map have the same first bit, so the root path is not empty.
+        let keys = keys(16);
+        let first_bit = |key: &PublicKey| ProofPath::new(&key.hash()).bit(0);
+        let present = keys.iter()
+            .filter(|key| first_bit(key) == 0)
+            .take(2)
+            .cloned()
+            .collect::<Vec<_>>();
+        let absent = keys.iter().find(|key| first_bit(key) == 1).unwrap();
+        let mut map = MerklePatriciaMap::new();
+        for key in &present {
+            map.insert(*key, 1_u64);
+        }
+        let root_hash = map.root_hash();
+        let proof = map.get_proof(absent);
+        assert_eq!(proof.get::<_, u64>(&root_hash, absent), Ok(None));
+        assert_eq!(proof.proof().len(), 1);
+
+        // The root is moved to the opposite subtree to declare a present key absent.
+        let node = &proof.proof()[0];
+        let mut path = node.path().as_ref().to_vec();
+        path[0] ^= 0x80;
+        let relocated = MapProofNode::new(
+            &Hash::from_slice(&path).unwrap(),
+            node.path_len(),
+            node.hash(),
+        );
+        let forged = MapProof::new(Vec::new(), &[present[0].hash()], vec![relocated]);
+        assert_eq!(
+            forged.get::<_, u64>(&root_hash, &present[0]),
+            Err(MapProofError::UnmatchedRootHash)
+        );
+    }
+
+    #[test]
+    fn test_insert_and_remove() {
+        let keys = keys(16);
+        let mut map = balances(16);
+        assert_eq!(map.len(), 16);
+        assert_eq!(map.get(&keys[3]), Some(&30));
+        let root_hash = map.root_hash();
+
+        // The hash doesn't depend on the order of insertions.
+        let mut reversed = MerklePatriciaMap::new();
+        for (i, key) in keys.iter().enumerate().rev() {
+            reversed.insert(*key, i as u64 * 10);
+        }
+        assert_eq!(reversed.root_hash(), root_hash);
+
+        assert_eq!(map.insert(keys[3], 31), Some(30));
+        assert_ne!(map.root_hash(), root_hash);
+        assert_eq!(map.insert(keys[3], 30), Some(31));
+        assert_eq!(map.root_hash(), root_hash);
+
+        assert_eq!(map.remove(&keys[5]), Some(50));
+        assert!(!map.contains(&keys[5]));
+        assert_eq!(map.root_hash(), {
+            let mut expected = balances(16);
+            expected.entries.remove(&keys[5].hash());
+            expected.root_hash()
+        });
+        assert_eq!(map.remove(&keys[5]), None);
+        assert_eq!(map.iter().count(), 15);
+    }
+
+    #[test]
+    fn test_proofs() {
+        let keys = keys(24);
+        for count in 0..17 {
+            let map = balances(count);
+            let root_hash = map.root_hash();
+            for i in 0..keys.len() {
+                let proof = map.get_proof(&keys[i]);
+                let expected = map.get(&keys[i]).cloned();
+                assert_eq!(proof.get(&root_hash, &keys[i]), Ok(expected));
+                assert!(proof.proof().len() <= count as usize);
+            }
+
+            let requested = keys.iter().cloned().step_by(3).collect::<Vec<_>>();
+            let proof = map.get_multiproof(&requested);
+            assert_eq!(proof.validate(&root_hash).unwrap().len(), requested.len());
+            for key in &requested {
+                assert_eq!(proof.get(&root_hash, key), Ok(map.get(key).cloned()));
+            }
+            assert_eq!(
+                proof.get::<_, u64>(&root_hash, &keys[1]),
+                Err(MapProofError::UnprovenKey {
+                    key: keys[1].hash(),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_proofs() {
+        let keys = keys(10);
+        let map = balances(8);
+        let root_hash = map.root_hash();
+        let proof = map.get_multiproof(&[keys[2], keys[9]]);
+        assert!(proof.validate(&root_hash).is_ok());
+
+        let other_value = MapProofEntry::new(&keys[2].hash(), &21_u64.into_bytes());
+        let forged = MapProof::new(vec![other_value], proof.missing_keys(), proof.proof());
+        assert_eq!(forged.validate(&root_hash), Err(MapProofError::UnmatchedRootHash));
+
+        let mut nodes = proof.proof();
+        nodes.pop();
+        let forged = MapProof::new(proof.entries(), proof.missing_keys(), nodes);
+        assert_eq!(forged.validate(&root_hash), Err(MapProofError::UnmatchedRootHash));
+
+        // Present key can't be proven absent.
+        let missing = vec![keys[9].hash(), keys[4].hash()];
+        let forged = MapProof::new(proof.entries(), &missing, proof.proof());
+        assert_eq!(
+            forged.validate(&root_hash),
+            Err(MapProofError::NonAbsence {
+                key: keys[4].hash(),
+            })
+        );
+        let missing = vec![keys[2].hash()];
+        let forged = MapProof::new(proof.entries(), &missing, proof.proof());
+        assert_eq!(
+            forged.validate(&root_hash),
+            Err(MapProofError::NonAbsence {
+                key: keys[2].hash(),
+            })
+        );
+
+        let mut nodes = proof.proof();
+        nodes.push(nodes[0].clone());
+        let forged = MapProof::new(proof.entries(), proof.missing_keys(), nodes);
+        assert_eq!(forged.validate(&root_hash), Err(MapProofError::DuplicatePath));
+
+        let mut nodes = proof.proof();
+        let embedded = ProofPath::new(&keys[2].hash()).prefix(4);
+        nodes.push(MapProofNode::new(&embedded.hash(), 4, &hash(&[])));
+        let forged = MapProof::new(proof.entries(), proof.missing_keys(), nodes);
+        assert_eq!(forged.validate(&root_hash), Err(MapProofError::EmbeddedPaths));
+
+        let invalid = MapProofNode::new(&hash(&[]), 3, &hash(&[]));
+        let forged = MapProof::new(vec![], &[], vec![invalid]);
+        assert_eq!(forged.validate(&root_hash), Err(MapProofError::InvalidPath));
+    }
+
+    #[test]
+    fn test_proof_json_and_binary() {
+        let keys = keys(6);
+        let map = balances(5);
+        let proof = map.get_multiproof(&keys[3..]);
+
+        let json = serde_json::to_string(&proof).unwrap();
+        let parsed: MapProof = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, proof);
+        assert_eq!(parsed.get(&map.root_hash(), &keys[3]), Ok(Some(30_u64)));
+        assert_eq!(parsed.get::<_, u64>(&map.root_hash(), &keys[5]), Ok(None));
+
+        let bytes = proof.clone().into_bytes();
+        assert_eq!(MapProof::from_bytes(bytes.into()), proof);
+
+        // Bits after the path length should be zero.
+        let mut json = MapProofNode::new(&keys[0].hash(), 256, &hash(&[]))
+            .serialize_field()
+            .unwrap();
+        json["path_len"] = serde_json::to_value(8).unwrap();
+        assert!(serde_json::from_value::<MapProofNode>(json).is_err());
+    }
+
+    #[test]
+    fn test_block_state_hash() {
+        let keys = keys(4);
+        let map = balances(4);
+        let block = Block::new(
+            0,
+            ValidatorId(0),
+            Height(1),
+            0,
+            &hash(&[]),
+            &Hash::zero(),
+            &map.root_hash(),
+        );
+
+        let proof = map.get_proof(&keys[2]);
+        assert_eq!(proof.get(block.state_hash(), &keys[2]), Ok(Some(20_u64)));
+    }
+}